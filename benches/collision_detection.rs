@@ -0,0 +1,152 @@
+//! Broad-phase + narrow-phase collision cost with 500+ live entities.
+//!
+//! `collision_frame` is everything one frame of play asks of collision, and is
+//! the number to hold against the 1ms budget in `README.md`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use glidewars::enemy::{EnemyManager, EnemyType};
+use glidewars::events::{EventBus, HitSource};
+use glidewars::player::Player;
+use glidewars::terrain::{Obstacle, ObstacleType, TerrainManager};
+use macroquad::prelude::*;
+
+const ENTITY_COUNTS: [usize; 3] = [100, 500, 1000];
+
+/// Obstacles spread along the course, two units apart
+fn populated_terrain(count: usize) -> TerrainManager {
+    let mut terrain = TerrainManager::new();
+    for i in 0..count {
+        let obstacle_type = match i % 4 {
+            0 => ObstacleType::Mountain,
+            1 => ObstacleType::Canyon,
+            2 => ObstacleType::Boulder,
+            _ => ObstacleType::WindTurbine,
+        };
+        terrain.add_obstacle(Obstacle {
+            position: vec3((i % 7) as f32 * 2.0 - 7.0, (i % 3) as f32, i as f32 * 2.0),
+            size: vec3(1.5, 3.0, 1.5),
+            obstacle_type,
+            blade_angle: i as f32,
+            closest_approach: f32::MAX,
+        });
+    }
+    terrain
+}
+
+fn populated_enemies(count: usize) -> EnemyManager {
+    let mut enemies = EnemyManager::new();
+    for i in 0..count {
+        let enemy_type = if i % 2 == 0 { EnemyType::Drone } else { EnemyType::Seeker };
+        enemies.spawn_enemy_at(
+            vec3((i % 7) as f32 * 2.0 - 7.0, (i % 5) as f32, i as f32 * 2.0),
+            enemy_type,
+        );
+    }
+    enemies
+}
+
+fn bench_terrain_collision(c: &mut Criterion) {
+    let mut group = c.benchmark_group("terrain_collision");
+    for count in ENTITY_COUNTS {
+        let terrain = populated_terrain(count);
+        let mut player = Player::new();
+        player.set_position(vec3(0.5, 8.0, count as f32));
+
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| black_box(terrain.check_collision(&player)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_enemy_collision(c: &mut Criterion) {
+    let mut group = c.benchmark_group("enemy_collision");
+    for count in ENTITY_COUNTS {
+        let enemies = populated_enemies(count);
+        let mut player = Player::new();
+        player.set_position(vec3(0.5, 8.0, count as f32));
+
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| black_box(enemies.check_collision(&player)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_projectile_sweeps(c: &mut Criterion) {
+    let mut group = c.benchmark_group("projectile_sweeps_x50");
+    for count in ENTITY_COUNTS {
+        let mut enemies = populated_enemies(count);
+        let mut events = EventBus::new();
+
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| {
+                // Projectiles fly high above the enemies so nothing is destroyed between iterations
+                for i in 0..50 {
+                    let z = i as f32 * (count as f32 * 2.0 / 50.0);
+                    black_box(enemies.check_projectile_hit(
+                        vec3(0.0, 20.0, z),
+                        vec3(0.0, 20.0, z + 0.5),
+                        HitSource::Player,
+                        &mut events,
+                    ));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_broad_phase_rebuild(c: &mut Criterion) {
+    let mut group = c.benchmark_group("broad_phase_rebuild");
+    for count in ENTITY_COUNTS {
+        let mut terrain = populated_terrain(count);
+
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| terrain.rebuild_grid())
+        });
+    }
+    group.finish();
+}
+
+fn bench_collision_frame(c: &mut Criterion) {
+    // Both grids rebuilt, the player checked against terrain and enemies, and a
+    // volley of 50 projectiles swept through the swarm
+    let mut group = c.benchmark_group("collision_frame");
+    for count in ENTITY_COUNTS {
+        let mut terrain = populated_terrain(count);
+        let mut enemies = populated_enemies(count);
+        let mut events = EventBus::new();
+        let mut player = Player::new();
+        player.set_position(vec3(0.5, 8.0, count as f32));
+
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| {
+                terrain.rebuild_grid();
+                enemies.rebuild_grid();
+                black_box(terrain.check_collision(&player));
+                black_box(enemies.check_collision(&player));
+                for i in 0..50 {
+                    let z = i as f32 * (count as f32 * 2.0 / 50.0);
+                    black_box(enemies.check_projectile_hit(
+                        vec3(0.0, 20.0, z),
+                        vec3(0.0, 20.0, z + 0.5),
+                        HitSource::Player,
+                        &mut events,
+                    ));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_terrain_collision,
+    bench_enemy_collision,
+    bench_projectile_sweeps,
+    bench_broad_phase_rebuild,
+    bench_collision_frame
+);
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+//! Enemy movement plus projectile resolution for large swarms.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use glidewars::enemy::{EnemyManager, EnemyType};
+use glidewars::events::EventBus;
+use glidewars::player::{Player, Weapon};
+use macroquad::prelude::*;
+
+fn swarm(count: usize) -> EnemyManager {
+    let mut enemies = EnemyManager::new();
+    for i in 0..count {
+        let enemy_type = match i % 4 {
+            0 => EnemyType::Drone,
+            1 => EnemyType::Seeker,
+            2 => EnemyType::Zigzag,
+            _ => EnemyType::Turret,
+        };
+        enemies.spawn_enemy_at(
+            vec3((i % 7) as f32 * 2.0 - 7.0, (i % 5) as f32, 20.0 + i as f32 * 0.5),
+            enemy_type,
+        );
+    }
+    enemies
+}
+
+/// Player with a volley of projectiles in flight along the swarm
+fn firing_player() -> Player {
+    let mut player = Player::new();
+    player.pickup_weapon(Weapon::Laser);
+    for i in 0..30 {
+        // Lasers leave one unit ahead of the glider
+        player.set_position(vec3((i % 7) as f32 * 2.0 - 7.0, 12.0, 19.0 + i as f32 * 10.0));
+        player.shoot();
+    }
+    player.set_position(vec3(0.0, 2.0, 0.0));
+    player
+}
+
+fn bench_enemy_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("enemy_update");
+    let mut player = firing_player();
+    for count in [50, 500, 1000] {
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, &count| {
+            b.iter_batched(
+                || swarm(count),
+                |mut enemies| {
+                    enemies.update(1.0 / 60.0, &mut player, &mut EventBus::new());
+                    enemies
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_enemy_update);
+criterion_main!(benches);
@@ -0,0 +1,47 @@
+//! Obstacle spawning, cleanup and grid maintenance while flying a course.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use glidewars::player::Player;
+use glidewars::terrain::{Obstacle, ObstacleType, TerrainManager};
+use macroquad::prelude::*;
+
+fn bench_course_flight(c: &mut Criterion) {
+    c.bench_function("terrain_update_1000_units", |b| {
+        b.iter(|| {
+            let mut terrain = TerrainManager::new();
+            let mut player = Player::new();
+
+            // Fly 1000 units at boost speed, one 60 FPS frame at a time
+            let mut z = 0.0;
+            while z < 1000.0 {
+                z += 15.0 / 60.0;
+                player.set_position(vec3(0.0, 2.0, z));
+                terrain.update(1.0 / 60.0, &player);
+            }
+            black_box(terrain.get_obstacles().len())
+        })
+    });
+}
+
+fn bench_dense_frame(c: &mut Criterion) {
+    // A single frame with 500 live obstacles: blade spin, cleanup and grid rebuild
+    let mut terrain = TerrainManager::new();
+    for i in 0..500 {
+        terrain.add_obstacle(Obstacle {
+            position: vec3((i % 7) as f32 * 2.0 - 7.0, 0.0, 40.0 + i as f32 * 0.1),
+            size: vec3(0.5, 4.0, 0.5),
+            obstacle_type: ObstacleType::WindTurbine,
+            blade_angle: 0.0,
+            closest_approach: f32::MAX,
+        });
+    }
+    let mut player = Player::new();
+    player.set_position(vec3(0.0, 2.0, 10.0));
+
+    c.bench_function("terrain_update_500_obstacles", |b| {
+        b.iter(|| terrain.update(1.0 / 60.0, &player))
+    });
+}
+
+criterion_group!(benches, bench_course_flight, bench_dense_frame);
+criterion_main!(benches);
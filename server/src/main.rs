@@ -0,0 +1,397 @@
+//! Reference online leaderboard server for Glide Wars.
+//!
+//! A small self-hostable HTTP service that keeps every submitted run in
+//! `scores.json` (plus `replays/`) under its data directory. The API is described
+//! in `src/leaderboard_api.rs`, which the game and this server share. Each run is
+//! played back from its replay before it's accepted (see `verify`); requests are
+//! answered by a few workers, so one run being checked doesn't hold up the rest.
+//!
+//! ```text
+//! glidewars-leaderboard [--port 8080] [--data ./leaderboard-data]
+//! ```
+//!
+//! `GLIDEWARS_LEADERBOARD_PORT` and `GLIDEWARS_LEADERBOARD_DATA` set the same
+//! things from the environment (used by `docker-compose.yml`).
+
+mod store;
+mod verify;
+
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tiny_http::{Header, Method, Request, Response, Server};
+use glidewars::leaderboard_api::{ErrorResponse, ScoreSubmission, SubmitResponse, MAX_LIMIT, MAX_REPLAY_LEN, SCORES_PATH};
+use store::ScoreStore;
+
+/// Largest request body read; anything bigger can't be a valid submission
+const MAX_BODY: u64 = MAX_REPLAY_LEN as u64 + 4096;
+
+/// Answers `ok` for container health checks
+const HEALTH_PATH: &str = "/health";
+
+/// Entries returned when a fetch doesn't give a limit
+const DEFAULT_LIMIT: usize = 10;
+
+/// Threads answering requests, and so how many runs can be checked at once
+const WORKERS: usize = 4;
+
+struct Config {
+    port: u16,
+    data_dir: PathBuf,
+}
+
+impl Config {
+    /// Defaults, then the environment, then command-line flags
+    fn load() -> Result<Self, String> {
+        let mut config = Config {
+            port: 8080,
+            data_dir: PathBuf::from("leaderboard-data"),
+        };
+        if let Ok(port) = std::env::var("GLIDEWARS_LEADERBOARD_PORT") {
+            config.port = port.parse().map_err(|_| format!("invalid port {:?}", port))?;
+        }
+        if let Ok(dir) = std::env::var("GLIDEWARS_LEADERBOARD_DATA") {
+            config.data_dir = PathBuf::from(dir);
+        }
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+            match arg.as_str() {
+                "--port" => config.port = value.parse().map_err(|_| format!("invalid port {:?}", value))?,
+                "--data" => config.data_dir = PathBuf::from(value),
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Status code and JSON body to answer with
+#[derive(Debug)]
+struct Reply {
+    status: u16,
+    body: String,
+}
+
+impl Reply {
+    fn json<T: serde::Serialize>(status: u16, value: &T) -> Self {
+        Self { status, body: serde_json::to_string(value).expect("API types always serialize") }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::json(status, &ErrorResponse { error: message.into() })
+    }
+}
+
+/// Answer one request; `now` is the Unix time new runs are dated with. The
+/// store is only locked once a submission has been checked.
+fn handle(store: &Mutex<ScoreStore>, method: &Method, url: &str, body: &str, now: u64) -> Reply {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let params = parse_query(query);
+    let param = |key: &str| params.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+
+    match (method, path) {
+        (Method::Get, HEALTH_PATH) => Reply { status: 200, body: "\"ok\"".to_string() },
+        (Method::Post, SCORES_PATH) => {
+            let submission: ScoreSubmission = match serde_json::from_str(body) {
+                Ok(submission) => submission,
+                Err(e) => return Reply::error(400, format!("invalid submission: {}", e)),
+            };
+            if let Err(e) = submission.validate().and_then(|()| verify::verify(&submission, verify::TIME_LIMIT)) {
+                return Reply::error(400, e);
+            }
+            match lock(store).submit(submission, now) {
+                Ok(rank) => Reply::json(200, &SubmitResponse { rank }),
+                Err(e) => {
+                    eprintln!("Failed to store score: {}", e);
+                    Reply::error(500, "couldn't store the score")
+                }
+            }
+        }
+        (Method::Get, SCORES_PATH) => {
+            let (Some(continent), Some(difficulty)) = (param("continent"), param("difficulty")) else {
+                return Reply::error(400, "continent and difficulty are required");
+            };
+            let limit = match param("limit").map(str::parse::<usize>) {
+                None => DEFAULT_LIMIT,
+                Some(Ok(limit)) => limit.min(MAX_LIMIT),
+                Some(Err(_)) => return Reply::error(400, "invalid limit"),
+            };
+            let store = lock(store);
+            let entries = match param("around") {
+                Some(name) => store.around(continent, difficulty, name, limit),
+                None => store.top(continent, difficulty, limit),
+            };
+            Reply::json(200, &entries)
+        }
+        (_, HEALTH_PATH | SCORES_PATH) => Reply::error(405, "method not allowed"),
+        _ => Reply::error(404, "not found"),
+    }
+}
+
+/// The store, still usable if another worker panicked while holding it
+fn lock(store: &Mutex<ScoreStore>) -> std::sync::MutexGuard<'_, ScoreStore> {
+    store.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Read a request, answer it and send the reply
+fn respond(mut request: Request, store: &Mutex<ScoreStore>) {
+    let mut body = String::new();
+    let reply = match request.as_reader().take(MAX_BODY).read_to_string(&mut body) {
+        Ok(_) => handle(store, request.method(), request.url(), &body, now()),
+        Err(_) => Reply::error(400, "body is not UTF-8 text"),
+    };
+
+    let response = Response::from_string(reply.body)
+        .with_status_code(reply.status)
+        .with_header(Header::from_bytes("Content-Type", "application/json").expect("valid header"))
+        .with_header(Header::from_bytes("Access-Control-Allow-Origin", "*").expect("valid header"));
+    if let Err(e) = request.respond(response) {
+        eprintln!("Failed to send response: {}", e);
+    }
+}
+
+/// `a=1&b=two%20words` as decoded pairs
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() && bytes[i + 1].is_ascii_hexdigit() && bytes[i + 2].is_ascii_hexdigit() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).expect("hex digits are ASCII");
+                decoded.push(u8::from_str_radix(hex, 16).expect("checked hex digits"));
+                i += 2;
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+fn main() {
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("usage: glidewars-leaderboard [--port PORT] [--data DIR]");
+            std::process::exit(2);
+        }
+    };
+
+    let store = match ScoreStore::open(config.data_dir.clone()) {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("Couldn't open scores in {}: {}", config.data_dir.display(), e);
+            std::process::exit(1);
+        }
+    };
+
+    let server = match Server::http(("0.0.0.0", config.port)) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("Couldn't listen on port {}: {}", config.port, e);
+            std::process::exit(1);
+        }
+    };
+    println!("Leaderboard listening on port {}, data in {}", config.port, config.data_dir.display());
+
+    let server = Arc::new(server);
+    let store = Arc::new(Mutex::new(store));
+    let workers: Vec<_> = (0..WORKERS)
+        .map(|_| {
+            let (server, store) = (Arc::clone(&server), Arc::clone(&store));
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    respond(request, &store);
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        if worker.join().is_err() {
+            eprintln!("A request worker panicked");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::OnceLock;
+    use std::time::Duration;
+    use glidewars::assets::Continent;
+    use glidewars::difficulty::DifficultySetting;
+    use glidewars::events::EventBus;
+    use glidewars::game_mode::GameMode;
+    use glidewars::leaderboard_api::RankedEntry;
+    use glidewars::player::PlayerControls;
+    use glidewars::replay::Replay;
+    use glidewars::simulation::{Phase, Simulation};
+
+    /// The tutorial flown to the end by ACE, weaving from side to side with the
+    /// guns firing and respawning whenever it's shot down, submitted with the
+    /// score and time it earned
+    fn fly_tutorial(seed: u64) -> ScoreSubmission {
+        let mut sim = Simulation::new(Continent::Tutorial, GameMode::Standard, DifficultySetting::Easy, false, seed);
+        sim.set_player("ACE");
+        let mut events = EventBus::new();
+        let mut tick = 0;
+        loop {
+            match sim.phase() {
+                Phase::Flying | Phase::BossFight => {
+                    let position = sim.player.position();
+                    let going_left = (tick / 40) % 2 == 0;
+                    let controls = PlayerControls {
+                        left: going_left && position.x > -15.0,
+                        right: !going_left && position.x < 15.0,
+                        up: position.y < 15.0,
+                        shoot: true,
+                        ..Default::default()
+                    };
+                    sim.step(controls, &mut events);
+                    events.clear();
+                    tick += 1;
+                }
+                Phase::Respawning => sim.respawn(),
+                Phase::GameOver => assert!(sim.use_continue(), "test run ran out of continues"),
+                Phase::Complete => break,
+            }
+        }
+        ScoreSubmission {
+            continent: "Tutorial".to_string(),
+            difficulty: "Easy".to_string(),
+            name: "ACE".to_string(),
+            score: sim.score.total(),
+            time: sim.level.elapsed_time(),
+            seed,
+            replay: sim.replay().map(Replay::to_text),
+        }
+    }
+
+    /// Two finished runs, lower score first (flown once and shared between tests)
+    fn runs() -> &'static [ScoreSubmission; 2] {
+        static RUNS: OnceLock<[ScoreSubmission; 2]> = OnceLock::new();
+        RUNS.get_or_init(|| {
+            let mut runs = [fly_tutorial(99), fly_tutorial(100)];
+            runs.sort_by_key(|run| run.score);
+            assert!(runs[0].score < runs[1].score);
+            runs
+        })
+    }
+
+    /// `run` as if `name` had flown it
+    fn submission(name: &str, run: &ScoreSubmission) -> String {
+        let mut replay = Replay::from_text(run.replay.as_ref().unwrap()).unwrap();
+        replay.player = name.to_string();
+        let run = ScoreSubmission { name: name.to_string(), replay: Some(replay.to_text()), ..run.clone() };
+        serde_json::to_string(&run).unwrap()
+    }
+
+    #[test]
+    fn test_submit_then_fetch() {
+        let [low, high] = runs();
+        let store = Mutex::new(ScoreStore::in_memory());
+        let reply = handle(&store, &Method::Post, "/scores", &submission("ACE", low), 1_792_281_600);
+        assert_eq!(reply.status, 200, "{}", reply.body);
+        assert_eq!(serde_json::from_str::<SubmitResponse>(&reply.body).unwrap().rank, 1);
+        handle(&store, &Method::Post, "/scores", &submission("BOB", high), 0);
+
+        let reply = handle(&store, &Method::Get, "/scores?continent=Tutorial&difficulty=Easy&limit=1", "", 0);
+        let entries: Vec<RankedEntry> = serde_json::from_str(&reply.body).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!((entries[0].rank, entries[0].name.as_str()), (1, "BOB"));
+
+        let reply = handle(&store, &Method::Get, "/scores?continent=Tutorial&difficulty=Easy&around=ACE", "", 0);
+        let entries: Vec<RankedEntry> = serde_json::from_str(&reply.body).unwrap();
+        assert_eq!(entries.iter().map(|e| e.rank).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(entries[1].date, 1_792_281_600);
+        assert!(entries[1].has_replay);
+    }
+
+    #[test]
+    fn test_bad_requests_rejected() {
+        let run = &runs()[0];
+        let store = Mutex::new(ScoreStore::in_memory());
+        assert_eq!(handle(&store, &Method::Post, "/scores", "{", 0).status, 400);
+        assert_eq!(handle(&store, &Method::Post, "/scores", &submission("", run), 0).status, 400);
+        assert_eq!(handle(&store, &Method::Post, "/scores", &submission("WAYTOOLONGNAME", run), 0).status, 400);
+        assert_eq!(handle(&store, &Method::Get, "/scores?continent=Europe", "", 0).status, 400);
+        assert_eq!(handle(&store, &Method::Get, "/scores?continent=Europe&difficulty=Easy&limit=x", "", 0).status, 400);
+        assert_eq!(handle(&store, &Method::Delete, "/scores", "", 0).status, 405);
+        assert_eq!(handle(&store, &Method::Get, "/elsewhere", "", 0).status, 404);
+        assert!(lock(&store).top("Tutorial", "Easy", 10).is_empty());
+    }
+
+    #[test]
+    fn test_runs_that_dont_match_their_replay_rejected() {
+        let run = &runs()[0];
+        let store = Mutex::new(ScoreStore::in_memory());
+        let reject = |tampered: ScoreSubmission, reason: &str| {
+            let reply = handle(&store, &Method::Post, "/scores", &serde_json::to_string(&tampered).unwrap(), 0);
+            assert_eq!(reply.status, 400);
+            let error: ErrorResponse = serde_json::from_str(&reply.body).unwrap();
+            assert!(error.error.contains(reason), "{:?} should mention {:?}", error.error, reason);
+        };
+
+        reject(ScoreSubmission { score: run.score + 1, ..run.clone() }, "scores");
+        reject(ScoreSubmission { time: run.time - 1.0, ..run.clone() }, "takes");
+        reject(ScoreSubmission { seed: run.seed + 1, ..run.clone() }, "seed");
+        reject(ScoreSubmission { continent: "Europe".to_string(), ..run.clone() }, "Tutorial");
+        reject(ScoreSubmission { name: "BOB".to_string(), ..run.clone() }, "flown by");
+        reject(ScoreSubmission { replay: None, ..run.clone() }, "required");
+        reject(ScoreSubmission { replay: Some("hello".to_string()), ..run.clone() }, "invalid replay");
+
+        // Replays that stop short, carry on past the end or run for days
+        let replay = run.replay.as_ref().unwrap();
+        let header: String = replay.lines().take(2).map(|line| format!("{}\n", line)).collect();
+        reject(ScoreSubmission { replay: Some(format!("{}00*60", header)), ..run.clone() }, "doesn't finish");
+        reject(ScoreSubmission { replay: Some(format!("{} 00", replay)), ..run.clone() }, "doesn't play back");
+        reject(ScoreSubmission { replay: Some(format!("{}00*4000000000", header)), ..run.clone() }, "too long");
+
+        // A time attack or endless run with the same inputs
+        let time_attack = replay.replacen(&format!("{} 0 ", run.seed), &format!("{} 1 ", run.seed), 1);
+        assert_ne!(&time_attack, replay);
+        reject(ScoreSubmission { replay: Some(time_attack), ..run.clone() }, "Time Attack");
+        let endless = replay.replacen(&format!("{} 0 ", run.seed), &format!("{} 2 ", run.seed), 1);
+        reject(ScoreSubmission { replay: Some(endless), ..run.clone() }, "Endless");
+
+        assert!(lock(&store).top("Tutorial", "Easy", 10).is_empty());
+        assert_eq!(verify::verify(run, verify::TIME_LIMIT), Ok(()));
+    }
+
+    #[test]
+    fn test_replays_that_play_too_long_rejected() {
+        let run = &runs()[0];
+        let error = verify::verify(run, Duration::ZERO).unwrap_err();
+        assert!(error.contains("took too long"), "{:?}", error);
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("North%20America"), "North America");
+        assert_eq!(percent_decode("a+b"), "a b");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+}
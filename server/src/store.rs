@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use glidewars::leaderboard_api::{RankedEntry, ScoreSubmission};
+
+/// A submitted run as kept on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredScore {
+    name: String,
+    score: u32,
+    time: f32,
+    date: u64,
+    seed: u64,
+    replay: Option<String>, // File name under `replays/`
+}
+
+impl StoredScore {
+    /// Higher score, then faster time; equal runs keep submission order
+    fn beats(&self, other: &StoredScore) -> bool {
+        self.score > other.score || (self.score == other.score && self.time < other.time)
+    }
+
+    fn ranked(&self, index: usize) -> RankedEntry {
+        RankedEntry {
+            rank: index + 1,
+            name: self.name.clone(),
+            score: self.score,
+            time: self.time,
+            date: self.date,
+            seed: self.seed,
+            has_replay: self.replay.is_some(),
+        }
+    }
+}
+
+/// `scores.json`
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ScoreFile {
+    boards: HashMap<String, Vec<StoredScore>>, // By "<continent>/<difficulty>", best first
+    next_replay: u64,
+}
+
+/// Every run submitted, per board. Unlike the game's local boards nothing is
+/// dropped, so any player can be found for "around me".
+pub struct ScoreStore {
+    dir: Option<PathBuf>, // None keeps everything in memory
+    file: ScoreFile,
+}
+
+impl ScoreStore {
+    /// Open the scores kept in `dir`, creating it if needed
+    pub fn open(dir: PathBuf) -> io::Result<Self> {
+        fs::create_dir_all(dir.join("replays"))?;
+        let file = match fs::read_to_string(dir.join("scores.json")) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => ScoreFile::default(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self { dir: Some(dir), file })
+    }
+
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        Self { dir: None, file: ScoreFile::default() }
+    }
+
+    fn board_key(continent: &str, difficulty: &str) -> String {
+        format!("{}/{}", continent, difficulty)
+    }
+
+    /// Record a validated run received at `date`, returning its rank (1 is first)
+    pub fn submit(&mut self, submission: ScoreSubmission, date: u64) -> io::Result<usize> {
+        let replay = match (&submission.replay, &self.dir) {
+            (Some(replay), Some(dir)) => {
+                let file_name = format!("{}.txt", self.file.next_replay);
+                fs::write(dir.join("replays").join(&file_name), replay)?;
+                self.file.next_replay += 1;
+                Some(file_name)
+            }
+            (Some(_), None) => Some(String::new()),
+            (None, _) => None,
+        };
+
+        let stored = StoredScore {
+            name: submission.name.trim().to_string(),
+            score: submission.score,
+            time: submission.time,
+            date,
+            seed: submission.seed,
+            replay,
+        };
+        let board = self.file.boards.entry(Self::board_key(&submission.continent, &submission.difficulty)).or_default();
+        let index = board.iter().position(|other| stored.beats(other)).unwrap_or(board.len());
+        board.insert(index, stored);
+
+        self.write()?;
+        Ok(index + 1)
+    }
+
+    /// The best `limit` runs on a board
+    pub fn top(&self, continent: &str, difficulty: &str, limit: usize) -> Vec<RankedEntry> {
+        self.board(continent, difficulty)
+            .iter()
+            .take(limit)
+            .enumerate()
+            .map(|(i, score)| score.ranked(i))
+            .collect()
+    }
+
+    /// `limit` runs centred on `name`'s best, or none if they have no run on the board
+    pub fn around(&self, continent: &str, difficulty: &str, name: &str, limit: usize) -> Vec<RankedEntry> {
+        let board = self.board(continent, difficulty);
+        let Some(best) = board.iter().position(|score| score.name == name) else {
+            return Vec::new();
+        };
+        let start = best.saturating_sub(limit / 2).min(board.len().saturating_sub(limit));
+        board
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(limit)
+            .map(|(i, score)| score.ranked(i))
+            .collect()
+    }
+
+    fn board(&self, continent: &str, difficulty: &str) -> &[StoredScore] {
+        self.file
+            .boards
+            .get(&Self::board_key(continent, difficulty))
+            .map_or(&[], |board| board.as_slice())
+    }
+
+    /// Replace `scores.json` in one step so a crash never leaves half a file
+    fn write(&self) -> io::Result<()> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        let json = serde_json::to_string(&self.file).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let tmp = dir.join("scores.json.tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(tmp, dir.join("scores.json"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(name: &str, score: u32) -> ScoreSubmission {
+        ScoreSubmission {
+            continent: "Europe".to_string(),
+            difficulty: "Normal".to_string(),
+            name: name.to_string(),
+            score,
+            time: 200.0,
+            seed: 7,
+            replay: Some("replay".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_around_centres_on_best_run() {
+        let mut store = ScoreStore::in_memory();
+        for i in 0..10 {
+            store.submit(run(&format!("P{}", i), 1000 - i * 10), 0).unwrap();
+        }
+        store.submit(run("P5", 100), 0).unwrap(); // A worse second run
+
+        let around: Vec<usize> = store.around("Europe", "Normal", "P5", 3).iter().map(|e| e.rank).collect();
+        assert_eq!(around, vec![5, 6, 7]);
+
+        // Clamped at the ends of the board
+        let around: Vec<usize> = store.around("Europe", "Normal", "P0", 3).iter().map(|e| e.rank).collect();
+        assert_eq!(around, vec![1, 2, 3]);
+        assert!(store.around("Europe", "Normal", "NOBODY", 3).is_empty());
+        assert!(store.top("Europe", "Hard", 10).is_empty());
+    }
+
+    #[test]
+    fn test_scores_and_replays_persist() {
+        let dir = std::env::temp_dir().join(format!("glidewars_leaderboard_server_{}", std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+
+        let mut store = ScoreStore::open(dir.clone()).unwrap();
+        assert_eq!(store.submit(run("ACE", 500), 1_000).unwrap(), 1);
+        assert_eq!(store.submit(run("BOB", 900), 2_000).unwrap(), 1);
+
+        let reopened = ScoreStore::open(dir.clone()).unwrap();
+        let top = reopened.top("Europe", "Normal", 10);
+        assert_eq!(top.iter().map(|e| e.name.as_str()).collect::<Vec<_>>(), vec!["BOB", "ACE"]);
+        assert!(top[0].has_replay);
+        assert_eq!(fs::read_to_string(dir.join("replays/0.txt")).unwrap(), "replay");
+    }
+}
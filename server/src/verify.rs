@@ -0,0 +1,86 @@
+//! Checking a submitted run by playing its replay through the game's simulation.
+//!
+//! Every tick of the run is simulated again from its seed, so a submission is
+//! only accepted if the inputs it came with really do finish the level with the
+//! score and time it claims, under the name of the profile that flew it.
+
+use glidewars::game_mode::GameMode;
+use glidewars::leaderboard_api::ScoreSubmission;
+use glidewars::replay::Replay;
+use glidewars::simulation::{Phase, Simulation};
+use std::time::{Duration, Instant};
+
+/// Longest run simulated: an hour of ticks, far more than a level takes even
+/// with every respawn and continue
+pub const MAX_TICKS: u32 = 60 * 60 * 60;
+
+/// Longest a replay is simulated for before it's turned away, so a request
+/// worker is never tied up for long however slow the machine
+pub const TIME_LIMIT: Duration = Duration::from_secs(10);
+
+/// Largest difference between a submitted time and the replayed one, in seconds
+const TIME_TOLERANCE: f32 = 0.001;
+
+/// How a replayed run ended
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    pub continent: String,
+    pub difficulty: String,
+    pub seed: u64,
+    pub finished: bool, // Whether it completed the level
+    pub score: u32,
+    pub time: f32,
+}
+
+/// Play a replay through the simulation, giving up at `deadline`
+pub fn outcome(replay: &Replay, deadline: Instant) -> Result<Outcome, String> {
+    if replay.ticks() > MAX_TICKS {
+        return Err("replay is too long".to_string());
+    }
+
+    let sim = Simulation::play_until(replay, deadline).map_err(|e| format!("replay doesn't play back: {}", e))?;
+    Ok(Outcome {
+        continent: replay.continent.name().to_string(),
+        difficulty: replay.difficulty.name().to_string(),
+        seed: replay.seed,
+        finished: sim.phase() == Phase::Complete,
+        score: sim.score.total(),
+        time: sim.level.elapsed_time(),
+    })
+}
+
+/// Why a submission doesn't match its own replay, if it doesn't. Replaying it
+/// stops after `limit`.
+pub fn verify(submission: &ScoreSubmission, limit: Duration) -> Result<(), String> {
+    let deadline = Instant::now() + limit;
+    let replay = submission.replay.as_deref().ok_or("a replay is required")?;
+    let replay = Replay::from_text(replay).map_err(|e| format!("invalid replay: {}", e))?;
+
+    // The boards rank standard runs; a time attack is judged on its time alone,
+    // and endless runs have boards of their own on each player's machine
+    if replay.mode != GameMode::Standard {
+        return Err(format!("{} runs aren't ranked online", replay.mode.name()));
+    }
+    // A replay goes up under the profile it was recorded by, not whoever sends it
+    if replay.player != submission.name.trim() {
+        return Err(format!("replay was flown by {:?}", replay.player));
+    }
+    let outcome = outcome(&replay, deadline)?;
+
+    if outcome.continent != submission.continent || outcome.difficulty != submission.difficulty {
+        return Err(format!("replay is of {} on {}", outcome.continent, outcome.difficulty));
+    }
+    if outcome.seed != submission.seed {
+        return Err("replay seed doesn't match".to_string());
+    }
+    if !outcome.finished {
+        return Err("replay doesn't finish the level".to_string());
+    }
+    if outcome.score != submission.score {
+        return Err(format!("replay scores {}, not {}", outcome.score, submission.score));
+    }
+    if (outcome.time - submission.time).abs() > TIME_TOLERANCE {
+        return Err(format!("replay takes {:.3}s, not {:.3}s", outcome.time, submission.time));
+    }
+    Ok(())
+}
@@ -0,0 +1,256 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::assets::Continent;
+use crate::boss::BossType;
+use crate::events::{EventListener, GameEvent};
+use crate::player::Weapon;
+
+/// Running totals kept across levels for `Condition::Lifetime`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Counter {
+    EnemyKills,
+    PowerupsCollected,
+}
+
+impl Counter {
+    /// Key in the saved counters map
+    fn key(&self) -> &'static str {
+        match self {
+            Counter::EnemyKills => "enemy_kills",
+            Counter::PowerupsCollected => "powerups_collected",
+        }
+    }
+}
+
+/// What has to happen for an achievement to unlock
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Condition {
+    /// Finish a level (any continent when `None`), optionally without taking a hit
+    ClearLevel { continent: Option<Continent>, undamaged: bool },
+    /// Reach this chain length in a ring course
+    RingChain(u32),
+    PerfectCourse,
+    /// Defeat a boss (any when `None`), optionally landing every hit with one weapon
+    DefeatBoss { boss: Option<BossType>, only_weapon: Option<Weapon> },
+    NearMissesInLevel(u32),
+    Lifetime(Counter, u32),
+}
+
+/// One row of the achievements table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Achievement {
+    pub id: &'static str, // Stored in the save; never rename
+    pub name: &'static str,
+    pub description: &'static str,
+    pub condition: Condition,
+}
+
+pub const ACHIEVEMENTS: [Achievement; 10] = [
+    Achievement { id: "first_flight", name: "FIRST FLIGHT", description: "Complete the tutorial", condition: Condition::ClearLevel { continent: Some(Continent::Tutorial), undamaged: false } },
+    Achievement { id: "untouchable_europe", name: "UNTOUCHABLE", description: "Clear Europe without taking damage", condition: Condition::ClearLevel { continent: Some(Continent::Europe), undamaged: true } },
+    Achievement { id: "flawless", name: "FLAWLESS", description: "Clear any continent without taking damage", condition: Condition::ClearLevel { continent: None, undamaged: true } },
+    Achievement { id: "ring_chain_10", name: "THREAD THE NEEDLE", description: "Chain 10 rings", condition: Condition::RingChain(10) },
+    Achievement { id: "perfect_course", name: "PERFECT COURSE", description: "Fly through every gate of a ring course", condition: Condition::PerfectCourse },
+    Achievement { id: "giant_killer", name: "GIANT KILLER", description: "Defeat a boss", condition: Condition::DefeatBoss { boss: None, only_weapon: None } },
+    Achievement { id: "laser_dragon", name: "DRAGON SLAYER", description: "Defeat the Dragon Kite with the laser only", condition: Condition::DefeatBoss { boss: Some(BossType::DragonKite), only_weapon: Some(Weapon::Laser) } },
+    Achievement { id: "daredevil", name: "DAREDEVIL", description: "10 near misses in one level", condition: Condition::NearMissesInLevel(10) },
+    Achievement { id: "ace", name: "ACE", description: "Destroy 100 enemies", condition: Condition::Lifetime(Counter::EnemyKills, 100) },
+    Achievement { id: "collector", name: "COLLECTOR", description: "Collect 50 powerups", condition: Condition::Lifetime(Counter::PowerupsCollected, 50) },
+];
+
+/// What's happened so far in the level being played
+#[derive(Debug, Clone, Default)]
+struct LevelRun {
+    continent: Option<Continent>, // None outside a level
+    whole_run_seen: bool,         // False for resumed runs, whose history wasn't recorded
+    damaged: bool,
+    near_misses: u32,
+    boss_weapons: Vec<Weapon>, // Weapons that have hit the boss
+}
+
+/// A profile's unlocked achievements and lifetime counters, stored in its save.
+/// Gameplay events are checked against `ACHIEVEMENTS` as they arrive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AchievementProgress {
+    unlocked: Vec<String>,
+    counters: HashMap<String, u32>,
+    #[serde(skip)]
+    run: LevelRun,
+    #[serde(skip)]
+    newly_unlocked: Vec<&'static Achievement>,
+}
+
+impl AchievementProgress {
+    /// Begin tracking a level played from the start
+    pub fn start_level(&mut self, continent: Continent) {
+        self.run = LevelRun {
+            continent: Some(continent),
+            whole_run_seen: true,
+            ..LevelRun::default()
+        };
+    }
+
+    /// Begin tracking a resumed run. What happened before it was suspended isn't
+    /// known, so it can't count for the no-damage or single-weapon achievements.
+    pub fn resume_level(&mut self, continent: Continent) {
+        self.start_level(continent);
+        self.run.whole_run_seen = false;
+    }
+
+    pub fn is_unlocked(&self, achievement: &Achievement) -> bool {
+        self.unlocked.iter().any(|id| id == achievement.id)
+    }
+
+    pub fn unlocked_count(&self) -> usize {
+        ACHIEVEMENTS.iter().filter(|a| self.is_unlocked(a)).count()
+    }
+
+    /// Current and target count for achievements that build up over time
+    pub fn counter_progress(&self, achievement: &Achievement) -> Option<(u32, u32)> {
+        match achievement.condition {
+            Condition::Lifetime(counter, target) => Some((self.counter(counter).min(target), target)),
+            _ => None,
+        }
+    }
+
+    /// Achievements unlocked since the last call, for the unlock toasts
+    pub fn take_newly_unlocked(&mut self) -> Vec<&'static Achievement> {
+        std::mem::take(&mut self.newly_unlocked)
+    }
+
+    /// Keep every unlock from both and the higher of each counter
+    pub fn merge(&mut self, other: &AchievementProgress) {
+        for id in &other.unlocked {
+            if !self.unlocked.contains(id) {
+                self.unlocked.push(id.clone());
+            }
+        }
+        for (key, &count) in &other.counters {
+            let ours = self.counters.entry(key.clone()).or_insert(0);
+            *ours = (*ours).max(count);
+        }
+    }
+
+    fn counter(&self, counter: Counter) -> u32 {
+        self.counters.get(counter.key()).copied().unwrap_or(0)
+    }
+
+    fn bump(&mut self, counter: Counter) {
+        *self.counters.entry(counter.key().to_string()).or_insert(0) += 1;
+    }
+
+    fn is_met(&self, condition: &Condition, event: &GameEvent) -> bool {
+        let run = &self.run;
+        match (condition, event) {
+            (Condition::ClearLevel { continent, undamaged }, GameEvent::LevelCompleted { .. }) => {
+                run.continent.is_some()
+                    && continent.is_none_or(|c| run.continent == Some(c))
+                    && (!undamaged || (run.whole_run_seen && !run.damaged))
+            }
+            (Condition::RingChain(target), GameEvent::RingPassed { chain, .. }) => chain >= target,
+            (Condition::PerfectCourse, GameEvent::RingCourseCompleted { perfect, .. }) => *perfect,
+            (Condition::DefeatBoss { boss, only_weapon }, GameEvent::BossDefeated { boss_type }) => {
+                boss.is_none_or(|b| b == *boss_type)
+                    && only_weapon.is_none_or(|weapon| run.whole_run_seen && run.boss_weapons.iter().all(|w| *w == weapon))
+            }
+            (Condition::NearMissesInLevel(target), GameEvent::NearMiss { .. }) => run.near_misses >= *target,
+            (Condition::Lifetime(counter, target), _) => self.counter(*counter) >= *target,
+            _ => false,
+        }
+    }
+}
+
+impl EventListener for AchievementProgress {
+    fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::PlayerDamaged { .. } | GameEvent::PlayerDied { .. } => self.run.damaged = true,
+            GameEvent::NearMiss { .. } => self.run.near_misses += 1,
+            GameEvent::BossHit { weapon, .. } if !self.run.boss_weapons.contains(weapon) => {
+                self.run.boss_weapons.push(*weapon);
+            }
+            GameEvent::EnemyKilled { .. } => self.bump(Counter::EnemyKills),
+            GameEvent::PowerupCollected { .. } => self.bump(Counter::PowerupsCollected),
+            _ => {}
+        }
+
+        for achievement in ACHIEVEMENTS.iter() {
+            if !self.is_unlocked(achievement) && self.is_met(&achievement.condition, event) {
+                self.unlocked.push(achievement.id.to_string());
+                self.newly_unlocked.push(achievement);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glidewars::enemy::EnemyType;
+    use crate::events::{DamageSource, HitSource};
+    use macroquad::prelude::Vec3;
+
+    fn unlocked_ids(progress: &mut AchievementProgress) -> Vec<&'static str> {
+        progress.take_newly_unlocked().iter().map(|a| a.id).collect()
+    }
+
+    #[test]
+    fn test_ids_are_unique() {
+        for (i, a) in ACHIEVEMENTS.iter().enumerate() {
+            assert!(ACHIEVEMENTS[i + 1..].iter().all(|b| b.id != a.id), "duplicate id {}", a.id);
+        }
+    }
+
+    #[test]
+    fn test_undamaged_clear() {
+        let mut progress = AchievementProgress::default();
+        progress.start_level(Continent::Europe);
+        progress.on_event(&GameEvent::LevelCompleted { outlasted_boss: false });
+        assert_eq!(unlocked_ids(&mut progress), vec!["untouchable_europe", "flawless"]);
+
+        // A hit rules it out, and so does resuming a suspended run
+        let mut progress = AchievementProgress::default();
+        progress.start_level(Continent::Europe);
+        progress.on_event(&GameEvent::PlayerDamaged { amount: 10.0, source: DamageSource::BossProjectile });
+        progress.on_event(&GameEvent::LevelCompleted { outlasted_boss: false });
+        assert!(unlocked_ids(&mut progress).is_empty());
+
+        progress.resume_level(Continent::Europe);
+        progress.on_event(&GameEvent::LevelCompleted { outlasted_boss: false });
+        assert!(unlocked_ids(&mut progress).is_empty());
+    }
+
+    #[test]
+    fn test_single_weapon_boss_kill() {
+        let mut progress = AchievementProgress::default();
+        progress.start_level(Continent::Asia);
+        progress.on_event(&GameEvent::BossHit { boss_type: BossType::DragonKite, weapon: Weapon::Missile });
+        progress.on_event(&GameEvent::BossHit { boss_type: BossType::DragonKite, weapon: Weapon::Laser });
+        progress.on_event(&GameEvent::BossDefeated { boss_type: BossType::DragonKite });
+        assert_eq!(unlocked_ids(&mut progress), vec!["giant_killer"]);
+
+        progress.start_level(Continent::Asia);
+        progress.on_event(&GameEvent::BossHit { boss_type: BossType::DragonKite, weapon: Weapon::Laser });
+        progress.on_event(&GameEvent::BossDefeated { boss_type: BossType::DragonKite });
+        assert_eq!(unlocked_ids(&mut progress), vec!["laser_dragon"]);
+    }
+
+    #[test]
+    fn test_lifetime_counter_unlocks_once_and_persists() {
+        let mut progress = AchievementProgress::default();
+        let kill = GameEvent::EnemyKilled { enemy_type: EnemyType::Drone, position: Vec3::ZERO, source: HitSource::Player };
+        for _ in 0..99 {
+            progress.on_event(&kill);
+        }
+        assert_eq!(progress.counter_progress(&ACHIEVEMENTS[8]), Some((99, 100)));
+        assert!(unlocked_ids(&mut progress).is_empty());
+
+        progress.on_event(&kill);
+        progress.on_event(&kill);
+        assert_eq!(unlocked_ids(&mut progress), vec!["ace"]);
+
+        let reloaded: AchievementProgress = serde_json::from_str(&serde_json::to_string(&progress).unwrap()).unwrap();
+        assert!(reloaded.is_unlocked(&ACHIEVEMENTS[8]));
+        assert_eq!(reloaded.unlocked_count(), 1);
+    }
+}
@@ -1,9 +1,14 @@
 use macroquad::prelude::*;
-use macroquad::rand::gen_range;
+use crate::rng::gen_range;
+use serde::{Deserialize, Serialize};
 use crate::assets::Continent;
-use crate::player::Player;
+use crate::collision::Shape;
+use crate::enemy::PROJECTILE_HIT_RADIUS;
+use crate::events::{EventBus, GameEvent};
+use crate::fmath;
+use crate::player::{self, Player, Weapon};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum BossType {
     TutorialBoss,          // Simple, predictable
     MountainGuardian,      // North America
@@ -15,6 +20,18 @@ pub enum BossType {
 }
 
 impl BossType {
+    pub fn all() -> [BossType; 7] {
+        [
+            BossType::TutorialBoss,
+            BossType::MountainGuardian,
+            BossType::JungleBehemoth,
+            BossType::StormBringer,
+            BossType::DragonKite,
+            BossType::DesertPhoenix,
+            BossType::TidalWave,
+        ]
+    }
+
     pub fn from_continent(continent: Continent) -> Self {
         match continent {
             Continent::Tutorial => BossType::TutorialBoss,
@@ -99,11 +116,31 @@ pub enum AttackPattern {
 
 pub struct BossProjectile {
     pub position: Vec3,
+    pub previous_position: Vec3,
     pub velocity: Vec3,
     pub lifetime: f32,
     pub damage: f32,
 }
 
+/// Boss health, phase and position at a checkpoint (projectiles in flight aren't kept)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BossSnapshot {
+    boss_type: BossType,
+    #[serde(with = "crate::snapshot::vec3")]
+    position: Vec3,
+    health: f32,
+    max_health: f32,
+    phase: u8,
+    time_alive: f32,
+    defeated: bool,
+}
+
+impl BossSnapshot {
+    pub fn is_defeated(&self) -> bool {
+        self.defeated
+    }
+}
+
 pub struct Boss {
     boss_type: BossType,
     pub position: Vec3,
@@ -147,25 +184,17 @@ impl Boss {
         self.time_alive += dt;
         self.attack_timer += dt;
 
-        // Update phase based on health
-        let health_percent = self.health / self.max_health;
-        self.phase = if health_percent > 0.66 {
-            1
-        } else if health_percent > 0.33 {
-            2
-        } else {
-            3
-        };
+        self.update_phase();
 
         // Boss movement - circular pattern around player, but stays ahead
         let angle = self.time_alive * 0.5;
         let radius = 15.0;
-        let target_x = player_pos.x + angle.cos() * radius;
-        let target_y = player_pos.y + 3.0 + (self.time_alive * 0.3).sin() * 2.0;
+        let target_x = player_pos.x + fmath::cos(angle) * radius;
+        let target_y = player_pos.y + 3.0 + fmath::sin(self.time_alive * 0.3) * 2.0;
 
         // Move toward target X/Y position
         let target_pos_xy = vec3(target_x, target_y, self.position.z);
-        let direction_xy = (target_pos_xy - self.position).normalize_or_zero();
+        let direction_xy = fmath::normalize_or_zero(target_pos_xy - self.position);
 
         // Horizontal movement (X and Y)
         self.velocity.x = direction_xy.x * 5.0;
@@ -204,6 +233,7 @@ impl Boss {
 
         // Update projectiles
         self.projectiles.retain_mut(|proj| {
+            proj.previous_position = proj.position;
             proj.position += proj.velocity * dt;
             proj.lifetime -= dt;
             proj.lifetime > 0.0
@@ -261,15 +291,16 @@ impl Boss {
 
         for i in 0..num_projectiles {
             let spread = (i as f32 - num_projectiles as f32 / 2.0) * 0.3;
-            let direction = (player_pos - self.position).normalize();
-            let velocity = vec3(
+            let direction = fmath::normalize(player_pos - self.position);
+            let velocity = fmath::normalize(vec3(
                 direction.x + spread,
                 direction.y + gen_range(-0.2, 0.2),
                 direction.z,
-            ).normalize() * 15.0;
+            )) * 15.0;
 
             self.projectiles.push(BossProjectile {
                 position: self.position,
+                previous_position: self.position,
                 velocity,
                 lifetime: 5.0,
                 damage: 15.0,
@@ -284,13 +315,14 @@ impl Boss {
         for i in 0..num_projectiles {
             let angle = (i as f32 / num_projectiles as f32) * std::f32::consts::PI * 2.0;
             let velocity = vec3(
-                angle.cos() * 10.0,
-                angle.sin() * 10.0,
+                fmath::cos(angle) * 10.0,
+                fmath::sin(angle) * 10.0,
                 gen_range(-2.0, 2.0),
             );
 
             self.projectiles.push(BossProjectile {
                 position: self.position,
+                previous_position: self.position,
                 velocity,
                 lifetime: 6.0,
                 damage: 10.0,
@@ -300,10 +332,55 @@ impl Boss {
 
     fn attack_charge(&mut self, player_pos: Vec3) {
         // Quick dash toward player
-        let direction = (player_pos - self.position).normalize();
+        let direction = fmath::normalize(player_pos - self.position);
         self.velocity = direction * 25.0;
     }
 
+    /// Update phase based on health, returning true if it changed
+    fn update_phase(&mut self) -> bool {
+        let health_percent = self.health / self.max_health;
+        let phase = if health_percent > 0.66 {
+            1
+        } else if health_percent > 0.33 {
+            2
+        } else {
+            3
+        };
+
+        let changed = phase != self.phase;
+        self.phase = phase;
+        changed
+    }
+
+    pub fn snapshot(&self) -> BossSnapshot {
+        BossSnapshot {
+            boss_type: self.boss_type,
+            position: self.position,
+            health: self.health,
+            max_health: self.max_health,
+            phase: self.phase,
+            time_alive: self.time_alive,
+            defeated: self.defeated,
+        }
+    }
+
+    pub fn from_snapshot(snapshot: &BossSnapshot) -> Self {
+        Self {
+            health: snapshot.health,
+            max_health: snapshot.max_health,
+            phase: snapshot.phase,
+            time_alive: snapshot.time_alive,
+            defeated: snapshot.defeated,
+            ..Self::new(snapshot.boss_type, snapshot.position)
+        }
+    }
+
+    /// Scale max health for difficulty (call right after spawning)
+    pub fn scale_health(&mut self, multiplier: f32) {
+        self.max_health *= multiplier;
+        self.health = self.max_health;
+    }
+
     pub fn take_damage(&mut self, damage: f32) {
         self.health -= damage;
         if self.health <= 0.0 {
@@ -336,16 +413,30 @@ impl Boss {
         self.boss_type
     }
 
+    /// Collision description: main body plus the three accent pods on top
+    pub fn collision_shape(&self) -> Shape {
+        let scale = self.boss_type.scale();
+        let mut shapes = vec![Shape::aabb(
+            self.position,
+            vec3(scale * 2.0, scale * 1.5, scale * 2.0),
+        )];
+        for i in 0..3 {
+            let offset = vec3((i as f32 - 1.0) * scale * 0.8, scale * 0.5, 0.0);
+            shapes.push(Shape::aabb(self.position + offset, Vec3::splat(scale * 0.4)));
+        }
+        Shape::Compound(shapes)
+    }
+
     pub fn check_collision_with_player(&self, player_pos: Vec3) -> bool {
-        let distance = (self.position - player_pos).length();
-        distance < 2.0 * self.boss_type.scale()
+        self.collision_shape().intersects_sphere(player_pos, player::COLLISION_RADIUS)
     }
 
     pub fn check_projectile_collision(&mut self, player_pos: Vec3) -> bool {
         let mut hit = false;
+        let player_shape = Shape::sphere(player_pos, player::COLLISION_RADIUS);
         self.projectiles.retain(|proj| {
-            let distance = (proj.position - player_pos).length();
-            if distance < 1.0 {
+            // Swept so fast projectiles can't pass through the glider between frames
+            if player_shape.sweep_sphere(proj.previous_position, proj.position, 0.3).is_some() {
                 hit = true;
                 false // Remove projectile
             } else {
@@ -355,10 +446,24 @@ impl Boss {
         hit
     }
 
-    pub fn check_hit_by_player_projectile(&mut self, projectile_pos: Vec3) -> bool {
-        let distance = (self.position - projectile_pos).length();
-        if distance < self.boss_type.scale() {
+    /// Test a player projectile moving from `start` to `end` this frame
+    pub fn check_hit_by_player_projectile(&mut self, start: Vec3, end: Vec3, weapon: Weapon, events: &mut EventBus) -> bool {
+        if self.defeated {
+            return false;
+        }
+
+        if self.collision_shape().sweep_sphere(start, end, PROJECTILE_HIT_RADIUS).is_some() {
             self.take_damage(20.0);
+            events.emit(GameEvent::BossHit { boss_type: self.boss_type, weapon });
+
+            if self.defeated {
+                events.emit(GameEvent::BossDefeated { boss_type: self.boss_type });
+            } else if self.update_phase() {
+                events.emit(GameEvent::BossPhaseChanged {
+                    boss_type: self.boss_type,
+                    phase: self.phase,
+                });
+            }
             true
         } else {
             false
@@ -472,15 +577,29 @@ mod tests {
 
         // Phase 2 (below 66% health)
         boss.take_damage(boss.max_health() * 0.4);
-        boss.update(0.016, vec3(0.0, 0.0, 0.0));
+        boss.update(0.016, vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, 0.0));
         assert_eq!(boss.phase(), 2);
 
         // Phase 3 (below 33% health)
         boss.take_damage(boss.max_health() * 0.4);
-        boss.update(0.016, vec3(0.0, 0.0, 0.0));
+        boss.update(0.016, vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, 0.0));
         assert_eq!(boss.phase(), 3);
     }
 
+    #[test]
+    fn test_snapshot_keeps_health_and_phase() {
+        let mut boss = Boss::new(BossType::DragonKite, vec3(0.0, 5.0, 50.0));
+        boss.take_damage(boss.max_health() * 0.5);
+        boss.update(0.016, Vec3::ZERO, Vec3::ZERO);
+
+        let json = serde_json::to_string(&boss.snapshot()).unwrap();
+        let restored = Boss::from_snapshot(&serde_json::from_str(&json).unwrap());
+        assert_eq!(restored.boss_type(), BossType::DragonKite);
+        assert_eq!(restored.health(), boss.health());
+        assert_eq!(restored.phase(), 2);
+        assert_eq!(restored.position, boss.position);
+    }
+
     #[test]
     fn test_boss_health_percentage() {
         let mut boss = Boss::new(BossType::TutorialBoss, vec3(0.0, 5.0, 50.0));
@@ -490,4 +609,30 @@ mod tests {
         boss.take_damage(boss.max_health() * 0.5);
         assert!((boss.health_percentage() - 0.5).abs() < 0.01);
     }
+
+    #[test]
+    fn test_projectile_hits_emit_phase_and_defeat_events() {
+        let mut boss = Boss::new(BossType::TutorialBoss, vec3(0.0, 0.0, 20.0));
+        let mut events = EventBus::new();
+
+        let hits_needed = (boss.max_health() / 20.0).ceil() as usize;
+        for _ in 0..hits_needed + 2 {
+            boss.check_hit_by_player_projectile(vec3(0.0, 0.0, 15.0), vec3(0.0, 0.0, 21.0), Weapon::Laser, &mut events);
+        }
+
+        let emitted = events.drain();
+        let phases: Vec<u8> = emitted
+            .iter()
+            .filter_map(|event| match event {
+                GameEvent::BossPhaseChanged { phase, .. } => Some(*phase),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(phases, vec![2, 3]);
+
+        // Hits after defeat are ignored and the defeat is reported once
+        let defeats = emitted.iter().filter(|event| matches!(event, GameEvent::BossDefeated { .. })).count();
+        assert_eq!(defeats, 1);
+        assert!(boss.is_defeated());
+    }
 }
@@ -1,14 +1,34 @@
 use macroquad::prelude::*;
 use serde::{Deserialize, Serialize};
-use crate::player::Player;
+use crate::boss::{Boss, BossSnapshot};
+use crate::director::{DifficultyDirector, DirectorSnapshot};
+use crate::drone_companion::{DroneCompanion, DroneSnapshot};
+use crate::enemy::{EnemyManager, EnemySnapshot};
+use crate::events::{EventBus, GameEvent};
+use crate::level::{LevelManager, LevelSnapshot};
+use crate::player::{Player, Weapon};
+use crate::powerup::{PowerupManager, PowerupSnapshot};
+use crate::rings::{RingManager, RingSnapshot};
+use crate::scoring::ScoreKeeper;
+use crate::terrain::{TerrainManager, TerrainSnapshot};
+
+/// Respawn countdown used until the player picks their own in Options
+pub const DEFAULT_RESPAWN_COUNTDOWN: f32 = 5.0;
+/// Longest countdown a player can configure
+pub const MAX_RESPAWN_COUNTDOWN: f32 = 60.0;
+/// How many of the most recent checkpoints the player can choose to respawn at
+pub const SELECTABLE_CHECKPOINTS: usize = 3;
+/// Seconds of invulnerability after respawning
+pub const RESPAWN_INVULNERABILITY: f32 = 2.0;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerState {
     pub position: [f32; 3],  // Store as array for serialization
     pub health: f32,
-    pub weapon_type: String,
+    pub weapon: Weapon,
     pub ammo: u32,
     pub score: u32,
+    pub boost_energy: f32,
 }
 
 impl PlayerState {
@@ -17,38 +37,118 @@ impl PlayerState {
         Self {
             position: [pos.x, pos.y, pos.z],
             health: player.health(),
-            weapon_type: player.current_weapon().unwrap_or("None").to_string(),
+            weapon: player.weapon(),
             ammo: player.ammo(),
             score,
+            boost_energy: player.boost_energy(),
         }
     }
 
     pub fn position_vec3(&self) -> Vec3 {
         vec3(self.position[0], self.position[1], self.position[2])
     }
+
+    /// Put the player back in this state, briefly invulnerable
+    pub fn apply_to(&self, player: &mut Player) {
+        player.restore_from_checkpoint(self.position_vec3(), self.health, self.weapon, self.ammo);
+        player.set_boost_energy(self.boost_energy);
+        player.start_invulnerability(RESPAWN_INVULNERABILITY);
+    }
+}
+
+/// Everything in the run that a respawn puts back: the player, score, level timer
+/// and every manager's world state. Serializable so it can be written to disk.
+/// It also holds the adaptive director, which only a resumed run takes back: a
+/// respawn leaves it as it is, so the death that caused it still counts.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WorldSnapshot {
+    pub player: PlayerState,
+    pub score: ScoreKeeper,
+    pub level: LevelSnapshot,
+    pub terrain: TerrainSnapshot,
+    pub enemies: EnemySnapshot,
+    pub powerups: PowerupSnapshot,
+    pub rings: RingSnapshot,
+    pub boss: Option<BossSnapshot>,
+    pub drone: DroneSnapshot,
+    #[serde(default)]
+    pub director: DirectorSnapshot,
+}
+
+impl WorldSnapshot {
+    #[allow(clippy::too_many_arguments)]
+    pub fn capture(
+        player: &Player,
+        score: &ScoreKeeper,
+        level: &LevelManager,
+        terrain: &TerrainManager,
+        enemies: &EnemyManager,
+        powerups: &PowerupManager,
+        rings: &RingManager,
+        boss: Option<&Boss>,
+        drone: &DroneCompanion,
+        director: &DifficultyDirector,
+    ) -> Self {
+        Self {
+            player: PlayerState::from_player(player, score.total()),
+            score: score.clone(),
+            level: level.snapshot(),
+            terrain: terrain.snapshot(),
+            enemies: enemies.snapshot(),
+            powerups: powerups.snapshot(),
+            rings: rings.snapshot(),
+            boss: boss.map(Boss::snapshot),
+            drone: drone.snapshot(),
+            director: director.snapshot(),
+        }
+    }
+
+    /// Restore the level and every manager (the player and score are restored separately),
+    /// returning the boss as it was
+    pub fn restore(
+        &self,
+        level: &mut LevelManager,
+        terrain: &mut TerrainManager,
+        enemies: &mut EnemyManager,
+        powerups: &mut PowerupManager,
+        rings: &mut RingManager,
+        drone: &mut DroneCompanion,
+    ) -> Option<Boss> {
+        level.restore(&self.level);
+        terrain.restore(&self.terrain);
+        enemies.restore(&self.enemies);
+        powerups.restore(&self.powerups);
+        rings.restore(&self.rings);
+        drone.restore(&self.drone);
+        self.boss.as_ref().map(Boss::from_snapshot)
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Checkpoint {
     pub position: f32,           // Z-axis position
-    pub player_state: PlayerState,
     pub time_created: f32,       // Game time when checkpoint was created
+    pub world: WorldSnapshot,
 }
 
 impl Checkpoint {
-    pub fn new(position: f32, player_state: PlayerState, time_created: f32) -> Self {
+    pub fn new(position: f32, world: WorldSnapshot, time_created: f32) -> Self {
         Self {
             position,
-            player_state,
             time_created,
+            world,
         }
     }
 }
 
+/// Checkpoints are indexed by the order they were reached in (0 is the first),
+/// but only the selectable few are kept; older ones can never be respawned at and
+/// a long run would otherwise pile up a world snapshot for every one of them.
 pub struct CheckpointManager {
-    checkpoints: Vec<Checkpoint>,
+    checkpoints: Vec<Checkpoint>, // The most recent `SELECTABLE_CHECKPOINTS`, oldest first
+    reached: usize,               // Checkpoints reached so far, kept or not
     active_checkpoint_index: usize,
-    respawn_timer: Option<f32>,  // 60 second countdown
+    respawn_timer: Option<f32>,  // Counts down from respawn_duration
     respawn_duration: f32,
 }
 
@@ -56,22 +156,31 @@ impl CheckpointManager {
     pub fn new() -> Self {
         Self {
             checkpoints: Vec::new(),
+            reached: 0,
             active_checkpoint_index: 0,
             respawn_timer: None,
-            respawn_duration: 60.0,
+            respawn_duration: DEFAULT_RESPAWN_COUNTDOWN,
         }
     }
 
-    pub fn create_checkpoint(&mut self, position: f32, player: &Player, score: u32, game_time: f32) {
-        let player_state = PlayerState::from_player(player, score);
-        let checkpoint = Checkpoint::new(position, player_state, game_time);
+    /// Set the respawn countdown, clamped to 0 - 60 seconds (0 respawns immediately)
+    pub fn set_respawn_duration(&mut self, seconds: f32) {
+        self.respawn_duration = seconds.clamp(0.0, MAX_RESPAWN_COUNTDOWN);
+    }
 
-        self.checkpoints.push(checkpoint);
-        self.active_checkpoint_index = self.checkpoints.len() - 1;
+    pub fn create_checkpoint(&mut self, position: f32, world: WorldSnapshot, game_time: f32, events: &mut EventBus) {
+        self.checkpoints.push(Checkpoint::new(position, world, game_time));
+        if self.checkpoints.len() > SELECTABLE_CHECKPOINTS {
+            self.checkpoints.remove(0);
+        }
+        self.reached += 1;
+        self.active_checkpoint_index = self.reached - 1;
+        events.emit(GameEvent::CheckpointReached {
+            number: self.active_checkpoint_index + 1,
+            position,
+        });
 
-        // Only show checkpoint messages in debug builds
-        #[cfg(debug_assertions)]
-        println!(
+        log::debug!(
             "Checkpoint {} created at Z={:.1}",
             self.active_checkpoint_index + 1,
             position
@@ -106,27 +215,72 @@ impl CheckpointManager {
     }
 
     pub fn get_active_checkpoint(&self) -> Option<&Checkpoint> {
-        self.checkpoints.get(self.active_checkpoint_index)
+        self.checkpoint(self.active_checkpoint_index)
+    }
+
+    /// The checkpoint at `index`, if it's still kept
+    pub fn checkpoint(&self, index: usize) -> Option<&Checkpoint> {
+        index.checked_sub(self.selectable_range().start).and_then(|i| self.checkpoints.get(i))
+    }
+
+    /// Indices of the checkpoints the player may respawn at (the most recent few)
+    pub fn selectable_range(&self) -> std::ops::Range<usize> {
+        self.reached - self.checkpoints.len()..self.reached
+    }
+
+    /// The checkpoints still kept, oldest first
+    pub fn checkpoints(&self) -> &[Checkpoint] {
+        &self.checkpoints
+    }
+
+    /// Step the respawn choice back to an earlier checkpoint
+    pub fn select_older(&mut self) {
+        if self.active_checkpoint_index > self.selectable_range().start {
+            self.active_checkpoint_index -= 1;
+        }
     }
 
-    pub fn restore_player_state(&self, player: &mut Player, score: &mut u32) -> bool {
+    /// Step the respawn choice forward to a more recent checkpoint
+    pub fn select_newer(&mut self) {
+        if self.active_checkpoint_index + 1 < self.selectable_range().end {
+            self.active_checkpoint_index += 1;
+        }
+    }
+
+    /// Choose the checkpoint at `index` directly, if it's one of the selectable ones
+    pub fn select(&mut self, index: usize) -> bool {
+        if self.selectable_range().contains(&index) {
+            self.active_checkpoint_index = index;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Forget the checkpoints after the active one. They were reached on the
+    /// attempt a respawn abandons, so the next checkpoint takes the place of the
+    /// first of them.
+    pub fn discard_newer(&mut self) {
+        if self.checkpoints.is_empty() {
+            return;
+        }
+        let kept = self.active_checkpoint_index + 1 - self.selectable_range().start;
+        self.checkpoints.truncate(kept);
+        self.reached = self.active_checkpoint_index + 1;
+    }
+
+    /// Index of the checkpoint a respawn would use
+    pub fn active_index(&self) -> usize {
+        self.active_checkpoint_index
+    }
+
+    pub fn restore_player_state(&self, player: &mut Player, score: &mut ScoreKeeper) -> bool {
         if let Some(checkpoint) = self.get_active_checkpoint() {
-            // Restore player state from checkpoint
-            let pos = checkpoint.player_state.position_vec3();
-            let health = checkpoint.player_state.health;
-            let weapon = match checkpoint.player_state.weapon_type.as_str() {
-                "LASER" => crate::player::Weapon::Laser,
-                "MISSILE" => crate::player::Weapon::Missile,
-                "SPREAD" => crate::player::Weapon::Spread,
-                _ => crate::player::Weapon::None,
-            };
-            let ammo = checkpoint.player_state.ammo;
-
-            player.restore_from_checkpoint(pos, health, weapon, ammo);
-            *score = checkpoint.player_state.score;
-
-            #[cfg(debug_assertions)]
-            println!(
+            checkpoint.world.player.apply_to(player);
+            *score = checkpoint.world.score.clone();
+            score.break_combo();
+
+            log::debug!(
                 "Respawned at checkpoint {} (Z={:.1})",
                 self.active_checkpoint_index + 1,
                 checkpoint.position
@@ -138,16 +292,29 @@ impl CheckpointManager {
         }
     }
 
+    /// Checkpoints reached so far
     pub fn checkpoint_count(&self) -> usize {
-        self.checkpoints.len()
+        self.reached
     }
 
     pub fn active_checkpoint_number(&self) -> usize {
         self.active_checkpoint_index + 1
     }
 
+    /// Replace all checkpoints (resuming a suspended run), making the newest active.
+    /// `reached` counts those before them too; runs suspended before older ones were
+    /// dropped carry all of theirs and no count.
+    pub fn restore_checkpoints(&mut self, mut checkpoints: Vec<Checkpoint>, reached: usize) {
+        self.reached = reached.max(checkpoints.len());
+        checkpoints.drain(..checkpoints.len().saturating_sub(SELECTABLE_CHECKPOINTS));
+        self.checkpoints = checkpoints;
+        self.active_checkpoint_index = self.reached.saturating_sub(1);
+        self.respawn_timer = None;
+    }
+
     pub fn clear(&mut self) {
         self.checkpoints.clear();
+        self.reached = 0;
         self.active_checkpoint_index = 0;
         self.respawn_timer = None;
     }
@@ -167,13 +334,34 @@ impl Default for CheckpointManager {
 mod tests {
     use super::*;
     use crate::player::Player;
+    use crate::events::EventBus;
+    use crate::scoring::ScoreKeeper;
+    use crate::assets::Continent;
+    use crate::difficulty::DifficultySetting;
+    use crate::drone_companion::DroneCompanion;
+
+    /// Snapshot of a fresh level with the given player and drone
+    fn world(player: &Player, drone: &DroneCompanion) -> WorldSnapshot {
+        WorldSnapshot::capture(
+            player,
+            &ScoreKeeper::new(),
+            &LevelManager::new(Continent::Tutorial, DifficultySetting::Normal),
+            &TerrainManager::new(),
+            &EnemyManager::new(),
+            &PowerupManager::new(),
+            &RingManager::new(),
+            None,
+            drone,
+            &DifficultyDirector::new(),
+        )
+    }
 
     #[test]
     fn test_checkpoint_creation() {
         let mut manager = CheckpointManager::new();
         let player = Player::new();
 
-        manager.create_checkpoint(100.0, &player, 500, 30.0);
+        manager.create_checkpoint(100.0, world(&player, &DroneCompanion::new()), 30.0, &mut EventBus::new());
 
         assert_eq!(manager.checkpoint_count(), 1);
         assert_eq!(manager.active_checkpoint_number(), 1);
@@ -185,19 +373,125 @@ mod tests {
 
         manager.start_respawn();
         assert!(manager.is_respawning());
-        assert_eq!(manager.respawn_time_remaining(), Some(60.0));
+        assert_eq!(manager.respawn_time_remaining(), Some(DEFAULT_RESPAWN_COUNTDOWN));
 
         // Update timer
-        let respawn_ready = manager.update_respawn(30.0);
+        let respawn_ready = manager.update_respawn(3.0);
         assert!(!respawn_ready);
         assert!(manager.is_respawning());
 
         // Complete respawn
-        let respawn_ready = manager.update_respawn(35.0);
+        let respawn_ready = manager.update_respawn(3.0);
         assert!(respawn_ready);
         assert!(!manager.is_respawning());
     }
 
+    #[test]
+    fn test_respawn_duration_is_configurable_and_clamped() {
+        let mut manager = CheckpointManager::new();
+
+        manager.set_respawn_duration(10.0);
+        manager.start_respawn();
+        assert_eq!(manager.respawn_time_remaining(), Some(10.0));
+
+        manager.set_respawn_duration(600.0);
+        manager.start_respawn();
+        assert_eq!(manager.respawn_time_remaining(), Some(MAX_RESPAWN_COUNTDOWN));
+
+        // Zero respawns on the next update
+        manager.set_respawn_duration(0.0);
+        manager.start_respawn();
+        assert!(manager.update_respawn(0.016));
+    }
+
+    #[test]
+    fn test_choose_among_recent_checkpoints() {
+        let mut manager = CheckpointManager::new();
+        let player = Player::new();
+        for i in 1..=5 {
+            manager.create_checkpoint(i as f32 * 100.0, world(&player, &DroneCompanion::new()), i as f32 * 30.0, &mut EventBus::new());
+        }
+        assert_eq!(manager.selectable_range(), 5 - SELECTABLE_CHECKPOINTS..5);
+
+        // Can't step back past the last few checkpoints
+        for _ in 0..10 {
+            manager.select_older();
+        }
+        assert_eq!(manager.active_checkpoint_number(), 5 - SELECTABLE_CHECKPOINTS + 1);
+        assert_eq!(manager.get_last_checkpoint_position(), Some(300.0));
+
+        for _ in 0..10 {
+            manager.select_newer();
+        }
+        assert_eq!(manager.active_checkpoint_number(), 5);
+    }
+
+    #[test]
+    fn test_only_selectable_checkpoints_are_kept() {
+        let mut manager = CheckpointManager::new();
+        let player = Player::new();
+        let mut events = EventBus::new();
+        for i in 1..=50 {
+            manager.create_checkpoint(i as f32 * 100.0, world(&player, &DroneCompanion::new()), i as f32 * 30.0, &mut events);
+        }
+
+        assert_eq!(manager.checkpoints().len(), SELECTABLE_CHECKPOINTS);
+        assert_eq!(manager.checkpoint_count(), 50);
+        assert_eq!(manager.active_checkpoint_number(), 50);
+        assert!(manager.checkpoint(46).is_none());
+        assert_eq!(manager.checkpoint(47).unwrap().position, 4800.0);
+        assert!(!manager.select(46));
+        assert!(events.drain().contains(&GameEvent::CheckpointReached { number: 50, position: 5000.0 }));
+
+        // Resumed from a run that kept all of them
+        let mut resumed = CheckpointManager::new();
+        resumed.restore_checkpoints(vec![manager.get_active_checkpoint().unwrap().clone(); 10], 0);
+        assert_eq!(resumed.checkpoints().len(), SELECTABLE_CHECKPOINTS);
+        assert_eq!(resumed.active_checkpoint_number(), 10);
+    }
+
+    #[test]
+    fn test_discard_newer() {
+        let mut manager = CheckpointManager::new();
+        manager.discard_newer();
+        assert_eq!(manager.checkpoint_count(), 0);
+
+        let player = Player::new();
+        for i in 1..=3 {
+            manager.create_checkpoint(i as f32 * 100.0, world(&player, &DroneCompanion::new()), i as f32 * 30.0, &mut EventBus::new());
+        }
+        manager.select_older();
+        manager.discard_newer();
+        assert_eq!(manager.checkpoint_count(), 2);
+        assert_eq!(manager.get_active_checkpoint().unwrap().position, 200.0);
+        manager.select_newer();
+        assert_eq!(manager.active_checkpoint_number(), 2);
+    }
+
+    #[test]
+    fn test_respawn_restores_player_effects_and_drone() {
+        let mut manager = CheckpointManager::new();
+        let mut player = Player::new();
+        let mut drone = DroneCompanion::new();
+        drone.activate(player.position());
+
+        manager.create_checkpoint(100.0, world(&player, &drone), 30.0, &mut EventBus::new());
+
+        // Die with the drone gone and the boost drained
+        drone.deactivate();
+        player.set_boost_energy(0.0);
+        player.take_damage(100.0);
+
+        assert!(manager.restore_player_state(&mut player, &mut ScoreKeeper::new()));
+        drone.restore(&manager.get_active_checkpoint().unwrap().world.drone);
+
+        assert_eq!(player.health(), 100.0);
+        assert_eq!(player.boost_energy(), 100.0);
+        assert!(player.is_invulnerable());
+        assert!(drone.is_active());
+        assert_eq!(drone.remaining_time(), 30.0);
+    }
+
     #[test]
     fn test_cancel_respawn() {
         let mut manager = CheckpointManager::new();
@@ -214,9 +508,9 @@ mod tests {
         let mut manager = CheckpointManager::new();
         let player = Player::new();
 
-        manager.create_checkpoint(100.0, &player, 500, 30.0);
-        manager.create_checkpoint(200.0, &player, 1000, 60.0);
-        manager.create_checkpoint(300.0, &player, 1500, 90.0);
+        manager.create_checkpoint(100.0, world(&player, &DroneCompanion::new()), 30.0, &mut EventBus::new());
+        manager.create_checkpoint(200.0, world(&player, &DroneCompanion::new()), 60.0, &mut EventBus::new());
+        manager.create_checkpoint(300.0, world(&player, &DroneCompanion::new()), 90.0, &mut EventBus::new());
 
         assert_eq!(manager.checkpoint_count(), 3);
         assert_eq!(manager.active_checkpoint_number(), 3);
@@ -230,7 +524,7 @@ mod tests {
         let mut manager = CheckpointManager::new();
         let player = Player::new();
 
-        manager.create_checkpoint(100.0, &player, 500, 30.0);
+        manager.create_checkpoint(100.0, world(&player, &DroneCompanion::new()), 30.0, &mut EventBus::new());
         manager.start_respawn();
 
         manager.clear();
@@ -247,8 +541,23 @@ mod tests {
         let state = PlayerState::from_player(&player, 1000);
 
         assert_eq!(state.health, 100.0);
-        assert_eq!(state.weapon_type, "LASER");
+        assert_eq!(state.weapon, crate::player::Weapon::Laser);
         assert_eq!(state.ammo, 50);
         assert_eq!(state.score, 1000);
     }
+
+    #[test]
+    fn test_checkpoint_survives_json_round_trip() {
+        let mut player = Player::new();
+        player.set_weapon(crate::player::Weapon::Missile, 12);
+        let mut manager = CheckpointManager::new();
+        manager.create_checkpoint(150.0, world(&player, &DroneCompanion::new()), 45.0, &mut EventBus::new());
+
+        let json = serde_json::to_string(manager.get_active_checkpoint().unwrap()).unwrap();
+        let loaded: Checkpoint = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.position, 150.0);
+        assert_eq!(loaded.world.player.weapon, crate::player::Weapon::Missile);
+        assert_eq!(loaded.world.player.ammo, 12);
+        assert!(loaded.world.boss.is_none());
+    }
 }
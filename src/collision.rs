@@ -0,0 +1,301 @@
+use macroquad::prelude::*;
+
+/// Collision shapes shared by terrain, enemies and bosses.
+///
+/// Every query treats the other party as a sphere (player glider, projectiles),
+/// which keeps the math exact for all primitives without a full GJK solver.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Sphere { center: Vec3, radius: f32 },
+    Aabb { center: Vec3, half_extents: Vec3 },
+    Obb { center: Vec3, half_extents: Vec3, rotation: Quat },
+    Capsule { start: Vec3, end: Vec3, radius: f32 },
+    Compound(Vec<Shape>),
+}
+
+// Conservative advancement stops once it is this close to a surface
+const SWEEP_EPSILON: f32 = 0.001;
+const SWEEP_MAX_ITERATIONS: usize = 32;
+
+impl Shape {
+    pub fn sphere(center: Vec3, radius: f32) -> Self {
+        Shape::Sphere { center, radius }
+    }
+
+    /// Axis-aligned box from a center and full size (matches `draw_cube` arguments)
+    pub fn aabb(center: Vec3, size: Vec3) -> Self {
+        Shape::Aabb { center, half_extents: size / 2.0 }
+    }
+
+    /// Oriented box from a center, full size and rotation
+    pub fn obb(center: Vec3, size: Vec3, rotation: Quat) -> Self {
+        Shape::Obb { center, half_extents: size / 2.0, rotation }
+    }
+
+    pub fn capsule(start: Vec3, end: Vec3, radius: f32) -> Self {
+        Shape::Capsule { start, end, radius }
+    }
+
+    /// Distance from a point to the surface of the shape (0.0 when inside)
+    pub fn distance_to_point(&self, point: Vec3) -> f32 {
+        match self {
+            Shape::Sphere { center, radius } => ((point - *center).length() - radius).max(0.0),
+            Shape::Aabb { center, half_extents } => {
+                box_distance(point - *center, *half_extents)
+            }
+            Shape::Obb { center, half_extents, rotation } => {
+                let local = rotation.inverse() * (point - *center);
+                box_distance(local, *half_extents)
+            }
+            Shape::Capsule { start, end, radius } => {
+                let closest = closest_point_on_segment(point, *start, *end);
+                ((point - closest).length() - radius).max(0.0)
+            }
+            Shape::Compound(shapes) => shapes
+                .iter()
+                .map(|shape| shape.distance_to_point(point))
+                .fold(f32::MAX, f32::min),
+        }
+    }
+
+    /// Check whether a sphere overlaps this shape
+    pub fn intersects_sphere(&self, center: Vec3, radius: f32) -> bool {
+        self.distance_to_point(center) < radius
+    }
+
+    /// Sweep a sphere from `start` to `end` and return the fraction of the
+    /// path (0.0-1.0) at which it first touches the shape.
+    ///
+    /// Uses conservative advancement: each step moves by the current clearance,
+    /// so fast movers can never skip over a thin obstacle between frames.
+    pub fn sweep_sphere(&self, start: Vec3, end: Vec3, radius: f32) -> Option<f32> {
+        let path = end - start;
+        let path_length = path.length();
+
+        if path_length <= SWEEP_EPSILON {
+            return self.intersects_sphere(end, radius).then_some(0.0);
+        }
+
+        let mut t = 0.0;
+        for _ in 0..SWEEP_MAX_ITERATIONS {
+            let clearance = self.distance_to_point(start + path * t) - radius;
+            if clearance <= SWEEP_EPSILON {
+                return Some(t);
+            }
+
+            t += clearance / path_length;
+            if t > 1.0 {
+                return None;
+            }
+        }
+
+        // Still approaching after the iteration budget: treat the end point as final
+        self.intersects_sphere(end, radius).then_some(1.0)
+    }
+
+    /// Axis-aligned bounds as (min, max), used for broad-phase bucketing
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        match self {
+            Shape::Sphere { center, radius } => (*center - Vec3::splat(*radius), *center + Vec3::splat(*radius)),
+            Shape::Aabb { center, half_extents } => (*center - *half_extents, *center + *half_extents),
+            Shape::Obb { center, half_extents, rotation } => {
+                // Project the rotated axes onto world axes
+                let extent = (*rotation * Vec3::X * half_extents.x).abs()
+                    + (*rotation * Vec3::Y * half_extents.y).abs()
+                    + (*rotation * Vec3::Z * half_extents.z).abs();
+                (*center - extent, *center + extent)
+            }
+            Shape::Capsule { start, end, radius } => (
+                start.min(*end) - Vec3::splat(*radius),
+                start.max(*end) + Vec3::splat(*radius),
+            ),
+            Shape::Compound(shapes) => shapes.iter().fold(
+                (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+                |(min, max), shape| {
+                    let (shape_min, shape_max) = shape.bounds();
+                    (min.min(shape_min), max.max(shape_max))
+                },
+            ),
+        }
+    }
+
+    /// Draw the shape as solid geometry (obstacles are rendered from their collision shapes)
+    pub fn draw(&self, color: Color) {
+        match self {
+            Shape::Sphere { center, radius } => draw_sphere(*center, *radius, None, color),
+            Shape::Aabb { center, half_extents } => draw_cube(*center, *half_extents * 2.0, None, color),
+            Shape::Obb { center, half_extents, rotation } => {
+                let e1 = *rotation * Vec3::X * half_extents.x * 2.0;
+                let e2 = *rotation * Vec3::Y * half_extents.y * 2.0;
+                let e3 = *rotation * Vec3::Z * half_extents.z * 2.0;
+                let corner = *center - (e1 + e2 + e3) / 2.0;
+                draw_affine_parallelepiped(corner, e1, e2, e3, None, color);
+            }
+            Shape::Capsule { start, end, radius } => {
+                // Capsules in this game are vertical posts, so a cylinder is close enough
+                let height = (*end - *start).length();
+                draw_cylinder(*start, *radius, *radius, height, None, color);
+                draw_sphere(*start, *radius, None, color);
+                draw_sphere(*end, *radius, None, color);
+            }
+            Shape::Compound(shapes) => {
+                for shape in shapes {
+                    shape.draw(color);
+                }
+            }
+        }
+    }
+
+    /// Draw a wireframe outline of the shape for the retro look
+    pub fn draw_wires(&self, color: Color) {
+        match self {
+            Shape::Sphere { center, radius } => draw_sphere_wires(*center, *radius, None, color),
+            Shape::Aabb { center, half_extents } => draw_cube_wires(*center, *half_extents * 2.0, color),
+            Shape::Obb { center, half_extents, rotation } => {
+                let corner = |x: f32, y: f32, z: f32| {
+                    *center + *rotation * (*half_extents * vec3(x, y, z))
+                };
+                let signs = [-1.0, 1.0];
+                for &a in &signs {
+                    for &b in &signs {
+                        draw_line_3d(corner(-1.0, a, b), corner(1.0, a, b), color);
+                        draw_line_3d(corner(a, -1.0, b), corner(a, 1.0, b), color);
+                        draw_line_3d(corner(a, b, -1.0), corner(a, b, 1.0), color);
+                    }
+                }
+            }
+            Shape::Capsule { start, end, radius } => {
+                let height = (*end - *start).length();
+                draw_cylinder_wires(*start, *radius, *radius, height, None, color);
+            }
+            Shape::Compound(shapes) => {
+                for shape in shapes {
+                    shape.draw_wires(color);
+                }
+            }
+        }
+    }
+}
+
+/// Distance from a point (relative to the box center) to a box surface
+fn box_distance(local: Vec3, half_extents: Vec3) -> f32 {
+    (local.abs() - half_extents).max(Vec3::ZERO).length()
+}
+
+fn closest_point_on_segment(point: Vec3, start: Vec3, end: Vec3) -> Vec3 {
+    let segment = end - start;
+    let length_squared = segment.length_squared();
+    if length_squared <= f32::EPSILON {
+        return start;
+    }
+
+    let t = ((point - start).dot(segment) / length_squared).clamp(0.0, 1.0);
+    start + segment * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sphere_intersection() {
+        let shape = Shape::sphere(vec3(0.0, 0.0, 0.0), 1.0);
+        assert!(shape.intersects_sphere(vec3(1.5, 0.0, 0.0), 0.7));
+        assert!(!shape.intersects_sphere(vec3(2.0, 0.0, 0.0), 0.7));
+    }
+
+    #[test]
+    fn test_aabb_corner_is_rounded() {
+        let shape = Shape::aabb(vec3(0.0, 0.0, 0.0), vec3(2.0, 2.0, 2.0));
+
+        // Face contact
+        assert!(shape.intersects_sphere(vec3(1.5, 0.0, 0.0), 0.7));
+        // Diagonal near the corner is farther than the per-axis test would suggest
+        assert!(!shape.intersects_sphere(vec3(1.6, 1.6, 0.0), 0.7));
+    }
+
+    #[test]
+    fn test_obb_rotation() {
+        // Long thin blade rotated 90 degrees around Z now points up
+        let blade = Shape::obb(
+            vec3(0.0, 0.0, 0.0),
+            vec3(4.0, 0.2, 0.2),
+            Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+        );
+
+        assert!(blade.intersects_sphere(vec3(0.0, 1.8, 0.0), 0.3));
+        assert!(!blade.intersects_sphere(vec3(1.8, 0.0, 0.0), 0.3));
+    }
+
+    #[test]
+    fn test_capsule_distance() {
+        let post = Shape::capsule(vec3(0.0, 0.0, 0.0), vec3(0.0, 4.0, 0.0), 0.25);
+
+        assert_eq!(post.distance_to_point(vec3(0.0, 2.0, 0.0)), 0.0);
+        assert!((post.distance_to_point(vec3(1.25, 2.0, 0.0)) - 1.0).abs() < 0.001);
+        assert!((post.distance_to_point(vec3(0.0, 5.25, 0.0)) - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_compound_uses_closest_child() {
+        let walls = Shape::Compound(vec![
+            Shape::aabb(vec3(-1.5, 0.0, 0.0), vec3(0.5, 3.0, 4.0)),
+            Shape::aabb(vec3(1.5, 0.0, 0.0), vec3(0.5, 3.0, 4.0)),
+        ]);
+
+        // Gap between the walls is open
+        assert!(!walls.intersects_sphere(vec3(0.0, 0.0, 0.0), 0.7));
+        assert!(walls.intersects_sphere(vec3(1.0, 0.0, 0.0), 0.7));
+    }
+
+    #[test]
+    fn test_sweep_prevents_tunneling() {
+        // Thin wall a fast mover would jump over in a single frame
+        let wall = Shape::aabb(vec3(0.0, 0.0, 10.0), vec3(4.0, 4.0, 0.1));
+        let start = vec3(0.0, 0.0, 5.0);
+        let end = vec3(0.0, 0.0, 15.0);
+
+        assert!(!wall.intersects_sphere(start, 0.2));
+        assert!(!wall.intersects_sphere(end, 0.2));
+
+        let hit = wall.sweep_sphere(start, end, 0.2).expect("sweep should hit the wall");
+        assert!(hit > 0.4 && hit < 0.5);
+    }
+
+    #[test]
+    fn test_sweep_misses() {
+        let boulder = Shape::sphere(vec3(5.0, 0.0, 10.0), 1.0);
+        assert_eq!(boulder.sweep_sphere(vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, 20.0), 0.7), None);
+    }
+
+    #[test]
+    fn test_sweep_zero_length_is_overlap_test() {
+        let boulder = Shape::sphere(vec3(0.0, 0.0, 0.0), 1.0);
+        assert_eq!(boulder.sweep_sphere(Vec3::ZERO, Vec3::ZERO, 0.5), Some(0.0));
+    }
+
+    #[test]
+    fn test_bounds() {
+        let compound = Shape::Compound(vec![
+            Shape::sphere(vec3(0.0, 0.0, 0.0), 1.0),
+            Shape::aabb(vec3(5.0, 0.0, 0.0), vec3(2.0, 2.0, 2.0)),
+        ]);
+
+        let (min, max) = compound.bounds();
+        assert_eq!(min, vec3(-1.0, -1.0, -1.0));
+        assert_eq!(max, vec3(6.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn test_obb_bounds_grow_with_rotation() {
+        let blade = Shape::obb(
+            Vec3::ZERO,
+            vec3(2.0, 0.2, 0.2),
+            Quat::from_rotation_z(std::f32::consts::FRAC_PI_4),
+        );
+
+        let (min, max) = blade.bounds();
+        assert!(max.y > 0.7 && max.y < 0.8);
+        assert!((min.x + max.x).abs() < 0.001);
+    }
+}
@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+use crate::director::DirectorTuning;
+use crate::enemy::EnemyType;
+use crate::fmath;
+
+/// Player-chosen difficulty from the options menu
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DifficultySetting {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl DifficultySetting {
+    /// Map the saved settings index (0=Easy, 1=Normal, 2=Hard)
+    pub fn from_index(index: u8) -> Self {
+        match index {
+            0 => DifficultySetting::Easy,
+            2 => DifficultySetting::Hard,
+            _ => DifficultySetting::Normal,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DifficultySetting::Easy => "Easy",
+            DifficultySetting::Normal => "Normal",
+            DifficultySetting::Hard => "Hard",
+        }
+    }
+
+    /// How much the setting scales the continent's difficulty
+    fn intensity(&self) -> f32 {
+        match self {
+            DifficultySetting::Easy => 0.75,
+            DifficultySetting::Normal => 1.0,
+            DifficultySetting::Hard => 1.3,
+        }
+    }
+
+    /// Multiplier on all damage the player takes
+    fn damage_taken(&self) -> f32 {
+        match self {
+            DifficultySetting::Easy => 0.6,
+            DifficultySetting::Normal => 1.0,
+            DifficultySetting::Hard => 1.4,
+        }
+    }
+}
+
+/// Combined difficulty for the current moment of a level.
+///
+/// Built from the continent's `difficulty_curve`, how far through the level the
+/// player is, and the Easy/Normal/Hard setting. Spawners, enemies and bosses read
+/// their tuning from here rather than ramping up on their own.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Difficulty {
+    pub level: f32,        // 1.0 = Normal at the start of North America
+    pub damage_taken: f32, // Multiplier on damage dealt to the player
+    pub spawn_rate: f32,   // Adaptive director nudges (1.0 = none)
+    pub aggression: f32,
+}
+
+impl Difficulty {
+    /// `progress` is how far through the level the player is (0.0 - 1.0)
+    pub fn new(continent_curve: f32, progress: f32, setting: DifficultySetting) -> Self {
+        let ramp = 1.0 + progress.clamp(0.0, 1.0) * 0.5;
+        Self {
+            level: continent_curve * ramp * setting.intensity(),
+            damage_taken: setting.damage_taken(),
+            spawn_rate: 1.0,
+            aggression: 1.0,
+        }
+    }
+
+    /// Endless mode's difficulty, which never stops climbing. `progress` counts
+    /// ramps flown (0.0 at the start, 1.0 after one, and so on); each one adds
+    /// half the starting difficulty again, as a whole fixed level does, and a
+    /// little more damage taken.
+    pub fn endless(progress: f32, setting: DifficultySetting) -> Self {
+        let progress = progress.max(0.0);
+        Self {
+            level: (1.0 + progress * 0.5) * setting.intensity(),
+            damage_taken: setting.damage_taken() * (1.0 + progress * 0.1),
+            spawn_rate: 1.0,
+            aggression: 1.0,
+        }
+    }
+
+    /// Apply the adaptive director's nudges on top of the base difficulty
+    pub fn with_director(self, tuning: DirectorTuning) -> Self {
+        Self {
+            spawn_rate: tuning.spawn_rate,
+            aggression: tuning.aggression,
+            ..self
+        }
+    }
+
+    /// Upper bound on obstacles in each spawn wave
+    pub fn max_obstacles_per_wave(&self) -> i32 {
+        (1.0 + self.level * 2.0).round().clamp(1.0, 6.0) as i32
+    }
+
+    /// Distance flown between obstacle waves
+    pub fn obstacle_wave_spacing(&self) -> f32 {
+        (24.0 / fmath::sqrt(self.level.max(0.1))).clamp(14.0, 30.0) / self.spawn_rate
+    }
+
+    /// Seconds between enemy spawns
+    pub fn enemy_spawn_interval(&self) -> f32 {
+        (2.0 / self.level.max(0.1)).clamp(0.6, 4.0) / self.spawn_rate
+    }
+
+    /// Multiplier on enemy movement speed
+    pub fn enemy_speed(&self) -> f32 {
+        (0.8 + self.level * 0.2).clamp(0.8, 1.6) * self.aggression
+    }
+
+    /// Hit points for a newly spawned enemy
+    pub fn enemy_health(&self) -> f32 {
+        30.0 * (0.7 + self.level * 0.3).clamp(0.7, 2.0)
+    }
+
+    /// Spawn weights per enemy type; harder levels favour seekers and turrets
+    pub fn enemy_weights(&self) -> [(EnemyType, u32); 4] {
+        let shift = ((self.level - 1.0) * 2.0).round().clamp(0.0, 4.0) as u32;
+        [
+            (EnemyType::Drone, 5 - shift),
+            (EnemyType::Zigzag, 3),
+            (EnemyType::Seeker, 1 + shift.div_ceil(2)),
+            (EnemyType::Turret, 1 + shift / 2),
+        ]
+    }
+
+    /// Multiplier on boss max health
+    pub fn boss_health(&self) -> f32 {
+        (0.6 + self.level * 0.4).clamp(0.6, 2.5)
+    }
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self::new(1.0, 0.0, DifficultySetting::Normal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normal_start_matches_original_tuning() {
+        let difficulty = Difficulty::default();
+        assert_eq!(difficulty.level, 1.0);
+        assert_eq!(difficulty.enemy_spawn_interval(), 2.0);
+        assert_eq!(difficulty.enemy_health(), 30.0);
+        assert_eq!(difficulty.boss_health(), 1.0);
+        assert_eq!(difficulty.damage_taken, 1.0);
+    }
+
+    #[test]
+    fn test_everything_ramps_with_setting_and_progress() {
+        let easy = Difficulty::new(1.4, 0.0, DifficultySetting::Easy);
+        let normal = Difficulty::new(1.4, 0.0, DifficultySetting::Normal);
+        let late = Difficulty::new(1.4, 1.0, DifficultySetting::Normal);
+        let hard = Difficulty::new(1.4, 1.0, DifficultySetting::Hard);
+
+        for (lower, higher) in [(easy, normal), (normal, late), (late, hard)] {
+            assert!(higher.level > lower.level);
+            assert!(higher.max_obstacles_per_wave() >= lower.max_obstacles_per_wave());
+            assert!(higher.enemy_spawn_interval() < lower.enemy_spawn_interval());
+            assert!(higher.enemy_speed() > lower.enemy_speed());
+            assert!(higher.enemy_health() > lower.enemy_health());
+            assert!(higher.boss_health() > lower.boss_health());
+        }
+        assert!(easy.damage_taken < normal.damage_taken);
+        assert!(hard.damage_taken > normal.damage_taken);
+    }
+
+    #[test]
+    fn test_endless_keeps_ramping() {
+        let start = Difficulty::endless(0.0, DifficultySetting::Normal);
+        assert_eq!(start, Difficulty::default());
+
+        // Past where any fixed level tops out, and still climbing
+        let oceania_end = Difficulty::new(2.0, 1.0, DifficultySetting::Normal);
+        let far = Difficulty::endless(6.0, DifficultySetting::Normal);
+        let further = Difficulty::endless(12.0, DifficultySetting::Normal);
+        assert!(far.level > oceania_end.level);
+        assert!(further.level > far.level);
+        assert!(further.damage_taken > far.damage_taken);
+    }
+
+    #[test]
+    fn test_director_nudges_spawns_and_speed() {
+        let base = Difficulty::default();
+        let pushed = base.with_director(DirectorTuning { spawn_rate: 1.25, aggression: 1.1, powerup_generosity: 0.75 });
+
+        assert_eq!(pushed.level, base.level);
+        assert_eq!(pushed.enemy_spawn_interval(), base.enemy_spawn_interval() / 1.25);
+        assert!(pushed.obstacle_wave_spacing() < base.obstacle_wave_spacing());
+        assert!(pushed.enemy_speed() > base.enemy_speed());
+    }
+
+    #[test]
+    fn test_enemy_mix_shifts_toward_seekers_and_turrets() {
+        let weight = |difficulty: Difficulty, enemy_type: EnemyType| {
+            difficulty.enemy_weights().iter().find(|(t, _)| *t == enemy_type).unwrap().1
+        };
+        let start = Difficulty::default();
+        let oceania = Difficulty::new(2.0, 1.0, DifficultySetting::Hard);
+
+        assert!(weight(oceania, EnemyType::Drone) < weight(start, EnemyType::Drone));
+        assert!(weight(oceania, EnemyType::Seeker) > weight(start, EnemyType::Seeker));
+        assert!(weight(oceania, EnemyType::Turret) > weight(start, EnemyType::Turret));
+    }
+}
@@ -0,0 +1,332 @@
+use serde::{Deserialize, Serialize};
+use crate::events::{EventListener, GameEvent};
+
+/// Seconds of play between director evaluations
+const EVALUATION_INTERVAL: f32 = 15.0;
+/// Furthest the director may push intensity either way
+pub const MAX_INTENSITY: f32 = 0.3;
+/// Largest single nudge per evaluation
+const MAX_STEP: f32 = 0.1;
+/// Performance scores closer to neutral than this leave intensity alone
+const DEAD_ZONE: f32 = 0.15;
+/// Damage per minute an average run takes
+const TARGET_DAMAGE_PER_MINUTE: f32 = 30.0;
+/// Ring accuracy an average run hits
+const TARGET_RING_ACCURACY: f32 = 0.6;
+/// A death outweighs everything else a window can earn
+const DEATH_PENALTY: f32 = 1.5;
+const MAX_LOGGED_DECISIONS: usize = 200;
+
+/// Multipliers the director applies on top of the level's difficulty
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DirectorTuning {
+    pub spawn_rate: f32,         // Enemy and obstacle wave frequency
+    pub aggression: f32,         // Enemy speed
+    pub powerup_generosity: f32, // Powerup spawn frequency
+}
+
+impl DirectorTuning {
+    fn from_intensity(intensity: f32) -> Self {
+        Self {
+            spawn_rate: 1.0 + intensity,
+            aggression: 1.0 + intensity * 0.5,
+            powerup_generosity: 1.0 - intensity,
+        }
+    }
+}
+
+/// What the director measured over one evaluation window
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DirectorMetrics {
+    pub deaths: u32,
+    pub damage_per_minute: f32,
+    pub health_trend: f32,          // Change in health over the window (-100 to 100)
+    pub ring_accuracy: Option<f32>, // None if no rings were resolved
+}
+
+/// One logged evaluation, kept so runs can be checked after the fact
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DirectorDecision {
+    pub time: f32,
+    pub metrics: DirectorMetrics,
+    pub performance: f32, // -1.0 (struggling) to 1.0 (cruising)
+    pub intensity_before: f32,
+    pub intensity_after: f32,
+}
+
+/// Everything the director has measured and decided so far, so a resumed run
+/// carries on adapting from where it was rather than from neutral
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DirectorSnapshot {
+    time: f32,
+    window_timer: f32,
+    intensity: f32,
+    window_deaths: u32,
+    window_damage: f32,
+    window_rings_passed: u32,
+    window_rings_missed: u32,
+    window_start_health: Option<f32>,
+    decisions: Vec<DirectorDecision>,
+}
+
+/// Optional dynamic difficulty that watches how the player is doing and nudges
+/// spawn rates, enemy aggression and powerup generosity within fixed bounds.
+pub struct DifficultyDirector {
+    enabled: bool,
+    time: f32,
+    window_timer: f32,
+    intensity: f32, // -MAX_INTENSITY..=MAX_INTENSITY, 0.0 = no adjustment
+    window_deaths: u32,
+    window_damage: f32,
+    window_rings_passed: u32,
+    window_rings_missed: u32,
+    window_start_health: Option<f32>,
+    decisions: Vec<DirectorDecision>,
+}
+
+impl DifficultyDirector {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            time: 0.0,
+            window_timer: 0.0,
+            intensity: 0.0,
+            window_deaths: 0,
+            window_damage: 0.0,
+            window_rings_passed: 0,
+            window_rings_missed: 0,
+            window_start_health: None,
+            decisions: Vec::new(),
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn update(&mut self, dt: f32, player_health: f32) {
+        if !self.enabled {
+            return;
+        }
+
+        self.time += dt;
+        self.window_timer += dt;
+        self.window_start_health.get_or_insert(player_health);
+
+        if self.window_timer >= EVALUATION_INTERVAL {
+            self.evaluate(player_health);
+        }
+    }
+
+    fn evaluate(&mut self, player_health: f32) {
+        let rings_resolved = self.window_rings_passed + self.window_rings_missed;
+        let metrics = DirectorMetrics {
+            deaths: self.window_deaths,
+            damage_per_minute: self.window_damage / (self.window_timer / 60.0),
+            health_trend: player_health - self.window_start_health.unwrap_or(player_health),
+            ring_accuracy: (rings_resolved > 0)
+                .then(|| self.window_rings_passed as f32 / rings_resolved as f32),
+        };
+
+        let performance = Self::performance(&metrics);
+        let intensity_before = self.intensity;
+        if performance.abs() > DEAD_ZONE {
+            self.intensity = (self.intensity + performance * MAX_STEP).clamp(-MAX_INTENSITY, MAX_INTENSITY);
+        }
+
+        let decision = DirectorDecision {
+            time: self.time,
+            metrics,
+            performance,
+            intensity_before,
+            intensity_after: self.intensity,
+        };
+        log::debug!(
+            "Director @{:.0}s: deaths={} dmg/min={:.0} health={:+.0} rings={:?} -> perf {:+.2}, intensity {:+.2} -> {:+.2}",
+            decision.time,
+            metrics.deaths,
+            metrics.damage_per_minute,
+            metrics.health_trend,
+            metrics.ring_accuracy,
+            performance,
+            intensity_before,
+            self.intensity,
+        );
+        if self.decisions.len() >= MAX_LOGGED_DECISIONS {
+            self.decisions.remove(0);
+        }
+        self.decisions.push(decision);
+
+        // Start the next window
+        self.window_timer = 0.0;
+        self.window_deaths = 0;
+        self.window_damage = 0.0;
+        self.window_rings_passed = 0;
+        self.window_rings_missed = 0;
+        self.window_start_health = Some(player_health);
+    }
+
+    /// Score a window from -1.0 (struggling) to 1.0 (cruising)
+    fn performance(metrics: &DirectorMetrics) -> f32 {
+        let mut score = -(metrics.deaths as f32) * DEATH_PENALTY;
+        score += ((TARGET_DAMAGE_PER_MINUTE - metrics.damage_per_minute) / 60.0).clamp(-0.5, 0.5);
+        score += (metrics.health_trend / 100.0).clamp(-0.5, 0.5);
+        if let Some(accuracy) = metrics.ring_accuracy {
+            score += (accuracy - TARGET_RING_ACCURACY).clamp(-0.5, 0.5);
+        }
+        score.clamp(-1.0, 1.0)
+    }
+
+    /// Current multipliers (all 1.0 when disabled)
+    pub fn tuning(&self) -> DirectorTuning {
+        if self.enabled {
+            DirectorTuning::from_intensity(self.intensity)
+        } else {
+            DirectorTuning::from_intensity(0.0)
+        }
+    }
+
+    pub fn decisions(&self) -> &[DirectorDecision] {
+        &self.decisions
+    }
+
+    pub fn snapshot(&self) -> DirectorSnapshot {
+        DirectorSnapshot {
+            time: self.time,
+            window_timer: self.window_timer,
+            intensity: self.intensity,
+            window_deaths: self.window_deaths,
+            window_damage: self.window_damage,
+            window_rings_passed: self.window_rings_passed,
+            window_rings_missed: self.window_rings_missed,
+            window_start_health: self.window_start_health,
+            decisions: self.decisions.clone(),
+        }
+    }
+
+    /// Pick up measurements and adjustments from `snapshot` (whether it's enabled stays as is)
+    pub fn restore(&mut self, snapshot: &DirectorSnapshot) {
+        self.time = snapshot.time;
+        self.window_timer = snapshot.window_timer;
+        self.intensity = snapshot.intensity;
+        self.window_deaths = snapshot.window_deaths;
+        self.window_damage = snapshot.window_damage;
+        self.window_rings_passed = snapshot.window_rings_passed;
+        self.window_rings_missed = snapshot.window_rings_missed;
+        self.window_start_health = snapshot.window_start_health;
+        self.decisions = snapshot.decisions.clone();
+    }
+}
+
+impl EventListener for DifficultyDirector {
+    fn on_event(&mut self, event: &GameEvent) {
+        if !self.enabled {
+            return;
+        }
+
+        match event {
+            GameEvent::PlayerDamaged { amount, .. } => self.window_damage += amount,
+            GameEvent::PlayerDied { .. } => self.window_deaths += 1,
+            GameEvent::RingPassed { .. } => self.window_rings_passed += 1,
+            GameEvent::RingMissed { .. } => self.window_rings_missed += 1,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::DamageSource;
+    use macroquad::prelude::Vec3;
+
+    /// Headless run: every `hit_every` seconds the player takes `damage`,
+    /// and every 5 seconds they pass (or miss) a ring
+    fn simulate(director: &mut DifficultyDirector, seconds: f32, hit_every: Option<f32>, damage: f32, hits_rings: bool) {
+        let dt = 0.1;
+        let mut health = 100.0;
+        let mut since_hit = 0.0;
+        let mut since_ring = 0.0;
+        let mut t = 0.0;
+        while t < seconds {
+            if let Some(interval) = hit_every {
+                since_hit += dt;
+                if since_hit >= interval {
+                    since_hit = 0.0;
+                    health -= damage;
+                    director.on_event(&GameEvent::PlayerDamaged { amount: damage, source: DamageSource::BossProjectile });
+                    if health <= 0.0 {
+                        director.on_event(&GameEvent::PlayerDied { position: Vec3::ZERO, cause: DamageSource::BossContact });
+                        health = 100.0; // Respawn
+                    }
+                }
+            }
+
+            since_ring += dt;
+            if since_ring >= 5.0 {
+                since_ring = 0.0;
+                director.on_event(&if hits_rings {
+                    GameEvent::RingPassed { position: Vec3::ZERO, chain: 1, precision: 0.5 }
+                } else {
+                    GameEvent::RingMissed { position: Vec3::ZERO }
+                });
+            }
+
+            director.update(dt, health);
+            t += dt;
+        }
+    }
+
+    #[test]
+    fn test_disabled_director_does_nothing() {
+        let mut director = DifficultyDirector::new();
+        simulate(&mut director, 120.0, Some(1.0), 10.0, false);
+
+        assert!(director.decisions().is_empty());
+        assert_eq!(director.tuning(), DirectorTuning::from_intensity(0.0));
+    }
+
+    #[test]
+    fn test_struggling_player_gets_relief_within_bounds() {
+        let mut director = DifficultyDirector::new();
+        director.set_enabled(true);
+        simulate(&mut director, 300.0, Some(2.0), 15.0, false);
+
+        let decisions = director.decisions();
+        assert!(decisions.len() >= 19); // One per evaluation window
+        assert!(decisions.iter().all(|d| d.intensity_after <= d.intensity_before));
+        assert!(decisions.iter().any(|d| d.metrics.deaths > 0));
+
+        let tuning = director.tuning();
+        assert_eq!(tuning.spawn_rate, 1.0 - MAX_INTENSITY);
+        assert!(tuning.aggression < 1.0);
+        assert!(tuning.powerup_generosity > 1.0 && tuning.powerup_generosity <= 1.0 + MAX_INTENSITY);
+    }
+
+    #[test]
+    fn test_skilled_player_gets_pushed_within_bounds() {
+        let mut director = DifficultyDirector::new();
+        director.set_enabled(true);
+        simulate(&mut director, 300.0, None, 0.0, true);
+
+        assert!(director.decisions().iter().all(|d| d.intensity_after >= d.intensity_before));
+        let tuning = director.tuning();
+        assert_eq!(tuning.spawn_rate, 1.0 + MAX_INTENSITY);
+        assert!(tuning.aggression > 1.0);
+        assert!(tuning.powerup_generosity < 1.0);
+    }
+
+    #[test]
+    fn test_death_outweighs_an_otherwise_clean_window() {
+        let metrics = DirectorMetrics {
+            deaths: 1,
+            damage_per_minute: 0.0,
+            health_trend: 0.0,
+            ring_accuracy: Some(1.0),
+        };
+        assert!(DifficultyDirector::performance(&metrics) < -DEAD_ZONE);
+
+        let steady = DirectorMetrics { deaths: 0, damage_per_minute: TARGET_DAMAGE_PER_MINUTE, health_trend: 0.0, ring_accuracy: None };
+        assert_eq!(DifficultyDirector::performance(&steady), 0.0);
+    }
+}
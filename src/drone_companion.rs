@@ -1,5 +1,9 @@
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::events::{EventListener, GameEvent};
+use crate::fmath;
 use crate::player::Player;
+use crate::powerup::PowerupType;
 
 pub struct DroneProjectile {
     pub position: Vec3,
@@ -7,6 +11,15 @@ pub struct DroneProjectile {
     pub lifetime: f32,
 }
 
+/// Whether the drone was out at a checkpoint, where, and for how much longer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DroneSnapshot {
+    active: bool,
+    #[serde(with = "crate::snapshot::vec3")]
+    position: Vec3,
+    remaining_time: f32,
+}
+
 pub struct DroneCompanion {
     position: Vec3,
     velocity: Vec3,
@@ -89,9 +102,9 @@ impl DroneCompanion {
         match self.behavior {
             DroneBehavior::FollowPlayer => {
                 // Follow beside player with slight offset
-                let offset = vec3(-3.0 + (self.behavior_timer * 0.5).sin() * 0.5, 1.0, -2.0);
+                let offset = vec3(-3.0 + fmath::sin(self.behavior_timer * 0.5) * 0.5, 1.0, -2.0);
                 let target_pos = player_pos + offset;
-                let direction = (target_pos - self.position).normalize_or_zero();
+                let direction = fmath::normalize_or_zero(target_pos - self.position);
 
                 // Match player's forward speed
                 self.velocity.z = player_vel.z;
@@ -103,11 +116,11 @@ impl DroneCompanion {
                 // Fly ahead of player to clear path
                 let ahead_distance = 15.0;
                 let target_z = player_pos.z + ahead_distance;
-                let target_x = player_pos.x + (self.behavior_timer * 0.8).sin() * 4.0;
+                let target_x = player_pos.x + fmath::sin(self.behavior_timer * 0.8) * 4.0;
                 let target_y = player_pos.y + 1.0;
 
                 let target_pos = vec3(target_x, target_y, target_z);
-                let direction = (target_pos - self.position).normalize_or_zero();
+                let direction = fmath::normalize_or_zero(target_pos - self.position);
 
                 // Move faster when clearing ahead
                 self.velocity = direction * 12.0;
@@ -204,8 +217,34 @@ impl DroneCompanion {
         }
     }
 
+    pub fn snapshot(&self) -> DroneSnapshot {
+        DroneSnapshot {
+            active: self.active,
+            position: self.position,
+            remaining_time: self.remaining_time,
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &DroneSnapshot) {
+        self.projectiles.clear();
+        self.active = snapshot.active;
+        self.position = snapshot.position;
+        self.velocity = Vec3::ZERO;
+        self.remaining_time = snapshot.remaining_time;
+        self.behavior = DroneBehavior::FollowPlayer;
+        self.behavior_timer = 0.0;
+    }
+
     pub fn deactivate(&mut self) {
         self.active = false;
         self.projectiles.clear();
     }
 }
+
+impl EventListener for DroneCompanion {
+    fn on_event(&mut self, event: &GameEvent) {
+        if let GameEvent::PowerupCollected { powerup_type: PowerupType::DroneCompanion, position } = event {
+            self.activate(*position);
+        }
+    }
+}
@@ -1,8 +1,17 @@
 use macroquad::prelude::*;
-use macroquad::rand::gen_range;
-use crate::player::Player;
-
-#[derive(Clone, Copy)]
+use crate::rng::gen_range;
+use serde::{Deserialize, Serialize};
+use crate::collision::Shape;
+use crate::difficulty::Difficulty;
+use crate::events::{EventBus, GameEvent, HitSource};
+use crate::fmath;
+use crate::player::{self, Player};
+use crate::spatial_grid::SpatialGrid;
+
+/// Radius of player and drone projectiles when testing hits (forgiving on purpose)
+pub const PROJECTILE_HIT_RADIUS: f32 = 0.6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum EnemyType {
     Drone,      // Flies straight
     Seeker,     // Follows player
@@ -10,11 +19,26 @@ pub enum EnemyType {
     Turret,     // Stationary, shoots at player
 }
 
+impl EnemyType {
+    pub fn name(&self) -> &str {
+        match self {
+            EnemyType::Drone => "Drone",
+            EnemyType::Seeker => "Seeker",
+            EnemyType::Zigzag => "Zigzag",
+            EnemyType::Turret => "Turret",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Enemy {
+    #[serde(with = "crate::snapshot::vec3")]
     pub position: Vec3,
+    #[serde(with = "crate::snapshot::vec3")]
     velocity: Vec3,
     enemy_type: EnemyType,
     health: f32,
+    speed: f32, // Movement multiplier from difficulty
     time_alive: f32,
 }
 
@@ -32,6 +56,7 @@ impl Enemy {
             velocity,
             enemy_type,
             health: 30.0,
+            speed: 1.0,
             time_alive: 0.0,
         }
     }
@@ -46,13 +71,13 @@ impl Enemy {
             }
             EnemyType::Seeker => {
                 // Follow player
-                let direction = (player_pos - self.position).normalize();
-                self.velocity = direction * 4.0;
+                let direction = fmath::normalize(player_pos - self.position);
+                self.velocity = direction * 4.0 * self.speed;
                 self.position += self.velocity * dt;
             }
             EnemyType::Zigzag => {
                 // Zigzag pattern
-                let zigzag = (self.time_alive * 3.0).sin() * 5.0;
+                let zigzag = fmath::sin(self.time_alive * 3.0) * 5.0;
                 self.velocity.x = zigzag;
                 self.position += self.velocity * dt;
             }
@@ -64,13 +89,32 @@ impl Enemy {
         }
     }
 
+    fn size(&self) -> Vec3 {
+        match self.enemy_type {
+            EnemyType::Drone => vec3(0.5, 0.5, 0.8),
+            EnemyType::Seeker => vec3(0.7, 0.4, 0.7),
+            EnemyType::Zigzag => vec3(0.6, 0.3, 0.9),
+            EnemyType::Turret => vec3(0.8, 0.8, 0.8),
+        }
+    }
+
+    /// Collision description matching the drawn body
+    pub fn collision_shape(&self) -> Shape {
+        match self.enemy_type {
+            // Seekers tumble toward the player, so use a rotation-independent sphere
+            EnemyType::Seeker => Shape::sphere(self.position, 0.4),
+            _ => Shape::aabb(self.position, self.size()),
+        }
+    }
+
     fn draw(&self) {
-        let (color, size) = match self.enemy_type {
-            EnemyType::Drone => (Color::from_rgba(255, 100, 100, 255), vec3(0.5, 0.5, 0.8)),
-            EnemyType::Seeker => (Color::from_rgba(255, 0, 0, 255), vec3(0.7, 0.4, 0.7)),
-            EnemyType::Zigzag => (Color::from_rgba(255, 150, 0, 255), vec3(0.6, 0.3, 0.9)),
-            EnemyType::Turret => (Color::from_rgba(180, 0, 0, 255), vec3(0.8, 0.8, 0.8)),
+        let color = match self.enemy_type {
+            EnemyType::Drone => Color::from_rgba(255, 100, 100, 255),
+            EnemyType::Seeker => Color::from_rgba(255, 0, 0, 255),
+            EnemyType::Zigzag => Color::from_rgba(255, 150, 0, 255),
+            EnemyType::Turret => Color::from_rgba(180, 0, 0, 255),
         };
+        let size = self.size();
 
         // Draw enemy body
         draw_cube(self.position, size, None, color);
@@ -87,16 +131,45 @@ impl Enemy {
         self.health -= damage;
     }
 
+    /// Apply a projectile hit and report it (and the kill, if this hit was fatal)
+    fn take_hit(&mut self, damage: f32, source: HitSource, events: &mut EventBus) {
+        let was_alive = !self.is_dead();
+        self.take_damage(damage);
+
+        events.emit(GameEvent::EnemyHit {
+            enemy_type: self.enemy_type,
+            position: self.position,
+            source,
+        });
+        if was_alive && self.is_dead() {
+            events.emit(GameEvent::EnemyKilled {
+                enemy_type: self.enemy_type,
+                position: self.position,
+                source,
+            });
+        }
+    }
+
     fn is_dead(&self) -> bool {
         self.health <= 0.0
     }
 }
 
+/// Live enemies and spawn timers at a checkpoint
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EnemySnapshot {
+    enemies: Vec<Enemy>,
+    spawn_timer: f32,
+    spawn_cooldown: f32,
+}
+
 pub struct EnemyManager {
     enemies: Vec<Enemy>,
     spawn_timer: f32,
     spawn_interval: f32,
     spawn_cooldown: f32,
+    difficulty: Difficulty,
+    grid: SpatialGrid,
 }
 
 impl EnemyManager {
@@ -106,10 +179,12 @@ impl EnemyManager {
             spawn_timer: 0.0,
             spawn_interval: 2.0,
             spawn_cooldown: 0.0,
+            difficulty: Difficulty::default(),
+            grid: SpatialGrid::new(5.0),
         }
     }
 
-    pub fn update(&mut self, dt: f32, player: &Player) {
+    pub fn update(&mut self, dt: f32, player: &mut Player, events: &mut EventBus) {
         let player_pos = player.position();
 
         // Update spawn cooldown
@@ -123,8 +198,6 @@ impl EnemyManager {
             if self.spawn_timer >= self.spawn_interval {
                 self.spawn_enemy(player_pos);
                 self.spawn_timer = 0.0;
-                // Gradually increase spawn rate (but not too fast)
-                self.spawn_interval = (self.spawn_interval * 0.98).max(0.8);
             }
         }
 
@@ -132,14 +205,18 @@ impl EnemyManager {
         for enemy in &mut self.enemies {
             enemy.update(dt, player_pos);
         }
-
-        // Check collisions with player projectiles
-        let projectiles = player.get_projectiles();
-        for (_proj_idx, proj) in projectiles.iter().enumerate() {
-            for enemy in &mut self.enemies {
-                let distance = (enemy.position - proj.position).length();
-                if distance < 1.0 {
-                    enemy.take_damage(20.0);
+        self.rebuild_grid();
+
+        // Check collisions with player projectiles (swept over this frame's travel)
+        let mut candidates = Vec::new();
+        for proj in player.projectiles_mut() {
+            let start = proj.position - proj.velocity * dt;
+            self.query_segment(start, proj.position, PROJECTILE_HIT_RADIUS, &mut candidates);
+            for &index in &candidates {
+                let enemy = &mut self.enemies[index];
+                if enemy.collision_shape().sweep_sphere(start, proj.position, PROJECTILE_HIT_RADIUS).is_some() {
+                    enemy.take_hit(20.0, HitSource::Player, events);
+                    proj.land(events);
                 }
             }
         }
@@ -148,6 +225,32 @@ impl EnemyManager {
         self.enemies.retain(|enemy| {
             !enemy.is_dead() && enemy.position.z > player_pos.z - 30.0
         });
+        self.rebuild_grid();
+    }
+
+    /// Set the difficulty used for spawn rate and newly spawned enemies
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+        self.spawn_interval = difficulty.enemy_spawn_interval();
+    }
+
+    /// The broad-phase index, rebuilt on every update
+    pub fn grid(&self) -> &SpatialGrid {
+        &self.grid
+    }
+
+    /// Re-index enemies in the broad-phase grid (call after changing the enemy list)
+    pub fn rebuild_grid(&mut self) {
+        self.grid.clear();
+        for (index, enemy) in self.enemies.iter().enumerate() {
+            let (min, max) = enemy.collision_shape().bounds();
+            self.grid.insert(index, min.z, max.z);
+        }
+    }
+
+    /// Broad-phase candidates for a sphere of `radius` swept from `start` to `end`
+    fn query_segment(&self, start: Vec3, end: Vec3, radius: f32, results: &mut Vec<usize>) {
+        self.grid.query(start.z.min(end.z) - radius, start.z.max(end.z) + radius, results);
     }
 
     fn spawn_enemy(&mut self, player_pos: Vec3) {
@@ -156,18 +259,31 @@ impl EnemyManager {
         let spawn_y = gen_range(0.0, 5.0);
         let spawn_z = player_pos.z + gen_range(40.0, 60.0);
 
-        // Random enemy type with weighted distribution
-        let enemy_type = match gen_range(0, 10) {
-            0..=4 => EnemyType::Drone,
-            5..=7 => EnemyType::Zigzag,
-            8 => EnemyType::Seeker,
-            _ => EnemyType::Turret,
-        };
+        // Random enemy type, weighted by difficulty
+        let weights = self.difficulty.enemy_weights();
+        let total: u32 = weights.iter().map(|(_, weight)| weight).sum();
+        let mut roll = gen_range(0, total);
+        let mut enemy_type = EnemyType::Drone;
+        for (candidate, weight) in weights {
+            if roll < weight {
+                enemy_type = candidate;
+                break;
+            }
+            roll -= weight;
+        }
 
-        self.enemies.push(Enemy::new(
-            vec3(spawn_x, spawn_y, spawn_z),
-            enemy_type,
-        ));
+        self.spawn_enemy_at(vec3(spawn_x, spawn_y, spawn_z), enemy_type);
+    }
+
+    /// Spawn a specific enemy type at a fixed position
+    pub fn spawn_enemy_at(&mut self, position: Vec3, enemy_type: EnemyType) {
+        let mut enemy = Enemy::new(position, enemy_type);
+        enemy.speed = self.difficulty.enemy_speed();
+        enemy.velocity *= enemy.speed;
+        enemy.health = self.difficulty.enemy_health();
+        let (min, max) = enemy.collision_shape().bounds();
+        self.grid.insert(self.enemies.len(), min.z, max.z);
+        self.enemies.push(enemy);
     }
 
     pub fn draw(&self) {
@@ -176,27 +292,28 @@ impl EnemyManager {
         }
     }
 
-    pub fn check_collision(&self, player: &Player) -> bool {
-        let player_pos = player.position();
-        let collision_distance = 1.0;
-
-        for enemy in &self.enemies {
-            let distance = (enemy.position - player_pos).length();
-            if distance < collision_distance {
-                return true;
-            }
-        }
-
-        false
+    /// Type of the enemy the player flew into this frame, if any
+    pub fn check_collision(&self, player: &Player) -> Option<EnemyType> {
+        let start = player.previous_position();
+        let end = player.position();
+
+        let mut candidates = Vec::new();
+        self.query_segment(start, end, player::COLLISION_RADIUS, &mut candidates);
+        candidates
+            .into_iter()
+            .map(|index| &self.enemies[index])
+            .find(|enemy| enemy.collision_shape().sweep_sphere(start, end, player::COLLISION_RADIUS).is_some())
+            .map(|enemy| enemy.enemy_type)
     }
 
-    pub fn check_projectile_hit(&mut self, projectile_pos: Vec3) -> bool {
-        let hit_distance = 1.0;
-
-        for enemy in &mut self.enemies {
-            let distance = (enemy.position - projectile_pos).length();
-            if distance < hit_distance {
-                enemy.take_damage(20.0);
+    /// Test a projectile moving from `start` to `end` this frame against all enemies
+    pub fn check_projectile_hit(&mut self, start: Vec3, end: Vec3, source: HitSource, events: &mut EventBus) -> bool {
+        let mut candidates = Vec::new();
+        self.query_segment(start, end, PROJECTILE_HIT_RADIUS, &mut candidates);
+        for index in candidates {
+            let enemy = &mut self.enemies[index];
+            if enemy.collision_shape().sweep_sphere(start, end, PROJECTILE_HIT_RADIUS).is_some() {
+                enemy.take_hit(20.0, source, events);
                 return true;
             }
         }
@@ -211,24 +328,41 @@ impl EnemyManager {
             let distance = (enemy.position - position).length();
             distance > radius
         });
+        self.rebuild_grid();
         let cleared = initial_count - self.enemies.len();
-        #[cfg(debug_assertions)]
         if cleared > 0 {
-            println!("Cleared {} enemies around checkpoint", cleared);
+            log::debug!("Cleared {} enemies around checkpoint", cleared);
         }
     }
 
+    pub fn snapshot(&self) -> EnemySnapshot {
+        EnemySnapshot {
+            enemies: self.enemies.clone(),
+            spawn_timer: self.spawn_timer,
+            spawn_cooldown: self.spawn_cooldown,
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &EnemySnapshot) {
+        self.enemies = snapshot.enemies.clone();
+        self.spawn_timer = snapshot.spawn_timer;
+        self.spawn_cooldown = snapshot.spawn_cooldown;
+        self.rebuild_grid();
+    }
+
     /// Pause spawning for a duration (used after respawn)
     pub fn pause_spawning(&mut self, duration: f32) {
         self.spawn_cooldown = duration;
-        #[cfg(debug_assertions)]
-        println!("Enemy spawning paused for {:.1}s", duration);
+        log::debug!("Enemy spawning paused for {:.1}s", duration);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::difficulty::DifficultySetting;
+    use crate::player::Weapon;
+    use crate::events::{EventBus, GameEvent, HitSource};
 
     fn create_test_player(pos: Vec3) -> Player {
         let mut player = Player::new();
@@ -246,37 +380,37 @@ mod tests {
     #[test]
     fn test_enemy_spawning() {
         let mut manager = EnemyManager::new();
-        let player = create_test_player(vec3(0.0, 0.0, 0.0));
+        let mut player = create_test_player(vec3(0.0, 0.0, 0.0));
 
         // Fast-forward time to trigger spawn
         for _ in 0..3 {
-            manager.update(1.0, &player);
+            manager.update(1.0, &mut player, &mut EventBus::new());
         }
 
         // Should have spawned at least one enemy
-        assert!(manager.enemies.len() > 0);
+        assert!(!manager.enemies.is_empty());
     }
 
     #[test]
-    fn test_spawn_interval_decreases() {
+    fn test_spawn_interval_follows_difficulty() {
         let mut manager = EnemyManager::new();
         let initial_interval = manager.spawn_interval;
-        let player = create_test_player(vec3(0.0, 0.0, 0.0));
 
-        // Trigger several spawns
-        for _ in 0..10 {
-            manager.update(3.0, &player);
-        }
+        // Later in a harder level enemies come faster, tougher and quicker
+        let hard = Difficulty::new(2.0, 1.0, DifficultySetting::Hard);
+        manager.set_difficulty(hard);
+        assert!(manager.spawn_interval < initial_interval);
+        assert!(manager.spawn_interval >= 0.6);
 
-        // Spawn interval should decrease (but not below 0.8)
-        assert!(manager.spawn_interval <= initial_interval);
-        assert!(manager.spawn_interval >= 0.8);
+        manager.spawn_enemy_at(vec3(0.0, 0.0, 10.0), EnemyType::Drone);
+        assert_eq!(manager.enemies[0].health, hard.enemy_health());
+        assert!(manager.enemies[0].velocity.z < -5.0);
     }
 
     #[test]
     fn test_enemy_cleanup() {
         let mut manager = EnemyManager::new();
-        let player = create_test_player(vec3(0.0, 0.0, 100.0));
+        let mut player = create_test_player(vec3(0.0, 0.0, 100.0));
 
         // Add enemy far behind player
         manager.enemies.push(Enemy::new(
@@ -284,7 +418,7 @@ mod tests {
             EnemyType::Drone,
         ));
 
-        manager.update(0.1, &player);
+        manager.update(0.1, &mut player, &mut EventBus::new());
 
         // Enemy should be removed (too far behind)
         assert_eq!(manager.enemies.len(), 0);
@@ -296,12 +430,9 @@ mod tests {
         let player = create_test_player(vec3(0.0, 0.0, 10.0));
 
         // Add enemy at player position
-        manager.enemies.push(Enemy::new(
-            vec3(0.0, 0.0, 10.0),
-            EnemyType::Drone,
-        ));
+        manager.spawn_enemy_at(vec3(0.0, 0.0, 10.0), EnemyType::Drone);
 
-        assert!(manager.check_collision(&player));
+        assert!(manager.check_collision(&player).is_some());
     }
 
     #[test]
@@ -310,12 +441,9 @@ mod tests {
         let player = create_test_player(vec3(0.0, 0.0, 10.0));
 
         // Add enemy far from player
-        manager.enemies.push(Enemy::new(
-            vec3(20.0, 20.0, 50.0),
-            EnemyType::Drone,
-        ));
+        manager.spawn_enemy_at(vec3(20.0, 20.0, 50.0), EnemyType::Drone);
 
-        assert!(!manager.check_collision(&player));
+        assert!(manager.check_collision(&player).is_none());
     }
 
     #[test]
@@ -335,7 +463,7 @@ mod tests {
         player.projectiles[0].position = vec3(0.0, 0.0, 5.0);
 
         let initial_health = manager.enemies[0].health;
-        manager.update(0.1, &player);
+        manager.update(0.1, &mut player, &mut EventBus::new());
 
         // Enemy should take damage
         assert!(manager.enemies[0].health < initial_health);
@@ -356,7 +484,7 @@ mod tests {
         // Kill enemy by setting health to 0
         manager.enemies[0].health = 0.0;
 
-        manager.update(0.1, &player);
+        manager.update(0.1, &mut player, &mut EventBus::new());
 
         // Dead enemy should be removed
         assert_eq!(manager.enemies.len(), 0);
@@ -422,4 +550,42 @@ mod tests {
         assert_eq!(manager.enemies.len(), 1);
         assert!(manager.enemies[0].position.x > 20.0);
     }
+
+    #[test]
+    fn test_projectile_hit_uses_grid_after_movement() {
+        let mut manager = EnemyManager::new();
+        let mut player = create_test_player(vec3(0.0, 0.0, 0.0));
+
+        manager.spawn_enemy_at(vec3(0.0, 0.0, 20.0), EnemyType::Drone);
+        for _ in 0..10 {
+            manager.update(0.1, &mut player, &mut EventBus::new());
+        }
+
+        // Drone has moved 5 units toward the player since it was indexed
+        let mut events = EventBus::new();
+        assert!(manager.check_projectile_hit(vec3(0.0, 0.0, 14.0), vec3(0.0, 0.0, 16.0), HitSource::Drone, &mut events));
+        assert!(!manager.check_projectile_hit(vec3(0.0, 0.0, 19.0), vec3(0.0, 0.0, 21.0), HitSource::Drone, &mut events));
+    }
+
+    #[test]
+    fn test_kill_emits_event_once() {
+        let mut manager = EnemyManager::new();
+        let mut events = EventBus::new();
+        manager.spawn_enemy_at(vec3(0.0, 0.0, 10.0), EnemyType::Turret);
+
+        // Two 20-damage hits kill a 30 health enemy
+        let start = vec3(0.0, 0.0, 9.0);
+        let end = vec3(0.0, 0.0, 11.0);
+        manager.check_projectile_hit(start, end, HitSource::Player, &mut events);
+        manager.check_projectile_hit(start, end, HitSource::Player, &mut events);
+        manager.check_projectile_hit(start, end, HitSource::Player, &mut events);
+
+        let emitted = events.drain();
+        let kills = emitted
+            .iter()
+            .filter(|event| matches!(event, GameEvent::EnemyKilled { enemy_type: EnemyType::Turret, .. }))
+            .count();
+        assert_eq!(kills, 1);
+        assert_eq!(emitted.len(), 4);
+    }
 }
@@ -0,0 +1,139 @@
+use macroquad::prelude::*;
+use crate::boss::BossType;
+use crate::enemy::EnemyType;
+use crate::player::Weapon;
+use crate::powerup::PowerupType;
+use crate::terrain::ObstacleType;
+
+/// Who landed a hit on an enemy
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HitSource {
+    Player,
+    Drone,
+}
+
+/// What hurt the player
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DamageSource {
+    Terrain(ObstacleType),
+    Enemy(EnemyType),
+    BossContact,
+    BossProjectile,
+}
+
+impl DamageSource {
+    pub fn name(&self) -> &str {
+        match self {
+            DamageSource::Terrain(obstacle) => obstacle.name(),
+            DamageSource::Enemy(enemy) => enemy.name(),
+            DamageSource::BossContact => "Boss Collision",
+            DamageSource::BossProjectile => "Boss Projectile",
+        }
+    }
+}
+
+/// Something that happened during gameplay this frame.
+///
+/// Managers emit these instead of touching score, stats or effects directly;
+/// subscribers react when the bus is dispatched at the end of the frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameEvent {
+    EnemyHit { enemy_type: EnemyType, position: Vec3, source: HitSource },
+    EnemyKilled { enemy_type: EnemyType, position: Vec3, source: HitSource },
+    PowerupCollected { powerup_type: PowerupType, position: Vec3 },
+    RingPassed { position: Vec3, chain: u32, precision: f32 }, // precision: 1.0 = dead center
+    RingMissed { position: Vec3 },
+    RingCourseCompleted { gates: u32, perfect: bool },
+    NearMiss { position: Vec3 },
+    PlayerDamaged { amount: f32, source: DamageSource },
+    PlayerDied { position: Vec3, cause: DamageSource },
+    ShotFired { weapon: Weapon },  // One per projectile
+    ShotLanded { weapon: Weapon }, // A projectile's first hit on an enemy or boss
+    ExtraLife { lives: u32 },
+    CheckpointReached { number: usize, position: f32 },
+    BossHit { boss_type: BossType, weapon: Weapon },
+    BossPhaseChanged { boss_type: BossType, phase: u8 },
+    BossDefeated { boss_type: BossType },
+    LevelCompleted { outlasted_boss: bool }, // Time ran out mid-fight with the boss still standing
+}
+
+/// Anything that wants to react to gameplay events (scoring, stats, HUD effects...)
+pub trait EventListener {
+    fn on_event(&mut self, event: &GameEvent);
+}
+
+/// Per-frame queue of gameplay events
+pub struct EventBus {
+    queue: Vec<GameEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self { queue: Vec::new() }
+    }
+
+    pub fn emit(&mut self, event: GameEvent) {
+        self.queue.push(event);
+    }
+
+    /// Take every event emitted since the last dispatch, oldest first
+    pub fn drain(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.queue)
+    }
+
+    /// Deliver every queued event to each listener in order, then empty the queue
+    pub fn dispatch(&mut self, listeners: &mut [&mut dyn EventListener]) {
+        for event in self.drain() {
+            for listener in listeners.iter_mut() {
+                listener.on_event(&event);
+            }
+        }
+    }
+
+    /// Drop queued events without delivering them (e.g. when leaving a level)
+    pub fn clear(&mut self) {
+        self.queue.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Recorder {
+        seen: Vec<GameEvent>,
+    }
+
+    impl EventListener for Recorder {
+        fn on_event(&mut self, event: &GameEvent) {
+            self.seen.push(*event);
+        }
+    }
+
+    #[test]
+    fn test_dispatch_delivers_in_order_to_all_listeners() {
+        let mut bus = EventBus::new();
+        bus.emit(GameEvent::RingPassed { position: Vec3::ZERO, chain: 1, precision: 0.0 });
+        bus.emit(GameEvent::BossDefeated { boss_type: BossType::TutorialBoss });
+
+        let mut first = Recorder { seen: Vec::new() };
+        let mut second = Recorder { seen: Vec::new() };
+        bus.dispatch(&mut [&mut first, &mut second]);
+
+        assert_eq!(first.seen.len(), 2);
+        assert_eq!(first.seen, second.seen);
+        assert!(matches!(first.seen[0], GameEvent::RingPassed { .. }));
+        assert!(bus.drain().is_empty());
+    }
+
+    #[test]
+    fn test_clear_drops_events() {
+        let mut bus = EventBus::new();
+        bus.emit(GameEvent::PlayerDied { position: Vec3::ZERO, cause: DamageSource::BossContact });
+        bus.clear();
+
+        let mut recorder = Recorder { seen: Vec::new() };
+        bus.dispatch(&mut [&mut recorder]);
+        assert!(recorder.seen.is_empty());
+    }
+}
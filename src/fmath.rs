@@ -0,0 +1,61 @@
+use macroquad::prelude::*;
+
+// Trig for anything that moves the simulated world. The standard library's sin
+// and cos call the platform's libm, which differs between native builds and the
+// browser in the last bit or so, and a replay recorded on one would drift when
+// the leaderboard server checks it on the other. These go through the `libm`
+// crate, the same code on every target. Drawing can keep using std; only the
+// simulation has to agree.
+
+pub fn sin(x: f32) -> f32 {
+    libm::sinf(x)
+}
+
+pub fn cos(x: f32) -> f32 {
+    libm::cosf(x)
+}
+
+pub fn sqrt(x: f32) -> f32 {
+    libm::sqrtf(x)
+}
+
+/// `Quat::from_rotation_z`, without std's trig
+pub fn rotation_z(angle: f32) -> Quat {
+    let half = angle * 0.5;
+    Quat::from_xyzw(0.0, 0.0, sin(half), cos(half))
+}
+
+/// `Vec3::normalize`, without std's sqrt
+pub fn normalize(v: Vec3) -> Vec3 {
+    v / sqrt(v.dot(v))
+}
+
+/// `Vec3::normalize_or_zero`, without std's sqrt
+pub fn normalize_or_zero(v: Vec3) -> Vec3 {
+    let length = sqrt(v.dot(v));
+    if length > 0.0 && length.is_finite() {
+        v / length
+    } else {
+        Vec3::ZERO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_std_closely() {
+        for i in -100..100 {
+            let x = i as f32 * 0.173;
+            assert!((sin(x) - x.sin()).abs() < 1e-6);
+            assert!((cos(x) - x.cos()).abs() < 1e-6);
+            let ours = rotation_z(x) * Vec3::X;
+            let glams = Quat::from_rotation_z(x) * Vec3::X;
+            assert!((ours - glams).length() < 1e-5);
+        }
+        let v = vec3(3.0, -4.0, 12.0);
+        assert!((normalize(v) - v.normalize()).length() < 1e-6);
+        assert_eq!(normalize_or_zero(Vec3::ZERO), Vec3::ZERO);
+    }
+}
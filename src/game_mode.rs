@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// How a continent is played
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Standard,   // Survive the level's timer and beat its boss, for score
+    TimeAttack, // Race to a fixed distance against the continent's medal times
+    Endless,    // Fly on through every continent in turn until the lives run out
+    BossRush,   // Every continent's boss back to back, for time
+}
+
+impl GameMode {
+    pub fn all() -> &'static [GameMode] {
+        &[GameMode::Standard, GameMode::TimeAttack, GameMode::Endless, GameMode::BossRush]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Standard => "Standard",
+            GameMode::TimeAttack => "Time Attack",
+            GameMode::Endless => "Endless",
+            GameMode::BossRush => "Boss Rush",
+        }
+    }
+
+    /// The mode after this one, wrapping around
+    pub fn next(self) -> Self {
+        let all = Self::all();
+        let index = all.iter().position(|mode| *mode == self).unwrap_or(0);
+        all[(index + 1) % all.len()]
+    }
+}
@@ -3,9 +3,13 @@ use macroquad::prelude::*;
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GameState {
     Splash,
+    ProfileSelect,  // Profile picker, after the splash screen
     MainMenu,
+    Achievements,
+    Statistics,
     Options,
     LevelSelect,
+    Leaderboard,
     TutorialInstructions, // Tutorial instructions screen
     Tutorial,
     InGame,
@@ -73,10 +77,25 @@ impl GameStateManager {
         match (from, to) {
             // From Splash
             (GameState::Splash, GameState::MainMenu) => true,
+            (GameState::Splash, GameState::ProfileSelect) => true,
+
+            // From ProfileSelect
+            (GameState::ProfileSelect, GameState::MainMenu) => true,
 
             // From MainMenu
             (GameState::MainMenu, GameState::Options) => true,
+            (GameState::MainMenu, GameState::ProfileSelect) => true,
+            (GameState::MainMenu, GameState::Achievements) => true,
+            (GameState::MainMenu, GameState::Statistics) => true,
             (GameState::MainMenu, GameState::LevelSelect) => true,
+            (GameState::MainMenu, GameState::InGame) => true,    // Resume a suspended run
+            (GameState::MainMenu, GameState::BossFight) => true, // Resume mid boss fight
+
+            // From Achievements
+            (GameState::Achievements, GameState::MainMenu) => true,
+
+            // From Statistics
+            (GameState::Statistics, GameState::MainMenu) => true,
 
             // From Options
             (GameState::Options, GameState::MainMenu) => true,
@@ -85,6 +104,10 @@ impl GameStateManager {
             (GameState::LevelSelect, GameState::MainMenu) => true,
             (GameState::LevelSelect, GameState::TutorialInstructions) => true,
             (GameState::LevelSelect, GameState::InGame) => true,
+            (GameState::LevelSelect, GameState::Leaderboard) => true,
+
+            // From Leaderboard
+            (GameState::Leaderboard, GameState::LevelSelect) => true,
 
             // From TutorialInstructions
             (GameState::TutorialInstructions, GameState::Tutorial) => true,
@@ -95,12 +118,15 @@ impl GameStateManager {
             (GameState::Tutorial, GameState::GameOver) => true,
             (GameState::Tutorial, GameState::Checkpoint) => true,
             (GameState::Tutorial, GameState::LevelComplete) => true,
+            (GameState::Tutorial, GameState::BossFight) => true,
+            (GameState::Tutorial, GameState::MainMenu) => true,
 
             // From InGame
             (GameState::InGame, GameState::BossFight) => true,
             (GameState::InGame, GameState::Checkpoint) => true,
             (GameState::InGame, GameState::GameOver) => true,
             (GameState::InGame, GameState::MainMenu) => true,
+            (GameState::InGame, GameState::LevelComplete) => true,
 
             // From BossFight
             (GameState::BossFight, GameState::LevelComplete) => true,
@@ -117,12 +143,15 @@ impl GameStateManager {
 
             // From LevelComplete
             (GameState::LevelComplete, GameState::LevelSelect) => true,
+            (GameState::LevelComplete, GameState::Leaderboard) => true, // Name entry for a new top run
             (GameState::LevelComplete, GameState::MainMenu) => true,
 
             // From GameOver
             (GameState::GameOver, GameState::MainMenu) => true,
             (GameState::GameOver, GameState::LevelSelect) => true,
             (GameState::GameOver, GameState::InGame) => true, // Restart level
+            (GameState::GameOver, GameState::Checkpoint) => true, // Continue from checkpoint
+            (GameState::GameOver, GameState::Leaderboard) => true, // An endless run's board
 
             // Same state is always allowed
             _ if from == to => true,
@@ -211,4 +240,23 @@ mod tests {
         manager.update(0.016);
         assert_eq!(manager.current_state(), GameState::LevelComplete);
     }
+
+    #[test]
+    fn test_leaderboard_transitions() {
+        let manager = GameStateManager::new();
+        assert!(manager.can_transition(GameState::LevelSelect, GameState::Leaderboard));
+        assert!(manager.can_transition(GameState::LevelComplete, GameState::Leaderboard));
+        assert!(manager.can_transition(GameState::GameOver, GameState::Leaderboard));
+        assert!(manager.can_transition(GameState::Leaderboard, GameState::LevelSelect));
+        assert!(!manager.can_transition(GameState::Leaderboard, GameState::InGame));
+    }
+
+    #[test]
+    fn test_level_end_transitions() {
+        let manager = GameStateManager::new();
+        // A level can finish without a boss fight, and the tutorial has a boss too
+        assert!(manager.can_transition(GameState::InGame, GameState::LevelComplete));
+        assert!(manager.can_transition(GameState::Tutorial, GameState::BossFight));
+        assert!(manager.can_transition(GameState::Tutorial, GameState::MainMenu));
+    }
 }
@@ -0,0 +1,191 @@
+use macroquad::prelude::*;
+use crate::replay::Replay;
+use crate::rng;
+use crate::simulation::{Simulation, TICK};
+
+/// How much further along a checkpoint has to be than the furthest one so far
+/// to count as a new split. Respawning re-creates checkpoints the run already
+/// passed, a little either side of where they first were.
+const REPASS_MARGIN: f32 = 10.0;
+
+/// The tick each checkpoint was first reached on
+#[derive(Debug, Clone, Default)]
+struct Splits {
+    ticks: Vec<u32>,
+    furthest: Option<f32>, // Position of the furthest checkpoint reached
+}
+
+impl Splits {
+    /// Note a checkpoint at `position` reached on `tick`; true if it's a new split
+    fn reach(&mut self, position: f32, tick: u32) -> bool {
+        if self.furthest.is_some_and(|furthest| position < furthest + REPASS_MARGIN) {
+            return false;
+        }
+        self.furthest = Some(position);
+        self.ticks.push(tick);
+        true
+    }
+}
+
+/// A recorded run played back to follow: where its glider was after every tick,
+/// and its split at each checkpoint
+pub struct GhostTrack {
+    pub name: String, // Shown on the HUD
+    pub replay: Replay,
+    positions: Vec<Vec3>,
+    splits: Splits,
+}
+
+impl GhostTrack {
+    /// Simulate `replay` to trace its path. The new run shares the gameplay
+    /// randomness, so it's left as it was.
+    pub fn record(name: &str, replay: Replay) -> Result<Self, String> {
+        let rng_state = rng::state();
+        let mut positions = Vec::new();
+        let mut splits = Splits::default();
+        let played = Simulation::play_observed(&replay, |sim| {
+            positions.push(sim.player.position());
+            if let Some(position) = sim.checkpoints.get_last_checkpoint_position() {
+                splits.reach(position, sim.ticks());
+            }
+        });
+        rng::set_state(rng_state);
+        played?;
+
+        Ok(Self { name: name.to_string(), replay, positions, splits })
+    }
+
+    /// Where the ghost was once `ticks` had been played, until its run ended
+    pub fn position(&self, ticks: u32) -> Option<Vec3> {
+        ticks.checked_sub(1).and_then(|tick| self.positions.get(tick as usize)).copied()
+    }
+}
+
+/// Racing a ghost: draws it alongside the run and compares splits
+pub struct GhostRace {
+    track: GhostTrack,
+    splits: Splits,                   // The player's
+    last_split: Option<(usize, f32)>, // Checkpoint number, seconds behind the ghost (negative when ahead)
+}
+
+impl GhostRace {
+    pub fn new(track: GhostTrack) -> Self {
+        Self { track, splits: Splits::default(), last_split: None }
+    }
+
+    /// Follow the run; called after each of its ticks
+    pub fn update(&mut self, sim: &Simulation) {
+        if let Some(position) = sim.checkpoints.get_last_checkpoint_position() {
+            self.reach(position, sim.ticks());
+        }
+    }
+
+    fn reach(&mut self, position: f32, tick: u32) {
+        if !self.splits.reach(position, tick) {
+            return;
+        }
+        let index = self.splits.ticks.len() - 1;
+        if let Some(ghost_tick) = self.track.splits.ticks.get(index) {
+            let delta = tick as f32 - *ghost_tick as f32;
+            self.last_split = Some((index + 1, delta * TICK));
+        }
+    }
+
+    /// The run being raced
+    pub fn replay(&self) -> &Replay {
+        &self.track.replay
+    }
+
+    /// The ghost glider, translucent, where it was at this point in its run.
+    /// Drawn after the scene so what's behind it shows through.
+    pub fn draw(&self, sim: &Simulation) {
+        let Some(position) = self.track.position(sim.ticks()) else {
+            return;
+        };
+        draw_cube(position, vec3(0.5, 0.3, 1.0), None, Color::from_rgba(255, 255, 255, 90));
+        for side in [-1.0, 1.0] {
+            draw_cube(position + vec3(side, 0.0, 0.0), vec3(1.0, 0.1, 0.5), None, Color::from_rgba(200, 220, 255, 70));
+        }
+    }
+
+    /// Ghost panel under the timer: whose run it is and the latest split
+    pub fn draw_hud(&self) {
+        let hud_color = Color::from_rgba(0, 255, 255, 255);
+        let panel_width = 200.0;
+        let x = screen_width() - panel_width - 10.0;
+
+        draw_rectangle(x, 140.0, panel_width, 60.0, Color::from_rgba(0, 10, 20, 200));
+        draw_rectangle_lines(x, 140.0, panel_width, 60.0, 2.0, hud_color);
+        draw_text(&format!("GHOST: {}", self.track.name), x + 10.0, 160.0, 16.0, Color::from_rgba(200, 220, 255, 255));
+
+        if let Some((checkpoint, delta)) = self.last_split {
+            let color = if delta <= 0.0 {
+                Color::from_rgba(0, 255, 0, 255)
+            } else {
+                Color::from_rgba(255, 80, 80, 255)
+            };
+            draw_text(&format!("CP{}  {:+.2}s", checkpoint, delta), x + 10.0, 188.0, 22.0, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::Continent;
+    use crate::difficulty::DifficultySetting;
+    use crate::events::EventBus;
+    use crate::game_mode::GameMode;
+    use crate::player::PlayerControls;
+
+    #[test]
+    fn test_track_follows_replay() {
+        let mut sim = Simulation::new(Continent::NorthAmerica, GameMode::Standard, DifficultySetting::Easy, false, 4242);
+        let mut events = EventBus::new();
+        for tick in 0..60 * 20 {
+            if !sim.is_running() {
+                break;
+            }
+            let controls = PlayerControls { up: tick % 90 < 40, shoot: true, ..Default::default() };
+            sim.step(controls, &mut events);
+            events.clear();
+        }
+
+        let rng_state = rng::state();
+        let track = GhostTrack::record("BEST", sim.replay().unwrap().clone()).unwrap();
+        assert_eq!(rng::state(), rng_state);
+        assert_eq!(track.position(sim.ticks()), Some(sim.player.position()));
+        assert_eq!(track.position(sim.ticks() + 1), None);
+        assert_eq!(track.position(0), None);
+        assert!(!track.splits.ticks.is_empty());
+    }
+
+    #[test]
+    fn test_split_deltas() {
+        let mut ghost = Splits::default();
+        assert!(ghost.reach(75.0, 300));
+        assert!(ghost.reach(150.0, 600));
+
+        let mut race = GhostRace::new(GhostTrack {
+            name: "BEST".to_string(),
+            replay: Replay::new(Continent::Tutorial, DifficultySetting::Easy, false, 1),
+            positions: Vec::new(),
+            splits: ghost,
+        });
+        assert_eq!(race.last_split, None);
+
+        race.reach(76.0, 330);
+        let (checkpoint, delta) = race.last_split.unwrap();
+        assert_eq!(checkpoint, 1);
+        assert!((delta - 0.5).abs() < 1e-4, "half a second behind");
+
+        // A checkpoint re-created after a respawn isn't a new split
+        race.reach(74.0, 500);
+        assert_eq!(race.last_split.unwrap().0, 1);
+
+        race.reach(151.0, 580);
+        let (checkpoint, delta) = race.last_split.unwrap();
+        assert_eq!(checkpoint, 2);
+        assert!(delta < 0.0, "ahead of the ghost");
+    }
+}
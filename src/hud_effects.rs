@@ -0,0 +1,205 @@
+use macroquad::prelude::*;
+use crate::achievements::Achievement;
+use crate::events::{EventListener, GameEvent};
+use crate::rings::CENTER_PASS_FRACTION;
+
+const POPUP_DURATION: f32 = 1.5;
+const MAX_POPUPS: usize = 5;
+const TOAST_DURATION: f32 = 4.0;
+
+struct Popup {
+    text: String,
+    color: Color,
+    age: f32,
+}
+
+/// Short-lived HUD feedback (popups and damage flash) driven by gameplay events,
+/// plus achievement unlock toasts
+pub struct HudEffects {
+    popups: Vec<Popup>,
+    damage_flash: f32, // 0.0 - 1.0, fades out over time
+    toasts: Vec<Popup>, // Oldest first; only the front one is on screen
+}
+
+impl HudEffects {
+    pub fn new() -> Self {
+        Self {
+            popups: Vec::new(),
+            damage_flash: 0.0,
+            toasts: Vec::new(),
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for popup in &mut self.popups {
+            popup.age += dt;
+        }
+        self.popups.retain(|popup| popup.age < POPUP_DURATION);
+
+        self.damage_flash = (self.damage_flash - dt * 2.0).max(0.0);
+    }
+
+    /// Queue an "ACHIEVEMENT UNLOCKED" toast
+    pub fn show_achievement(&mut self, achievement: &Achievement) {
+        self.toasts.push(Popup {
+            text: achievement.name.to_string(),
+            color: Color::from_rgba(255, 215, 0, 255),
+            age: 0.0,
+        });
+    }
+
+    /// Toasts stay up across screens (unlocks often land on the level complete
+    /// screen), so they're updated and drawn every frame, separately from the HUD
+    pub fn update_toasts(&mut self, dt: f32) {
+        if let Some(toast) = self.toasts.first_mut() {
+            toast.age += dt;
+            if toast.age >= TOAST_DURATION {
+                self.toasts.remove(0);
+            }
+        }
+    }
+
+    pub fn draw_toasts(&self) {
+        let Some(toast) = self.toasts.first() else {
+            return;
+        };
+
+        // Slide in from the top right, slide back out at the end
+        let slide = (toast.age / 0.3).min(1.0).min((TOAST_DURATION - toast.age) / 0.3);
+        let (w, h) = (300.0, 60.0);
+        let x = screen_width() - (w + 20.0) * slide;
+        let y = 20.0;
+
+        draw_rectangle(x, y, w, h, Color::from_rgba(20, 20, 40, 230));
+        draw_rectangle_lines(x, y, w, h, 2.0, toast.color);
+        draw_text("ACHIEVEMENT UNLOCKED", x + 15.0, y + 22.0, 18.0, Color::from_rgba(200, 200, 200, 255));
+        draw_text(&toast.text, x + 15.0, y + 48.0, 26.0, toast.color);
+    }
+
+    pub fn clear(&mut self) {
+        self.popups.clear();
+        self.damage_flash = 0.0;
+    }
+
+    fn push_popup(&mut self, text: String, color: Color) {
+        // Newest popup on top; drop the oldest when the stack is full
+        self.popups.insert(0, Popup { text, color, age: 0.0 });
+        self.popups.truncate(MAX_POPUPS);
+    }
+
+    pub fn draw(&self) {
+        let screen_w = screen_width();
+        let screen_h = screen_height();
+
+        // Red vignette when hurt
+        if self.damage_flash > 0.0 {
+            let alpha = (self.damage_flash * 120.0) as u8;
+            let color = Color::from_rgba(255, 0, 0, alpha);
+            let border = 25.0;
+            draw_rectangle(0.0, 0.0, screen_w, border, color);
+            draw_rectangle(0.0, screen_h - border, screen_w, border, color);
+            draw_rectangle(0.0, border, border, screen_h - border * 2.0, color);
+            draw_rectangle(screen_w - border, border, border, screen_h - border * 2.0, color);
+        }
+
+        // Popups stack below the continent panel, drifting up as they fade
+        for (i, popup) in self.popups.iter().enumerate() {
+            let progress = popup.age / POPUP_DURATION;
+            let alpha = ((1.0 - progress) * 255.0) as u8;
+            let font_size = 28.0;
+            let text_width = measure_text(&popup.text, None, font_size as u16, 1.0).width;
+            let y = screen_h * 0.25 + i as f32 * 32.0 - progress * 20.0;
+
+            draw_text(
+                &popup.text,
+                screen_w / 2.0 - text_width / 2.0,
+                y,
+                font_size,
+                Color::new(popup.color.r, popup.color.g, popup.color.b, alpha as f32 / 255.0),
+            );
+        }
+    }
+}
+
+impl EventListener for HudEffects {
+    fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::EnemyKilled { .. } => {
+                self.push_popup("ENEMY DOWN".to_string(), Color::from_rgba(255, 150, 0, 255));
+            }
+            GameEvent::PowerupCollected { .. } => {
+                self.push_popup("POWERUP".to_string(), Color::from_rgba(0, 255, 100, 255));
+            }
+            GameEvent::RingPassed { chain, precision, .. } => {
+                if *precision >= 1.0 - CENTER_PASS_FRACTION {
+                    self.push_popup("CENTER!".to_string(), Color::from_rgba(255, 255, 0, 255));
+                }
+                let text = if *chain > 1 { format!("RING x{}", chain) } else { "RING!".to_string() };
+                self.push_popup(text, Color::from_rgba(0, 255, 255, 255));
+            }
+            GameEvent::RingMissed { .. } => {
+                self.push_popup("RING MISSED".to_string(), Color::from_rgba(150, 150, 150, 255));
+            }
+            GameEvent::RingCourseCompleted { perfect: true, .. } => {
+                self.push_popup("PERFECT COURSE!".to_string(), Color::from_rgba(255, 215, 0, 255));
+            }
+            GameEvent::NearMiss { .. } => {
+                self.push_popup("NEAR MISS!".to_string(), Color::from_rgba(255, 0, 255, 255));
+            }
+            GameEvent::PlayerDamaged { amount, .. } => {
+                // Continuous contact damage arrives in small per-frame amounts
+                self.damage_flash = self.damage_flash.max((amount / 15.0).clamp(0.2, 1.0));
+            }
+            GameEvent::ExtraLife { .. } => {
+                self.push_popup("EXTRA LIFE!".to_string(), Color::from_rgba(0, 255, 100, 255));
+            }
+            GameEvent::CheckpointReached { number, .. } => {
+                self.push_popup(format!("CHECKPOINT {}", number), Color::from_rgba(255, 255, 0, 255));
+            }
+            GameEvent::BossPhaseChanged { phase, .. } => {
+                self.push_popup(format!("BOSS PHASE {}", phase), Color::from_rgba(255, 50, 50, 255));
+            }
+            GameEvent::BossDefeated { boss_type } => {
+                self.push_popup(format!("{} DEFEATED!", boss_type.name()), Color::from_rgba(255, 215, 0, 255));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::DamageSource;
+
+    #[test]
+    fn test_popups_expire() {
+        let mut effects = HudEffects::new();
+        effects.on_event(&GameEvent::RingPassed { position: Vec3::ZERO, chain: 1, precision: 0.0 });
+        assert_eq!(effects.popups.len(), 1);
+
+        effects.update(POPUP_DURATION + 0.1);
+        assert!(effects.popups.is_empty());
+    }
+
+    #[test]
+    fn test_damage_flash_fades() {
+        let mut effects = HudEffects::new();
+        effects.on_event(&GameEvent::PlayerDamaged { amount: 15.0, source: DamageSource::BossProjectile });
+        assert_eq!(effects.damage_flash, 1.0);
+
+        effects.update(1.0);
+        assert_eq!(effects.damage_flash, 0.0);
+    }
+
+    #[test]
+    fn test_toasts_show_one_at_a_time() {
+        let mut effects = HudEffects::new();
+        effects.show_achievement(&crate::achievements::ACHIEVEMENTS[0]);
+        effects.show_achievement(&crate::achievements::ACHIEVEMENTS[1]);
+
+        effects.update_toasts(TOAST_DURATION);
+        assert_eq!(effects.toasts.len(), 1);
+        assert_eq!(effects.toasts[0].age, 0.0); // The second hasn't started yet
+    }
+}
@@ -0,0 +1,293 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::assets::Continent;
+use crate::difficulty::DifficultySetting;
+use crate::storage::{self, SharedStorage};
+
+/// Runs kept on each continent's board per difficulty
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// Storage directory for the replays of runs on the boards
+const REPLAY_DIR: &str = "replays";
+
+/// One finished run on a leaderboard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub name: String,    // As entered after the run
+    pub profile: String, // Name of the profile that flew it
+    pub score: u32,
+    pub time: f32,  // Seconds to clear the level
+    pub date: u64,  // Unix seconds
+    pub seed: u64,  // Seed the run's random generation started from
+    pub replay: Option<String>, // Storage key of the run's replay, when one was recorded
+    #[serde(default)]
+    pub distance: f32, // How far an endless run got; 0 for level runs
+}
+
+impl LeaderboardEntry {
+    /// Whether this run places above `other`: higher score, then faster time
+    fn beats(&self, other: &LeaderboardEntry) -> bool {
+        self.score > other.score || (self.score == other.score && self.time < other.time)
+    }
+
+    /// Whether this endless run places above `other`: further, then higher score
+    fn beats_endless(&self, other: &LeaderboardEntry) -> bool {
+        self.distance > other.distance || (self.distance == other.distance && self.score > other.score)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct LeaderboardFile {
+    boards: HashMap<String, Vec<LeaderboardEntry>>, // By `board_key`, best first
+}
+
+/// The top runs per continent and difficulty on this machine. Shared by every
+/// profile, so it's kept next to `profiles.json` rather than in a save.
+pub struct LeaderboardStore {
+    storage: SharedStorage,
+    key: String,
+    file: LeaderboardFile,
+}
+
+impl LeaderboardStore {
+    pub fn new() -> Self {
+        Self::open(storage::platform_storage(), "leaderboards.json")
+    }
+
+    /// Open the boards under `key`; an unreadable file starts them over empty
+    pub fn open(storage: SharedStorage, key: &str) -> Self {
+        let file = storage
+            .read(key)
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self { storage, key: key.to_string(), file }
+    }
+
+    fn board_key(continent: Continent, difficulty: DifficultySetting) -> String {
+        format!("{}/{}", continent.name(), difficulty.name())
+    }
+
+    /// Endless runs aren't of any one continent, so they have boards of their own
+    fn endless_key(difficulty: DifficultySetting) -> String {
+        format!("Endless/{}", difficulty.name())
+    }
+
+    /// A board's runs, best first
+    pub fn top(&self, continent: Continent, difficulty: DifficultySetting) -> &[LeaderboardEntry] {
+        self.board(&Self::board_key(continent, difficulty))
+    }
+
+    /// An endless board's runs, furthest first
+    pub fn endless_top(&self, difficulty: DifficultySetting) -> &[LeaderboardEntry] {
+        self.board(&Self::endless_key(difficulty))
+    }
+
+    fn board(&self, key: &str) -> &[LeaderboardEntry] {
+        self.file.boards.get(key).map_or(&[], |board| board.as_slice())
+    }
+
+    /// Where `entry` would place (0 is first), or `None` if it wouldn't make the board
+    pub fn rank(&self, continent: Continent, difficulty: DifficultySetting, entry: &LeaderboardEntry) -> Option<usize> {
+        self.rank_on(&Self::board_key(continent, difficulty), entry, LeaderboardEntry::beats)
+    }
+
+    /// `rank` for an endless run
+    pub fn endless_rank(&self, difficulty: DifficultySetting, entry: &LeaderboardEntry) -> Option<usize> {
+        self.rank_on(&Self::endless_key(difficulty), entry, LeaderboardEntry::beats_endless)
+    }
+
+    fn rank_on(&self, key: &str, entry: &LeaderboardEntry, beats: fn(&LeaderboardEntry, &LeaderboardEntry) -> bool) -> Option<usize> {
+        let board = self.board(key);
+        let rank = board.iter().position(|other| beats(entry, other)).unwrap_or(board.len());
+        (rank < LEADERBOARD_SIZE).then_some(rank)
+    }
+
+    /// Put a run on its board, dropping whatever falls off the bottom, and write the
+    /// boards out. The run's `replay` text is kept for as long as the run stays on
+    /// the board. Returns where it placed, or `None` if it didn't make the board.
+    pub fn submit(
+        &mut self,
+        continent: Continent,
+        difficulty: DifficultySetting,
+        entry: LeaderboardEntry,
+        replay: Option<&str>,
+    ) -> Result<Option<usize>, Box<dyn std::error::Error>> {
+        self.submit_to(&Self::board_key(continent, difficulty), entry, replay, LeaderboardEntry::beats)
+    }
+
+    /// `submit` for an endless run
+    pub fn submit_endless(
+        &mut self,
+        difficulty: DifficultySetting,
+        entry: LeaderboardEntry,
+        replay: Option<&str>,
+    ) -> Result<Option<usize>, Box<dyn std::error::Error>> {
+        self.submit_to(&Self::endless_key(difficulty), entry, replay, LeaderboardEntry::beats_endless)
+    }
+
+    fn submit_to(
+        &mut self,
+        key: &str,
+        mut entry: LeaderboardEntry,
+        replay: Option<&str>,
+        beats: fn(&LeaderboardEntry, &LeaderboardEntry) -> bool,
+    ) -> Result<Option<usize>, Box<dyn std::error::Error>> {
+        let Some(rank) = self.rank_on(key, &entry, beats) else {
+            return Ok(None);
+        };
+
+        if let Some(replay) = replay {
+            let key = format!("{}/{}-{}.txt", REPLAY_DIR, entry.date, entry.seed);
+            self.storage.write(&key, replay)?;
+            entry.replay = Some(key);
+        }
+
+        let board = self.file.boards.entry(key.to_string()).or_default();
+        board.insert(rank, entry);
+        for dropped in board.drain(LEADERBOARD_SIZE.min(board.len())..) {
+            if let Some(key) = dropped.replay {
+                self.storage.remove(&key).ok();
+            }
+        }
+
+        self.storage.write(&self.key, &serde_json::to_string(&self.file)?)?;
+        Ok(Some(rank))
+    }
+
+    /// The replay text of a run on the boards, if it has one
+    pub fn replay(&self, entry: &LeaderboardEntry) -> Option<String> {
+        entry.replay.as_ref().and_then(|key| self.storage.read(key).ok().flatten())
+    }
+}
+
+/// Current time as Unix seconds, for dating runs
+pub fn now() -> u64 {
+    macroquad::miniquad::date::now() as u64
+}
+
+/// "2026-10-18" for a Unix timestamp (UTC)
+pub fn format_date(unix_seconds: u64) -> String {
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let days = (unix_seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::FileStorage;
+    use std::rc::Rc;
+
+    fn temp_storage(name: &str) -> SharedStorage {
+        let dir = std::env::temp_dir().join(format!("glidewars_leaderboard_{}_{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        Rc::new(FileStorage::new(dir))
+    }
+
+    fn run(name: &str, score: u32, time: f32) -> LeaderboardEntry {
+        LeaderboardEntry {
+            name: name.to_string(),
+            profile: "PLAYER".to_string(),
+            score,
+            time,
+            date: 0,
+            seed: 1,
+            replay: None,
+            distance: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_board_keeps_best_runs_in_order() {
+        let mut store = LeaderboardStore::open(temp_storage("order"), "leaderboards.json");
+        for score in 1..=LEADERBOARD_SIZE as u32 {
+            store.submit(Continent::Europe, DifficultySetting::Normal, run("AAA", score * 100, 200.0), None).unwrap();
+        }
+        assert_eq!(store.top(Continent::Europe, DifficultySetting::Normal)[0].score, 1000);
+
+        // Too low once the board is full
+        assert_eq!(store.rank(Continent::Europe, DifficultySetting::Normal, &run("BBB", 50, 100.0)), None);
+
+        // Same score as third place but faster, so it goes above it
+        let rank = store.submit(Continent::Europe, DifficultySetting::Normal, run("CCC", 800, 150.0), None).unwrap();
+        assert_eq!(rank, Some(2));
+        let board = store.top(Continent::Europe, DifficultySetting::Normal);
+        assert_eq!(board.len(), LEADERBOARD_SIZE);
+        assert_eq!(board[2].name, "CCC");
+        assert_eq!(board.last().unwrap().score, 200);
+
+        // Other difficulties and continents have boards of their own
+        assert!(store.top(Continent::Europe, DifficultySetting::Hard).is_empty());
+        assert!(store.top(Continent::Asia, DifficultySetting::Normal).is_empty());
+    }
+
+    #[test]
+    fn test_boards_reload_from_storage() {
+        let storage = temp_storage("reload");
+        let mut store = LeaderboardStore::open(storage.clone(), "leaderboards.json");
+        store.submit(Continent::Africa, DifficultySetting::Hard, run("ACE", 4200, 300.0), None).unwrap();
+
+        let reopened = LeaderboardStore::open(storage.clone(), "leaderboards.json");
+        assert_eq!(reopened.top(Continent::Africa, DifficultySetting::Hard), &[run("ACE", 4200, 300.0)]);
+
+        // A corrupt file starts the boards over rather than failing
+        storage.write("leaderboards.json", "not json").unwrap();
+        let reopened = LeaderboardStore::open(storage, "leaderboards.json");
+        assert!(reopened.top(Continent::Africa, DifficultySetting::Hard).is_empty());
+    }
+
+    #[test]
+    fn test_replays_kept_while_on_board() {
+        let storage = temp_storage("replays");
+        let mut store = LeaderboardStore::open(storage.clone(), "leaderboards.json");
+        let mut first = run("ACE", 100, 200.0);
+        first.date = 1;
+        store.submit(Continent::Asia, DifficultySetting::Easy, first, Some("replay text")).unwrap();
+        let entry = store.top(Continent::Asia, DifficultySetting::Easy)[0].clone();
+        assert_eq!(store.replay(&entry).as_deref(), Some("replay text"));
+
+        // Pushed off the bottom of the board, it takes its replay with it
+        for score in 2..=LEADERBOARD_SIZE as u32 + 1 {
+            store.submit(Continent::Asia, DifficultySetting::Easy, run("BOB", score * 100, 200.0), None).unwrap();
+        }
+        assert!(!storage.exists(entry.replay.as_ref().unwrap()));
+    }
+
+    #[test]
+    fn test_endless_boards_rank_by_distance() {
+        let mut store = LeaderboardStore::open(temp_storage("endless"), "leaderboards.json");
+        let endless_run = |name: &str, distance: f32, score: u32| LeaderboardEntry { distance, ..run(name, score, 600.0) };
+
+        store.submit_endless(DifficultySetting::Normal, endless_run("FAR", 5000.0, 100), None).unwrap();
+        store.submit_endless(DifficultySetting::Normal, endless_run("RICH", 3000.0, 9000), None).unwrap();
+        // As far as the leader, so the score decides
+        let rank = store.submit_endless(DifficultySetting::Normal, endless_run("BEST", 5000.0, 200), None).unwrap();
+        assert_eq!(rank, Some(0));
+
+        let names: Vec<&str> = store.endless_top(DifficultySetting::Normal).iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["BEST", "FAR", "RICH"]);
+        assert_eq!(store.endless_rank(DifficultySetting::Normal, &endless_run("NEW", 4000.0, 0)), Some(2));
+
+        // Separate from every continent's boards and the other difficulties
+        assert!(store.endless_top(DifficultySetting::Hard).is_empty());
+        assert!(Continent::all().into_iter().all(|c| store.top(c, DifficultySetting::Normal).is_empty()));
+    }
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_792_281_600), "2026-10-18");
+    }
+}
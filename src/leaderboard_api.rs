@@ -0,0 +1,90 @@
+//! What the game and the online leaderboard server send each other.
+//!
+//! Shared by both sides: the server includes this file with `#[path]`, so it
+//! must not depend on anything else in the game.
+//!
+//! - `POST /scores` with a `ScoreSubmission` replies with a `SubmitResponse`. The
+//!   server plays the run's replay through the game's simulation and rejects the
+//!   submission unless it finishes the level with the same score and time, and
+//!   was recorded by the profile it's submitted under.
+//! - `GET /scores?continent=..&difficulty=..&limit=N` replies with the top N `RankedEntry`s
+//! - `GET /scores?continent=..&difficulty=..&limit=N&around=NAME` replies with the N
+//!   entries around NAME's best run, or none if NAME has no run on that board
+//! - `GET /health` replies `ok`
+
+use serde::{Deserialize, Serialize};
+
+pub const SCORES_PATH: &str = "/scores";
+
+/// Most entries returned by one fetch
+pub const MAX_LIMIT: usize = 50;
+
+/// Longest name the server accepts (matches the profile name limit)
+pub const MAX_NAME_LEN: usize = 12;
+
+/// Largest replay the server stores, in bytes of its text encoding
+pub const MAX_REPLAY_LEN: usize = 512 * 1024;
+
+/// Boards exist for every continent and difficulty, by display name
+pub const CONTINENT_NAMES: [&str; 7] = ["Tutorial", "North America", "South America", "Europe", "Asia", "Africa", "Oceania"];
+pub const DIFFICULTY_NAMES: [&str; 3] = ["Easy", "Normal", "Hard"];
+
+/// A finished run sent to the server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreSubmission {
+    pub continent: String,
+    pub difficulty: String,
+    pub name: String,
+    pub score: u32,
+    pub time: f32,
+    pub seed: u64,
+    pub replay: Option<String>, // The run's input replay (`Replay::to_text`); required
+}
+
+impl ScoreSubmission {
+    /// Why the server would turn this submission away, if it would
+    pub fn validate(&self) -> Result<(), String> {
+        if !CONTINENT_NAMES.contains(&self.continent.as_str()) {
+            return Err(format!("unknown continent {:?}", self.continent));
+        }
+        if !DIFFICULTY_NAMES.contains(&self.difficulty.as_str()) {
+            return Err(format!("unknown difficulty {:?}", self.difficulty));
+        }
+        let name_len = self.name.trim().chars().count();
+        if name_len == 0 || name_len > MAX_NAME_LEN {
+            return Err(format!("name must be 1 to {} characters", MAX_NAME_LEN));
+        }
+        if !self.time.is_finite() || self.time <= 0.0 {
+            return Err("time must be positive".to_string());
+        }
+        match &self.replay {
+            None => return Err("a replay is required".to_string()),
+            Some(replay) if replay.len() > MAX_REPLAY_LEN => return Err("replay is too large".to_string()),
+            Some(_) => {}
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubmitResponse {
+    pub rank: usize, // 1 is first, counting every run on the board
+}
+
+/// One run on a board as the server reports it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankedEntry {
+    pub rank: usize, // 1 is first
+    pub name: String,
+    pub score: u32,
+    pub time: f32,
+    pub date: u64, // Unix seconds, as received by the server
+    pub seed: u64,
+    pub has_replay: bool,
+}
+
+/// The error body of any request the server rejects
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
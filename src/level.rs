@@ -1,5 +1,8 @@
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
 use crate::assets::Continent;
+use crate::difficulty::{Difficulty, DifficultySetting};
+use crate::game_mode::GameMode;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct LevelConfig {
@@ -66,6 +69,114 @@ impl LevelConfig {
     }
 }
 
+/// Time attack medals, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Medal {
+    Gold,
+    Silver,
+    Bronze,
+}
+
+impl Medal {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Medal::Gold => "Gold",
+            Medal::Silver => "Silver",
+            Medal::Bronze => "Bronze",
+        }
+    }
+}
+
+/// A continent's time attack course: the distance to the finish and the time
+/// each medal needs. Gold takes boosting most of the way, which means flying
+/// the ring gates to keep the boost topped up; bronze is the time at cruising speed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeAttackConfig {
+    pub distance: f32, // Z position of the finish
+    pub gold: f32,     // Seconds
+    pub silver: f32,
+    pub bronze: f32,
+}
+
+impl TimeAttackConfig {
+    pub fn for_continent(continent: Continent) -> Self {
+        let (distance, gold, silver, bronze) = match continent {
+            Continent::Tutorial => (1000.0, 80.0, 88.0, 100.0),
+            Continent::NorthAmerica => (2000.0, 160.0, 176.0, 200.0),
+            Continent::SouthAmerica => (2200.0, 176.0, 194.0, 220.0),
+            Continent::Europe => (2400.0, 192.0, 211.0, 240.0),
+            Continent::Asia => (2600.0, 208.0, 229.0, 260.0),
+            Continent::Africa => (2800.0, 224.0, 246.0, 280.0),
+            Continent::Oceania => (3000.0, 240.0, 264.0, 300.0),
+        };
+        Self { distance, gold, silver, bronze }
+    }
+
+    /// The best medal a finishing time earns, if any
+    pub fn medal(&self, time: f32) -> Option<Medal> {
+        if time <= self.gold {
+            Some(Medal::Gold)
+        } else if time <= self.silver {
+            Some(Medal::Silver)
+        } else if time <= self.bronze {
+            Some(Medal::Bronze)
+        } else {
+            None
+        }
+    }
+
+    /// Each medal with its target time, best first
+    pub fn targets(&self) -> [(Medal, f32); 3] {
+        [(Medal::Gold, self.gold), (Medal::Silver, self.silver), (Medal::Bronze, self.bronze)]
+    }
+}
+
+/// The continents an endless run flies through, in order, over and over
+pub const ENDLESS_ROUTE: [Continent; 6] = [
+    Continent::NorthAmerica,
+    Continent::SouthAmerica,
+    Continent::Europe,
+    Continent::Asia,
+    Continent::Africa,
+    Continent::Oceania,
+];
+/// Distance an endless run spends over each continent
+pub const ENDLESS_LEG_DISTANCE: f32 = 1500.0;
+/// Distance between endless mini-bosses
+pub const MINI_BOSS_INTERVAL: f32 = 1200.0;
+/// Distance over which endless difficulty climbs by one ramp
+const ENDLESS_RAMP_DISTANCE: f32 = 3000.0;
+
+/// The continents whose bosses a boss rush fights, in order: the same tour as endless
+pub const BOSS_RUSH_ROUTE: [Continent; 6] = ENDLESS_ROUTE;
+/// Seconds of flying before a boss rush's first fight and between the rest
+pub const BOSS_RUSH_INTERLUDE: f32 = 5.0;
+/// How far through its own level each boss is met: the boss rush fights them
+/// as hard as they are there
+const BOSS_RUSH_PROGRESS: f32 = 0.9;
+
+/// "3:05.2" for 185.2 seconds
+pub fn format_race_time(seconds: f32) -> String {
+    let tenths = (seconds * 10.0).round() as u32;
+    format!("{}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
+
+/// Level timer and progress flags at a checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelSnapshot {
+    elapsed_time: f32,
+    player_distance: f32,
+    boss_spawned: bool,
+    level_complete: bool,
+    last_checkpoint_distance: f32,
+    #[serde(default)]
+    mini_bosses: u32,
+    #[serde(default)]
+    rush_fight: usize,
+    #[serde(default)]
+    next_fight_time: f32,
+}
+
 pub struct LevelManager {
     config: LevelConfig,
     elapsed_time: f32,
@@ -73,10 +184,16 @@ pub struct LevelManager {
     boss_spawned: bool,
     level_complete: bool,
     last_checkpoint_distance: f32,
+    setting: DifficultySetting,
+    mode: GameMode,
+    time_attack: TimeAttackConfig,
+    mini_bosses: u32, // Endless mini-bosses met so far
+    rush_fight: usize,    // Boss rush fights won, so also the index of the next one
+    next_fight_time: f32, // When the boss rush's interlude ends, in level time
 }
 
 impl LevelManager {
-    pub fn new(continent: Continent) -> Self {
+    pub fn new(continent: Continent, setting: DifficultySetting) -> Self {
         Self {
             config: LevelConfig::for_continent(continent),
             elapsed_time: 0.0,
@@ -84,21 +201,50 @@ impl LevelManager {
             boss_spawned: false,
             level_complete: false,
             last_checkpoint_distance: 0.0,
+            setting,
+            mode: GameMode::Standard,
+            time_attack: TimeAttackConfig::for_continent(continent),
+            mini_bosses: 0,
+            rush_fight: 0,
+            next_fight_time: BOSS_RUSH_INTERLUDE,
         }
     }
 
+    /// Play the level as `mode`; set before it starts
+    pub fn set_mode(&mut self, mode: GameMode) {
+        self.mode = mode;
+    }
+
+    pub fn mode(&self) -> GameMode {
+        self.mode
+    }
+
     pub fn update(&mut self, dt: f32, player_z_position: f32) {
         self.elapsed_time += dt;
         self.player_distance = player_z_position;
 
-        // Check if boss should spawn
-        if !self.boss_spawned && self.elapsed_time >= self.config.boss_spawn_time {
-            self.boss_spawned = true;
-        }
-
-        // Check if level is complete
-        if self.elapsed_time >= self.config.duration {
-            self.level_complete = true;
+        match self.mode {
+            GameMode::Standard => {
+                // Check if boss should spawn
+                if !self.boss_spawned && self.elapsed_time >= self.config.boss_spawn_time {
+                    self.boss_spawned = true;
+                }
+
+                // Check if level is complete
+                if self.elapsed_time >= self.config.duration {
+                    self.level_complete = true;
+                }
+            }
+            // A straight race to the finish, without a boss in the way
+            GameMode::TimeAttack => {
+                if self.player_distance >= self.time_attack.distance {
+                    self.level_complete = true;
+                }
+            }
+            // Never over: no timer, no finish and no continent boss, just mini-bosses
+            GameMode::Endless => {}
+            // Over once the last boss is beaten (`mark_rush_boss_defeated`)
+            GameMode::BossRush => {}
         }
     }
 
@@ -110,7 +256,45 @@ impl LevelManager {
         self.level_complete
     }
 
+    /// Whether an endless run has flown far enough for its next mini-boss
+    pub fn should_spawn_mini_boss(&self) -> bool {
+        self.mode == GameMode::Endless && self.player_distance >= (self.mini_bosses + 1) as f32 * MINI_BOSS_INTERVAL
+    }
+
+    pub fn mark_mini_boss_spawned(&mut self) {
+        self.mini_bosses += 1;
+    }
+
+    /// Whether a boss rush's interlude is over and its next boss is due
+    pub fn should_spawn_rush_boss(&self) -> bool {
+        self.mode == GameMode::BossRush && self.rush_fight < BOSS_RUSH_ROUTE.len() && self.elapsed_time >= self.next_fight_time
+    }
+
+    /// A boss rush fight won: on to the next one after an interlude, or the rush is over
+    pub fn mark_rush_boss_defeated(&mut self) {
+        self.rush_fight += 1;
+        self.next_fight_time = self.elapsed_time + BOSS_RUSH_INTERLUDE;
+        if self.rush_fight >= BOSS_RUSH_ROUTE.len() {
+            self.level_complete = true;
+        }
+    }
+
+    /// Boss rush fights won so far
+    pub fn rush_fights_won(&self) -> usize {
+        self.rush_fight
+    }
+
+    /// Seconds until the boss rush's next fight
+    pub fn interlude_remaining(&self) -> f32 {
+        (self.next_fight_time - self.elapsed_time).max(0.0)
+    }
+
     pub fn should_create_checkpoint(&self) -> bool {
+        // A boss rush checkpoints at the start of each fight instead
+        if self.mode == GameMode::BossRush {
+            return false;
+        }
+
         // Create checkpoint every checkpoint_interval distance
         self.player_distance - self.last_checkpoint_distance >= self.config.checkpoint_interval
     }
@@ -119,6 +303,31 @@ impl LevelManager {
         self.last_checkpoint_distance = self.player_distance;
     }
 
+    pub fn snapshot(&self) -> LevelSnapshot {
+        LevelSnapshot {
+            elapsed_time: self.elapsed_time,
+            player_distance: self.player_distance,
+            boss_spawned: self.boss_spawned,
+            level_complete: self.level_complete,
+            last_checkpoint_distance: self.last_checkpoint_distance,
+            mini_bosses: self.mini_bosses,
+            rush_fight: self.rush_fight,
+            next_fight_time: self.next_fight_time,
+        }
+    }
+
+    /// Wind the level timer back to a checkpoint
+    pub fn restore(&mut self, snapshot: &LevelSnapshot) {
+        self.elapsed_time = snapshot.elapsed_time;
+        self.player_distance = snapshot.player_distance;
+        self.boss_spawned = snapshot.boss_spawned;
+        self.level_complete = snapshot.level_complete;
+        self.last_checkpoint_distance = snapshot.last_checkpoint_distance;
+        self.mini_bosses = snapshot.mini_bosses;
+        self.rush_fight = snapshot.rush_fight;
+        self.next_fight_time = snapshot.next_fight_time;
+    }
+
     pub fn elapsed_time(&self) -> f32 {
         self.elapsed_time
     }
@@ -127,14 +336,61 @@ impl LevelManager {
         (self.config.duration - self.elapsed_time).max(0.0)
     }
 
+    /// Distance left to the time attack finish
+    pub fn remaining_distance(&self) -> f32 {
+        (self.time_attack.distance - self.player_distance).max(0.0)
+    }
+
+    /// How far the player has flown
+    pub fn distance(&self) -> f32 {
+        self.player_distance
+    }
+
+    /// The continent being flown over: the level's own, in endless mode whichever
+    /// leg of the route the player is on, and in a boss rush the one whose boss
+    /// is being fought (or is next)
+    pub fn continent(&self) -> Continent {
+        match self.mode {
+            GameMode::Endless => {
+                let leg = (self.player_distance.max(0.0) / ENDLESS_LEG_DISTANCE) as usize;
+                ENDLESS_ROUTE[leg % ENDLESS_ROUTE.len()]
+            }
+            GameMode::BossRush => BOSS_RUSH_ROUTE[self.rush_fight.min(BOSS_RUSH_ROUTE.len() - 1)],
+            GameMode::Standard | GameMode::TimeAttack => self.config.continent,
+        }
+    }
+
+    pub fn time_attack(&self) -> &TimeAttackConfig {
+        &self.time_attack
+    }
+
+    pub fn setting(&self) -> DifficultySetting {
+        self.setting
+    }
+
     pub fn config(&self) -> &LevelConfig {
         &self.config
     }
 
     pub fn difficulty_multiplier(&self) -> f32 {
-        // Gradually increase difficulty over time
-        let progress = self.elapsed_time / self.config.duration;
-        self.config.difficulty_curve * (1.0 + progress * 0.5)
+        self.difficulty().level
+    }
+
+    /// Current difficulty: continent curve, ramped over the level (its time, or
+    /// its distance in time attack), scaled by the setting. Endless runs ramp
+    /// with distance for as long as they last; a boss rush takes each boss's
+    /// continent as it is when that boss turns up.
+    pub fn difficulty(&self) -> Difficulty {
+        let progress = match self.mode {
+            GameMode::Standard => self.elapsed_time / self.config.duration,
+            GameMode::TimeAttack => (self.player_distance / self.time_attack.distance).min(1.0),
+            GameMode::Endless => return Difficulty::endless(self.player_distance / ENDLESS_RAMP_DISTANCE, self.setting),
+            GameMode::BossRush => {
+                let curve = LevelConfig::for_continent(self.continent()).difficulty_curve;
+                return Difficulty::new(curve, BOSS_RUSH_PROGRESS, self.setting);
+            }
+        };
+        Difficulty::new(self.config.difficulty_curve, progress, self.setting)
     }
 
     pub fn reset(&mut self) {
@@ -143,6 +399,9 @@ impl LevelManager {
         self.boss_spawned = false;
         self.level_complete = false;
         self.last_checkpoint_distance = 0.0;
+        self.mini_bosses = 0;
+        self.rush_fight = 0;
+        self.next_fight_time = BOSS_RUSH_INTERLUDE;
     }
 
     pub fn checkpoint_count(&self) -> u32 {
@@ -151,7 +410,11 @@ impl LevelManager {
 
     pub fn total_checkpoints(&self) -> u32 {
         // Estimate total checkpoints based on expected travel distance
-        let expected_distance = 10.0 * self.config.duration; // Assuming 10 units/sec speed
+        let expected_distance = match self.mode {
+            GameMode::Standard => 10.0 * self.config.duration, // Assuming 10 units/sec speed
+            GameMode::TimeAttack => self.time_attack.distance,
+            GameMode::Endless | GameMode::BossRush => return 0, // Nothing to count towards
+        };
         (expected_distance / self.config.checkpoint_interval) as u32
     }
 }
@@ -169,7 +432,7 @@ mod tests {
 
     #[test]
     fn test_level_manager_creation() {
-        let manager = LevelManager::new(Continent::NorthAmerica);
+        let manager = LevelManager::new(Continent::NorthAmerica, DifficultySetting::Normal);
         assert_eq!(manager.elapsed_time(), 0.0);
         assert!(!manager.is_complete());
         assert!(!manager.should_spawn_boss());
@@ -177,20 +440,20 @@ mod tests {
 
     #[test]
     fn test_boss_spawn_timing() {
-        let mut manager = LevelManager::new(Continent::NorthAmerica);
+        let mut manager = LevelManager::new(Continent::NorthAmerica, DifficultySetting::Normal);
 
         // Before boss spawn time
         manager.update(200.0, 0.0);
         assert!(!manager.should_spawn_boss());
 
-        // After boss spawn time
-        manager.update(100.0, 0.0);
+        // After boss spawn time, before the level ends
+        manager.update(80.0, 0.0);
         assert!(manager.should_spawn_boss());
     }
 
     #[test]
     fn test_level_completion() {
-        let mut manager = LevelManager::new(Continent::Tutorial);
+        let mut manager = LevelManager::new(Continent::Tutorial, DifficultySetting::Normal);
 
         // Complete the level
         manager.update(250.0, 0.0);
@@ -199,7 +462,7 @@ mod tests {
 
     #[test]
     fn test_checkpoint_creation() {
-        let mut manager = LevelManager::new(Continent::NorthAmerica);
+        let mut manager = LevelManager::new(Continent::NorthAmerica, DifficultySetting::Normal);
 
         // Move player forward
         manager.update(10.0, 80.0);
@@ -212,7 +475,7 @@ mod tests {
 
     #[test]
     fn test_difficulty_multiplier() {
-        let mut manager = LevelManager::new(Continent::NorthAmerica);
+        let mut manager = LevelManager::new(Continent::NorthAmerica, DifficultySetting::Normal);
 
         let initial_difficulty = manager.difficulty_multiplier();
 
@@ -223,9 +486,123 @@ mod tests {
         assert!(mid_difficulty > initial_difficulty);
     }
 
+    #[test]
+    fn test_difficulty_setting_scales_level() {
+        let easy = LevelManager::new(Continent::Europe, DifficultySetting::Easy);
+        let hard = LevelManager::new(Continent::Europe, DifficultySetting::Hard);
+
+        assert!(easy.difficulty().level < hard.difficulty().level);
+        assert!(easy.difficulty().damage_taken < hard.difficulty().damage_taken);
+    }
+
+    #[test]
+    fn test_time_attack_ends_at_distance() {
+        let mut manager = LevelManager::new(Continent::Europe, DifficultySetting::Normal);
+        manager.set_mode(GameMode::TimeAttack);
+        let distance = manager.time_attack().distance;
+
+        // No time limit and no boss
+        manager.update(1000.0, distance - 1.0);
+        assert!(!manager.is_complete());
+        assert!(!manager.should_spawn_boss());
+        assert_eq!(manager.remaining_distance(), 1.0);
+
+        manager.update(0.1, distance);
+        assert!(manager.is_complete());
+    }
+
+    #[test]
+    fn test_endless_never_ends() {
+        let mut manager = LevelManager::new(Continent::NorthAmerica, DifficultySetting::Normal);
+        manager.set_mode(GameMode::Endless);
+
+        manager.update(10_000.0, 100.0);
+        assert!(!manager.is_complete());
+        assert!(!manager.should_spawn_boss());
+        assert!(!manager.should_spawn_mini_boss());
+        assert_eq!(manager.continent(), Continent::NorthAmerica);
+
+        // Mini-bosses every interval, one at a time
+        manager.update(0.1, MINI_BOSS_INTERVAL);
+        assert!(manager.should_spawn_mini_boss());
+        manager.mark_mini_boss_spawned();
+        assert!(!manager.should_spawn_mini_boss());
+        let checkpoint = manager.snapshot();
+
+        // The route cycles back round, and the difficulty keeps climbing
+        let early = manager.difficulty();
+        let lap = ENDLESS_LEG_DISTANCE * ENDLESS_ROUTE.len() as f32;
+        manager.update(0.1, lap + ENDLESS_LEG_DISTANCE * 2.0);
+        assert_eq!(manager.continent(), Continent::Europe);
+        assert!(manager.difficulty().level > early.level);
+        assert!(manager.should_spawn_mini_boss());
+
+        // A respawn winds the mini-boss count back with everything else
+        manager.mark_mini_boss_spawned();
+        manager.restore(&checkpoint);
+        manager.update(0.1, MINI_BOSS_INTERVAL * 2.0);
+        assert!(manager.should_spawn_mini_boss());
+    }
+
+    #[test]
+    fn test_boss_rush_fights_each_continent_in_turn() {
+        let mut manager = LevelManager::new(Continent::NorthAmerica, DifficultySetting::Normal);
+        manager.set_mode(GameMode::BossRush);
+        assert!(!manager.should_create_checkpoint());
+
+        // A moment to get going before the first fight
+        manager.update(1.0, 10.0);
+        assert!(!manager.should_spawn_rush_boss());
+        assert_eq!(manager.interlude_remaining(), BOSS_RUSH_INTERLUDE - 1.0);
+        manager.update(BOSS_RUSH_INTERLUDE, 60.0);
+
+        let mut previous_level = 0.0;
+        for (fight, continent) in BOSS_RUSH_ROUTE.into_iter().enumerate() {
+            assert!(manager.should_spawn_rush_boss());
+            assert_eq!(manager.continent(), continent);
+            assert_eq!(manager.rush_fights_won(), fight);
+            assert!(manager.difficulty().level > previous_level, "each boss is tougher than the last");
+            previous_level = manager.difficulty().level;
+
+            manager.update(30.0, 60.0);
+            manager.mark_rush_boss_defeated();
+            if fight + 1 < BOSS_RUSH_ROUTE.len() {
+                // An interlude before the next
+                assert!(!manager.should_spawn_rush_boss());
+                assert!(!manager.is_complete());
+                manager.update(BOSS_RUSH_INTERLUDE, 60.0);
+            }
+        }
+
+        assert!(manager.is_complete());
+        assert!(!manager.should_spawn_rush_boss());
+        assert!(!manager.should_spawn_boss());
+    }
+
+    #[test]
+    fn test_medals() {
+        let config = TimeAttackConfig::for_continent(Continent::Asia);
+        assert_eq!(config.medal(config.gold), Some(Medal::Gold));
+        assert_eq!(config.medal(config.gold + 0.1), Some(Medal::Silver));
+        assert_eq!(config.medal(config.bronze), Some(Medal::Bronze));
+        assert_eq!(config.medal(config.bronze + 0.1), None);
+
+        // Every continent's targets get harder the better the medal
+        for continent in Continent::all() {
+            let config = TimeAttackConfig::for_continent(continent);
+            assert!(config.gold < config.silver && config.silver < config.bronze);
+        }
+    }
+
+    #[test]
+    fn test_format_race_time() {
+        assert_eq!(format_race_time(185.24), "3:05.2");
+        assert_eq!(format_race_time(59.96), "1:00.0");
+    }
+
     #[test]
     fn test_level_reset() {
-        let mut manager = LevelManager::new(Continent::Tutorial);
+        let mut manager = LevelManager::new(Continent::Tutorial, DifficultySetting::Normal);
         manager.update(100.0, 50.0);
 
         manager.reset();
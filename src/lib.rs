@@ -0,0 +1,34 @@
+//! The game without its window: the world, the rules that score it and the
+//! simulation that steps them, plus saved-run and leaderboard formats.
+//!
+//! The game binary draws and drives these; the leaderboard server replays runs
+//! through them and the benches measure them.
+// The game's types were written for the binary, not as a library API
+#![allow(clippy::new_without_default, clippy::should_implement_trait)]
+
+pub mod assets;
+pub mod boss;
+pub mod checkpoint;
+pub mod collision;
+pub mod difficulty;
+pub mod director;
+pub mod drone_companion;
+pub mod enemy;
+pub mod events;
+pub mod fmath;
+pub mod game_mode;
+pub mod leaderboard_api;
+pub mod level;
+pub mod lives;
+pub mod player;
+pub mod powerup;
+pub mod replay;
+pub mod rings;
+pub mod rng;
+pub mod scoring;
+pub mod simulation;
+pub mod snapshot;
+pub mod spatial_grid;
+pub mod storage;
+pub mod suspend;
+pub mod terrain;
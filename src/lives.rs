@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+use crate::difficulty::DifficultySetting;
+use crate::events::{EventBus, GameEvent};
+
+/// Lives, continues and extra-life thresholds for one difficulty setting
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LivesConfig {
+    pub starting_lives: u32,
+    pub max_lives: u32,
+    pub continues: u32,
+    pub extra_life_every: u32, // Score between extra lives
+    pub continue_penalty: f32, // Fraction of score lost when continuing
+}
+
+impl LivesConfig {
+    pub fn for_setting(setting: DifficultySetting) -> Self {
+        match setting {
+            DifficultySetting::Easy => Self {
+                starting_lives: 5,
+                max_lives: 9,
+                continues: 5,
+                extra_life_every: 15_000,
+                continue_penalty: 0.25,
+            },
+            DifficultySetting::Normal => Self {
+                starting_lives: 3,
+                max_lives: 6,
+                continues: 3,
+                extra_life_every: 20_000,
+                continue_penalty: 0.5,
+            },
+            DifficultySetting::Hard => Self {
+                starting_lives: 2,
+                max_lives: 4,
+                continues: 1,
+                extra_life_every: 30_000,
+                continue_penalty: 0.5,
+            },
+        }
+    }
+}
+
+/// Tracks lives and continues for the current run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LivesManager {
+    config: LivesConfig,
+    lives: u32,
+    continues_left: u32,
+    next_extra_life_at: u32,
+    pending_penalty: Option<f32>, // Applied once the continue's respawn restores the score
+}
+
+impl LivesManager {
+    pub fn new(config: LivesConfig) -> Self {
+        Self {
+            config,
+            lives: config.starting_lives,
+            continues_left: config.continues,
+            next_extra_life_at: config.extra_life_every,
+            pending_penalty: None,
+        }
+    }
+
+    /// Award extra lives for every score threshold crossed since the last check
+    pub fn check_extra_life(&mut self, score: u32, events: &mut EventBus) {
+        while score >= self.next_extra_life_at {
+            self.next_extra_life_at += self.config.extra_life_every;
+            if self.lives < self.config.max_lives {
+                self.lives += 1;
+                events.emit(GameEvent::ExtraLife { lives: self.lives });
+            }
+        }
+    }
+
+    /// Spend a life, returning false when none are left (game over)
+    pub fn lose_life(&mut self) -> bool {
+        self.lives = self.lives.saturating_sub(1);
+        self.lives > 0
+    }
+
+    pub fn can_continue(&self) -> bool {
+        self.continues_left > 0
+    }
+
+    /// Spend a continue: lives refill and the score penalty is queued for the respawn
+    pub fn use_continue(&mut self) -> bool {
+        if !self.can_continue() {
+            return false;
+        }
+        self.continues_left -= 1;
+        self.lives = self.config.starting_lives;
+        self.pending_penalty = Some(self.config.continue_penalty);
+        true
+    }
+
+    /// Score penalty owed by a continue, if one was just used
+    pub fn take_continue_penalty(&mut self) -> Option<f32> {
+        self.pending_penalty.take()
+    }
+
+    pub fn lives(&self) -> u32 {
+        self.lives
+    }
+
+    pub fn continues_left(&self) -> u32 {
+        self.continues_left
+    }
+
+    pub fn continue_penalty(&self) -> f32 {
+        self.config.continue_penalty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_harder_settings_are_stingier() {
+        let easy = LivesConfig::for_setting(DifficultySetting::Easy);
+        let hard = LivesConfig::for_setting(DifficultySetting::Hard);
+        assert!(easy.starting_lives > hard.starting_lives);
+        assert!(easy.continues > hard.continues);
+        assert!(easy.extra_life_every < hard.extra_life_every);
+    }
+
+    #[test]
+    fn test_running_out_of_lives_then_continuing() {
+        let mut lives = LivesManager::new(LivesConfig::for_setting(DifficultySetting::Hard));
+        assert!(lives.lose_life());
+        assert!(!lives.lose_life()); // Game over
+
+        assert!(lives.use_continue());
+        assert_eq!(lives.lives(), 2);
+        assert_eq!(lives.take_continue_penalty(), Some(0.5));
+        assert_eq!(lives.take_continue_penalty(), None);
+
+        // Hard only has one continue
+        assert!(lives.lose_life());
+        assert!(!lives.lose_life());
+        assert!(!lives.use_continue());
+    }
+
+    #[test]
+    fn test_extra_lives_at_thresholds() {
+        let mut lives = LivesManager::new(LivesConfig::for_setting(DifficultySetting::Normal));
+        let mut events = EventBus::new();
+
+        lives.check_extra_life(19_999, &mut events);
+        assert_eq!(lives.lives(), 3);
+
+        // Jumping past two thresholds at once awards both
+        lives.check_extra_life(40_500, &mut events);
+        assert_eq!(lives.lives(), 5);
+        assert_eq!(events.drain().len(), 2);
+
+        // Capped at max lives
+        lives.check_extra_life(200_000, &mut events);
+        assert_eq!(lives.lives(), 6);
+    }
+}
@@ -1,60 +1,108 @@
 use macroquad::prelude::*;
 
-mod player;
-mod terrain;
-mod enemy;
-mod powerup;
 mod camera_system;
 mod game_state;
 mod input_manager;
 mod scene_manager;
-mod assets;
-mod level;
-mod checkpoint;
-mod boss;
 mod ui;
 mod save_system;
-mod rings;
-mod drone_companion;
 mod clouds;
 mod air_particles;
-
-use player::Player;
-use terrain::TerrainManager;
-use enemy::EnemyManager;
-use powerup::PowerupManager;
+mod hud_effects;
+mod profiles;
+mod profile_export;
+mod achievements;
+mod stats;
+mod leaderboard;
+mod online_leaderboard;
+mod ghost;
+
+// The world and its rules are in the library, shared with the leaderboard server
+use glidewars::{
+    assets, boss, checkpoint, difficulty, drone_companion, events, game_mode, leaderboard_api, level, lives,
+    player, replay, rings, rng, scoring, simulation, storage, suspend,
+};
+
+use player::{Player, PlayerControls};
 use camera_system::GameCamera;
 use game_state::GameState;
 use input_manager::InputManager;
 use scene_manager::SceneManager;
 use assets::{AssetManager, Continent};
-use level::LevelManager;
+use level::{LevelManager, Medal, TimeAttackConfig, BOSS_RUSH_ROUTE};
+use game_mode::GameMode;
+use difficulty::DifficultySetting;
+use lives::LivesManager;
 use checkpoint::CheckpointManager;
 use boss::{Boss, BossType};
-use ui::{SplashScreen, MainMenu, OptionsMenu, LevelSelectScreen, TutorialInstructions};
-use rings::RingManager;
+use ui::{SplashScreen, MainMenu, OptionsMenu, LevelSelectScreen, TutorialInstructions, ProfileSelectScreen, AchievementsScreen, StatisticsScreen, LeaderboardScreen};
 use drone_companion::DroneCompanion;
 use clouds::CloudManager;
 use air_particles::AirParticleSystem;
-use save_system::SaveManager;
+use save_system::{BossRushRecord, SaveManager, Settings};
+use profiles::ProfileManager;
+use profile_export::ProfileExport;
+use suspend::SuspendStore;
+use leaderboard::{LeaderboardEntry, LeaderboardStore, LEADERBOARD_SIZE};
+use online_leaderboard::{OnlineLeaderboard, OnlineReply};
+use events::{EventBus, EventListener};
+use hud_effects::HudEffects;
+use scoring::{ScoreCategory, ScoreKeeper};
+use replay::Replay;
+use simulation::{Phase, Simulation, TICK};
+use ghost::{GhostRace, GhostTrack};
+
+/// Most frame time simulated in one frame; after a longer stall the game slows
+/// down rather than running dozens of ticks at once
+const MAX_CATCH_UP: f32 = 0.25;
+
+/// Prints the library's debug messages (checkpoints, bosses, director decisions)
+/// to the console. Only installed in debug builds; anywhere else running the
+/// simulation, such as the leaderboard server, they go nowhere.
+#[cfg(debug_assertions)]
+struct ConsoleLogger;
+
+#[cfg(debug_assertions)]
+impl log::Log for ConsoleLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        println!("{}", record.args());
+    }
+
+    fn flush(&self) {}
+}
 
 #[macroquad::main("Glide Wars")]
 async fn main() {
+    #[cfg(debug_assertions)]
+    {
+        static LOGGER: ConsoleLogger = ConsoleLogger;
+        if log::set_logger(&LOGGER).is_ok() {
+            log::set_max_level(log::LevelFilter::Debug);
+        }
+    }
+
     let mut scene_manager = SceneManager::new();
     let mut input_manager = InputManager::new();
     let asset_manager = AssetManager::default();
-    let mut save_manager = SaveManager::new();
+    let mut profiles = ProfileManager::new();
+    let (mut save_manager, mut suspend_store) = open_active_profile(&profiles);
+
+    // Closing the window mid-level suspends the run instead of losing it
+    prevent_quit();
 
     // Game state
-    let mut player = Player::new();
-    let mut terrain = TerrainManager::new();
-    let mut enemies = EnemyManager::new();
-    let mut powerups = PowerupManager::new();
-    let mut rings = RingManager::new();
-    let mut drone = DroneCompanion::new();
+    let mut run: Option<Simulation> = None; // The level being played
+    let mut tick_time = 0.0; // Frame time not simulated yet
+    let mut ghost: Option<GhostRace> = None; // Raced alongside the run
     let mut clouds = CloudManager::new();
     let mut air_particles = AirParticleSystem::new();
     let mut camera = GameCamera::new();
+    let mut events = EventBus::new();
+    let mut hud_effects = HudEffects::new();
 
     // Level management
     // UI screens
@@ -63,21 +111,34 @@ async fn main() {
     let mut options_menu = OptionsMenu::new();
     let mut level_select_screen = LevelSelectScreen::new();
     let mut tutorial_instructions = TutorialInstructions::new();
+    let mut profile_select_screen = ProfileSelectScreen::new();
+    let mut achievements_screen = AchievementsScreen::new();
+    let mut statistics_screen = StatisticsScreen::new();
+    let mut leaderboard_screen = LeaderboardScreen::new();
+    profile_select_screen.set_profiles(profiles.profiles(), &profiles.active().id);
+    main_menu.set_profile_name(&profiles.active().name);
+    let mut pending_import: Option<ProfileExport> = None; // Found by IMPORT, waiting for merge/overwrite
 
     // Apply saved settings to options menu
-    options_menu.set_from_settings(
-        save_manager.data().settings.sound_volume,
-        save_manager.data().settings.music_volume,
-        save_manager.data().settings.difficulty as usize,
-    );
+    apply_settings_to_options(&mut options_menu, &save_manager.data().settings);
 
-    let mut level_manager: Option<LevelManager> = None;
-    let mut checkpoint_manager = CheckpointManager::new();
-    let mut boss: Option<Boss> = None;
+    let mut leaderboards = LeaderboardStore::new();
+    let online = OnlineLeaderboard::from_env();
     let mut current_continent = Continent::Tutorial;
+    let mut current_mode = GameMode::Standard;
     let mut level_select_synced = false;
+    let mut pending_replay: Option<String> = None; // Replay of the run waiting for a name on its board
+    let mut chosen_ghost: Option<(String, Replay)> = None; // Picked from a board to race, with whose run it was
 
     loop {
+        if is_quit_requested() {
+            if let Some(sim) = &run {
+                suspend_on_leaving(&mut suspend_store, scene_manager.current_state(), sim);
+            }
+            save_manager.auto_save();
+            break;
+        }
+
         clear_background(BLACK);
 
         let dt = get_frame_time();
@@ -91,7 +152,7 @@ async fn main() {
         input_manager.resize(current_width, current_height);
 
         // Update scene manager
-        scene_manager.update(dt, &input_manager);
+        scene_manager.update(dt);
 
         // Handle states
         match scene_manager.current_state() {
@@ -105,18 +166,151 @@ async fn main() {
                 }
 
                 if splash_screen.is_completed() {
-                    scene_manager.request_transition(GameState::MainMenu);
+                    scene_manager.request_transition(GameState::ProfileSelect);
+                }
+            }
+
+            GameState::ProfileSelect => {
+                let action = profile_select_screen.update(dt);
+                profile_select_screen.draw();
+
+                match action.clone() {
+                    ui::profile_select::ProfileAction::Export(id) => {
+                        let name = profile_name(&profiles, &id);
+                        let export = if id == profiles.active().id {
+                            save_manager.export(&name)
+                        } else {
+                            profiles.open_save(&id).export(&name)
+                        };
+                        match export_profile(&export) {
+                            Ok(message) => profile_select_screen.show_message(&message),
+                            Err(e) => profile_select_screen.show_error(&e),
+                        }
+                    }
+                    ui::profile_select::ProfileAction::Import(id) => match find_import() {
+                        Ok(export) => {
+                            profile_select_screen.show_import_preview(&id, &export.profile_name, &export.summary());
+                            pending_import = Some(export);
+                        }
+                        Err(e) => profile_select_screen.show_error(&e),
+                    },
+                    ui::profile_select::ProfileAction::ConfirmImport(id, mode) => {
+                        if let Some(export) = pending_import.take() {
+                            let result = if id == profiles.active().id {
+                                save_manager.import(&export, mode);
+                                apply_settings_to_options(&mut options_menu, &save_manager.data().settings);
+                                level_select_synced = false;
+                                save_manager.save()
+                            } else {
+                                let mut target = profiles.open_save(&id);
+                                target.import(&export, mode);
+                                target.save()
+                            };
+                            match result {
+                                Ok(()) => profile_select_screen.show_message(&format!(
+                                    "IMPORTED {} INTO {}",
+                                    export.profile_name,
+                                    profile_name(&profiles, &id)
+                                )),
+                                Err(e) => profile_select_screen.show_error(&format!("IMPORT FAILED: {}", e)),
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+
+                let previous_profile = profiles.active().id.clone();
+                let result = match action.clone() {
+                    ui::profile_select::ProfileAction::Select(id) => {
+                        save_manager.auto_save();
+                        profiles.select(&id)
+                    }
+                    ui::profile_select::ProfileAction::Create(name) => profiles.create(&name).map(|_| ()),
+                    ui::profile_select::ProfileAction::Rename(id, name) => profiles.rename(&id, &name),
+                    ui::profile_select::ProfileAction::Delete(id) => profiles.delete(&id),
+                    _ => Ok(()),
+                };
+
+                if matches!(
+                    action,
+                    ui::profile_select::ProfileAction::Select(_)
+                        | ui::profile_select::ProfileAction::Create(_)
+                        | ui::profile_select::ProfileAction::Rename(..)
+                        | ui::profile_select::ProfileAction::Delete(_)
+                ) {
+                    // Switch saves if a different profile is now active (picked, or the old one deleted)
+                    if profiles.active().id != previous_profile {
+                        (save_manager, suspend_store) = open_active_profile(&profiles);
+                        apply_settings_to_options(&mut options_menu, &save_manager.data().settings);
+                        level_select_synced = false;
+                    }
+                    save_manager.data_mut().player_name = Some(profiles.active().name.clone());
+                    main_menu.set_profile_name(&profiles.active().name);
+                    profile_select_screen.set_profiles(profiles.profiles(), &profiles.active().id);
+                }
+
+                match result {
+                    Err(e) => profile_select_screen.show_error(&e.to_string()),
+                    Ok(()) => {
+                        if matches!(action, ui::profile_select::ProfileAction::Select(_) | ui::profile_select::ProfileAction::Back) {
+                            scene_manager.request_transition(GameState::MainMenu);
+                        }
+                    }
                 }
             }
 
             GameState::MainMenu => {
+                // A ghost picked from a board is raced until the player leaves for the menu
+                chosen_ghost = None;
+                main_menu.set_resume_available(suspend_store.has_run());
                 let action = main_menu.update(dt);
                 main_menu.draw();
 
                 match action {
+                    ui::main_menu::MenuAction::Resume => {
+                        if let Some(suspended) = suspend_store.take() {
+                            current_continent = suspended.continent;
+                            current_mode = suspended.mode;
+                            save_manager.data_mut().resume_level(suspended.continent);
+                            let mut sim = Simulation::resume(suspended, save_manager.data().settings.adaptive_difficulty);
+                            sim.set_player(&profiles.active().name);
+                            sim.checkpoints.set_respawn_duration(save_manager.data().settings.respawn_countdown);
+
+                            // Back where it was left, waiting on the same screen if it was
+                            let state = match sim.phase() {
+                                Phase::BossFight => GameState::BossFight,
+                                Phase::Respawning => {
+                                    sim.checkpoints.start_respawn(); // The countdown, at this player's length
+                                    GameState::Checkpoint
+                                }
+                                Phase::GameOver => GameState::GameOver,
+                                Phase::Flying | Phase::Complete => GameState::InGame,
+                            };
+                            scene_manager.request_transition(state);
+                            run = Some(sim);
+                            ghost = None;
+                            tick_time = 0.0;
+                            clouds.clear();
+                            air_particles.clear();
+                            hud_effects.clear();
+                            events.clear();
+                        }
+                    }
                     ui::main_menu::MenuAction::Start => {
                         scene_manager.request_transition(GameState::LevelSelect);
                     }
+                    ui::main_menu::MenuAction::Achievements => {
+                        achievements_screen.set_progress(&save_manager.data().achievements);
+                        scene_manager.request_transition(GameState::Achievements);
+                    }
+                    ui::main_menu::MenuAction::Statistics => {
+                        statistics_screen.set_save(save_manager.data());
+                        scene_manager.request_transition(GameState::Statistics);
+                    }
+                    ui::main_menu::MenuAction::Profiles => {
+                        profile_select_screen.set_profiles(profiles.profiles(), &profiles.active().id);
+                        scene_manager.request_transition(GameState::ProfileSelect);
+                    }
                     ui::main_menu::MenuAction::Options => {
                         scene_manager.request_transition(GameState::Options);
                     }
@@ -124,6 +318,30 @@ async fn main() {
                 }
             }
 
+            GameState::Achievements => {
+                let action = achievements_screen.update(dt);
+                achievements_screen.draw();
+
+                match action {
+                    ui::achievements::AchievementsAction::Back => {
+                        scene_manager.request_transition(GameState::MainMenu);
+                    }
+                    ui::achievements::AchievementsAction::None => {}
+                }
+            }
+
+            GameState::Statistics => {
+                let action = statistics_screen.update(dt);
+                statistics_screen.draw();
+
+                match action {
+                    ui::statistics::StatisticsAction::Back => {
+                        scene_manager.request_transition(GameState::MainMenu);
+                    }
+                    ui::statistics::StatisticsAction::None => {}
+                }
+            }
+
             GameState::Options => {
                 let action = options_menu.update(dt);
                 options_menu.draw();
@@ -136,6 +354,9 @@ async fn main() {
                             options_menu.get_music_volume(),
                             options_menu.get_difficulty() as u8,
                         );
+                        save_manager.data_mut().settings.adaptive_difficulty = options_menu.get_adaptive_difficulty();
+                        save_manager.data_mut().settings.respawn_countdown = options_menu.get_respawn_countdown();
+                        save_manager.data_mut().settings.ghost = options_menu.get_ghost();
                         save_manager.auto_save();
 
                         scene_manager.request_transition(GameState::MainMenu);
@@ -148,6 +369,8 @@ async fn main() {
                 // Sync unlocked continents with save data on first entry
                 if !level_select_synced {
                     level_select_screen.sync_with_save(&save_manager.data().unlocked_continents);
+                    level_select_screen.set_time_attack_bests(save_manager.data());
+                    level_select_screen.set_boss_rush(save_manager.data());
                     level_select_synced = true;
                 }
 
@@ -155,9 +378,10 @@ async fn main() {
                 level_select_screen.draw();
 
                 match action {
-                    ui::level_select::LevelSelectAction::StartLevel(continent) => {
+                    ui::level_select::LevelSelectAction::StartLevel(continent, mode) => {
                         current_continent = continent;
-                        if continent == Continent::Tutorial {
+                        current_mode = mode;
+                        if continent == Continent::Tutorial && mode == GameMode::Standard {
                             // Show tutorial instructions first
                             tutorial_instructions.reset();
                             scene_manager.request_transition(GameState::TutorialInstructions);
@@ -165,6 +389,16 @@ async fn main() {
                             scene_manager.request_transition(GameState::InGame);
                         }
                     }
+                    ui::level_select::LevelSelectAction::ShowLeaderboard(continent, mode) => {
+                        let setting = DifficultySetting::from_index(save_manager.data().settings.difficulty);
+                        if mode == GameMode::Endless {
+                            leaderboard_screen.set_endless_boards(&leaderboards, setting);
+                        } else {
+                            leaderboard_screen.set_boards(&leaderboards, continent, setting);
+                            leaderboard_screen.set_online_available(online.is_some(), &profiles.active().name);
+                        }
+                        scene_manager.request_transition(GameState::Leaderboard);
+                    }
                     ui::level_select::LevelSelectAction::Back => {
                         scene_manager.request_transition(GameState::MainMenu);
                     }
@@ -172,6 +406,61 @@ async fn main() {
                 }
             }
 
+            GameState::Leaderboard => {
+                let action = leaderboard_screen.update(dt);
+                leaderboard_screen.draw();
+
+                match action {
+                    // Endless boards are local only
+                    ui::leaderboard::LeaderboardAction::Submit(entry) if leaderboard_screen.is_endless() => {
+                        let difficulty = leaderboard_screen.board().difficulty;
+                        match leaderboards.submit_endless(difficulty, entry, None) {
+                            Ok(rank) => {
+                                leaderboard_screen.set_endless_boards(&leaderboards, difficulty);
+                                if let Some(rank) = rank {
+                                    leaderboard_screen.highlight(rank);
+                                }
+                            }
+                            Err(e) => eprintln!("Failed to save leaderboard: {}", e),
+                        }
+                    }
+                    ui::leaderboard::LeaderboardAction::Submit(entry) => {
+                        let board = leaderboard_screen.board();
+                        let replay = pending_replay.take();
+                        if let Some(online) = &online {
+                            online.submit(online_leaderboard::submission(board, &entry, replay.clone()));
+                        }
+                        match leaderboards.submit(board.continent, board.difficulty, entry, replay.as_deref()) {
+                            Ok(rank) => {
+                                leaderboard_screen.set_boards(&leaderboards, board.continent, board.difficulty);
+                                if let Some(rank) = rank {
+                                    leaderboard_screen.highlight(rank);
+                                }
+                            }
+                            Err(e) => eprintln!("Failed to save leaderboard: {}", e),
+                        }
+                    }
+                    ui::leaderboard::LeaderboardAction::FetchOnline(board) => {
+                        if let Some(online) = &online {
+                            online.fetch(board, &profiles.active().name, LEADERBOARD_SIZE);
+                        }
+                    }
+                    ui::leaderboard::LeaderboardAction::RaceGhost(entry) => {
+                        match leaderboards.replay(&entry).map(|text| Replay::from_text(&text)) {
+                            Some(Ok(replay)) => {
+                                leaderboard_screen.show_message(&format!("NEXT {} RUN RACES {}'S GHOST", replay.continent.name().to_uppercase(), entry.name));
+                                chosen_ghost = Some((entry.name, replay));
+                            }
+                            _ => leaderboard_screen.show_error("COULDN'T LOAD THAT RUN'S REPLAY"),
+                        }
+                    }
+                    ui::leaderboard::LeaderboardAction::Back => {
+                        scene_manager.request_transition(GameState::LevelSelect);
+                    }
+                    ui::leaderboard::LeaderboardAction::None => {}
+                }
+            }
+
             GameState::TutorialInstructions => {
                 let action = tutorial_instructions.update(dt);
                 tutorial_instructions.draw();
@@ -187,311 +476,250 @@ async fn main() {
                 }
             }
 
-            GameState::Tutorial | GameState::InGame => {
-                // Initialize level manager if not present
-                if level_manager.is_none() {
-                    level_manager = Some(LevelManager::new(current_continent));
-                    checkpoint_manager.clear();
-                    rings.reset();
-                    drone.deactivate();
+            GameState::Tutorial | GameState::InGame | GameState::BossFight => {
+                // Start a fresh run if there isn't one to carry on
+                if run.is_none() {
+                    // Starting over abandons any suspended run
+                    suspend_store.clear();
+
+                    // Racing a ghost means flying its level: same seed, difficulty and director
+                    let settings = &save_manager.data().settings;
+                    ghost = ghost_for_run(chosen_ghost.as_ref(), &leaderboards, current_continent, current_mode, settings, &profiles.active().name);
+                    let mut sim = match &ghost {
+                        Some(race) => {
+                            let replay = race.replay();
+                            Simulation::new(current_continent, current_mode, replay.difficulty, replay.adaptive, replay.seed)
+                        }
+                        None => {
+                            let setting = DifficultySetting::from_index(settings.difficulty);
+                            Simulation::new(current_continent, current_mode, setting, settings.adaptive_difficulty, new_seed())
+                        }
+                    };
+                    sim.set_player(&profiles.active().name);
+                    sim.checkpoints.set_respawn_duration(settings.respawn_countdown);
+                    run = Some(sim);
+                    tick_time = 0.0;
+                    save_manager.data_mut().start_level(current_continent);
                     clouds.clear();
                     air_particles.clear();
-                    boss = None;
-                }
-
-                let level_mgr = level_manager.as_mut().unwrap();
-
-                // Update level
-                level_mgr.update(dt, player.position().z);
-
-                // Create checkpoints
-                if level_mgr.should_create_checkpoint() {
-                    checkpoint_manager.create_checkpoint(
-                        player.position().z,
-                        &player,
-                        scene_manager.scene_data().score,
-                        level_mgr.elapsed_time(),
-                    );
-                    level_mgr.mark_checkpoint_created();
+                    hud_effects.clear();
+                    events.clear();
                 }
-
-                // Spawn boss if time
-                if level_mgr.should_spawn_boss() && boss.is_none() {
-                    let boss_type = BossType::from_continent(current_continent);
-                    let spawn_pos = player.position() + vec3(0.0, 5.0, 30.0);
-                    boss = Some(Boss::new(boss_type, spawn_pos));
-                    #[cfg(debug_assertions)]
-                    println!("=== BOSS SPAWNED: {} at {:.1}s ===", boss_type.name(), level_mgr.elapsed_time());
-                    scene_manager.request_transition(GameState::BossFight);
-                }
-
-                // Check if level complete
-                if level_mgr.is_complete() && boss.as_ref().map_or(true, |b| b.is_defeated()) {
-                    scene_manager.request_transition(GameState::LevelComplete);
-                }
-
-                // Update game
-                player.update(dt);
-                terrain.update(dt, &player);
-                enemies.update(dt, &player);
-                rings.update(dt, &player);
-                drone.update(dt, &player);
-                clouds.update(dt, &player);
-
-                // Emit air particles when player moves vertically
-                air_particles.emit(player.position(), player.velocity().y);
-                air_particles.update(dt);
-
-                let score = scene_manager.scene_data().score;
-                let mut score_mut = score;
-                powerups.update(dt, &player, &mut score_mut);
-                scene_manager.scene_data_mut().score = score_mut;
-
-                // Check collisions
-                if terrain.check_collision(&player) || enemies.check_collision(&player) {
-                    player.take_damage(10.0);
-                    if player.is_dead() {
-                        save_manager.data_mut().record_death();
-                        checkpoint_manager.start_respawn();
-                        scene_manager.request_transition(GameState::Checkpoint);
+                let sim = run.as_mut().unwrap();
+
+                // Gameplay advances in fixed ticks; a long frame catches up (within reason)
+                tick_time = (tick_time + dt).min(MAX_CATCH_UP);
+                while tick_time >= TICK && sim.is_running() {
+                    sim.step(PlayerControls::from_keyboard(), &mut events);
+                    if let Some(race) = ghost.as_mut() {
+                        race.update(sim);
                     }
+                    tick_time -= TICK;
                 }
 
-                // Check powerup collection
-                if let Some(powerup_type) = powerups.check_collection(&mut player, &mut scene_manager.scene_data_mut().score) {
-                    if matches!(powerup_type, powerup::PowerupType::DroneCompanion) {
-                        drone.activate(player.position());
+                match sim.phase() {
+                    Phase::Flying if scene_manager.current_state() == GameState::BossFight => {
+                        scene_manager.request_transition(GameState::InGame);
+                    }
+                    Phase::Flying => {}
+                    Phase::BossFight if scene_manager.current_state() != GameState::BossFight => {
+                        scene_manager.request_transition(GameState::BossFight);
                     }
+                    Phase::BossFight => {}
+                    Phase::Respawning => scene_manager.request_transition(GameState::Checkpoint),
+                    Phase::GameOver => scene_manager.request_transition(GameState::GameOver),
+                    Phase::Complete => scene_manager.request_transition(GameState::LevelComplete),
                 }
 
-                // Check ring collection
-                rings.check_collection(&player, &mut scene_manager.scene_data_mut().score);
+                camera.update(&sim.player);
 
-                // Drone projectiles hit enemies
-                if drone.is_active() {
-                    let mut projectiles_to_remove = Vec::new();
-                    let drone_projectiles = drone.get_projectiles();
-                    for (idx, proj) in drone_projectiles.iter().enumerate() {
-                        if enemies.check_projectile_hit(proj.position) {
-                            projectiles_to_remove.push(idx);
-                            scene_manager.scene_data_mut().score += 25;
-                        }
-                    }
-                    // Remove collected projectiles (reverse order to preserve indices)
-                    for idx in projectiles_to_remove.iter().rev() {
-                        drone.clear_projectile(*idx);
+                // Endless runs take on the look of each continent they fly over
+                if sim.mode == GameMode::Endless {
+                    if let Some(assets) = asset_manager.get_continent_assets(&sim.level.continent()) {
+                        sim.terrain.set_theme(&assets.terrain);
                     }
                 }
 
-                // Update camera
-                camera.update(&player);
-
                 // Render 3D scene
                 set_camera(camera.get_camera());
 
-                clouds.draw(); // Background layer
-                terrain.draw();
-                enemies.draw();
-                powerups.draw();
-                rings.draw();
-                drone.draw();
-                air_particles.draw(); // Air trail effects
-                player.draw();
+                if let (GameState::BossFight, Some(boss)) = (scene_manager.current_state(), sim.boss.as_ref()) {
+                    sim.terrain.draw();
+                    sim.player.draw();
+                    boss.draw();
+                } else {
+                    clouds.update(dt, &sim.player);
+
+                    // Emit air particles when player moves vertically
+                    air_particles.emit(sim.player.position(), sim.player.velocity().y);
+                    air_particles.update(dt);
+
+                    clouds.draw(); // Background layer
+                    sim.terrain.draw();
+                    sim.enemies.draw();
+                    sim.powerups.draw();
+                    sim.rings.draw();
+                    sim.drone.draw();
+                    air_particles.draw(); // Air trail effects
+                    sim.player.draw();
+                }
+                if let Some(race) = &ghost {
+                    race.draw(sim);
+                }
 
                 // Render 2D UI
                 set_default_camera();
 
-                draw_hud_with_level(&player, scene_manager.scene_data().score, level_mgr, &checkpoint_manager, &asset_manager, current_continent, &save_manager, &drone);
+                draw_hud_with_level(&HudView::new(sim, &save_manager));
+                draw_lives(&sim.lives);
+                if let Some(race) = &ghost {
+                    race.draw_hud();
+                }
+                if let (GameState::BossFight, Some(boss)) = (scene_manager.current_state(), sim.boss.as_ref()) {
+                    draw_boss_health_bar(boss);
+                } else if sim.mode == GameMode::BossRush && sim.phase() == Phase::Flying {
+                    draw_boss_rush_interlude(&sim.level);
+                }
+                hud_effects.update(dt);
+                hud_effects.draw();
 
-                // Back to menu
+                // Back to menu, suspending the run so it can be resumed (the tutorial just ends)
                 if input.back {
+                    suspend_on_leaving(&mut suspend_store, scene_manager.current_state(), sim);
                     scene_manager.request_transition(GameState::MainMenu);
-                    level_manager = None;
+                    run = None;
                 }
             }
 
-            GameState::BossFight => {
-                if let Some(ref mut boss_instance) = boss {
-                    // Update level timer
-                    if let Some(ref mut level_mgr) = level_manager {
-                        level_mgr.update(dt, player.position().z);
-
-                        // Check if level time is up (even if boss not defeated)
-                        if level_mgr.is_complete() {
-                            scene_manager.scene_data_mut().score += 5000; // Bonus for completing level
-                            scene_manager.request_transition(GameState::LevelComplete);
-                        }
-                    }
-
-                    // Update boss
-                    boss_instance.update(dt, player.position(), player.velocity());
-
-                    // Boss collision with player
-                    if boss_instance.check_collision_with_player(player.position()) {
-                        player.take_damage(20.0 * dt); // Continuous damage
+            GameState::Checkpoint => {
+                if let Some(sim) = run.as_mut() {
+                    // Pick which of the recent checkpoints to respawn at
+                    if is_key_pressed(KeyCode::Left) || is_key_pressed(KeyCode::A) {
+                        sim.checkpoints.select_older();
                     }
-
-                    // Boss projectiles hit player
-                    if boss_instance.check_projectile_collision(player.position()) {
-                        player.take_damage(15.0);
+                    if is_key_pressed(KeyCode::Right) || is_key_pressed(KeyCode::D) {
+                        sim.checkpoints.select_newer();
                     }
 
-                    // Player projectiles hit boss
-                    let projectiles = player.get_projectiles();
-                    for (idx, proj) in projectiles.iter().enumerate() {
-                        if boss_instance.check_hit_by_player_projectile(proj.position) {
-                            // Add score for hitting boss
-                            scene_manager.scene_data_mut().score += 50;
-                        }
-                    }
-
-                    // Check if boss defeated
-                    if boss_instance.is_defeated() {
-                        scene_manager.scene_data_mut().score += 5000; // Big bonus for defeating boss
+                    // Respawn when the countdown runs out, or straight away on confirm
+                    if sim.checkpoints.update_respawn(dt) || input.confirm {
+                        sim.respawn();
+                        tick_time = 0.0;
+                        hud_effects.clear();
+                        air_particles.clear();
 
-                        // Check if level is complete
-                        if let Some(ref level_mgr) = level_manager {
-                            if level_mgr.is_complete() {
-                                // Level time is up, proceed to completion
-                                scene_manager.request_transition(GameState::LevelComplete);
-                            } else {
-                                // Boss defeated but level continues, go back to InGame
-                                #[cfg(debug_assertions)]
-                                println!("Boss defeated! Continuing level...");
-                                scene_manager.request_transition(GameState::InGame);
-                            }
+                        // Go back to appropriate state
+                        if sim.phase() == Phase::BossFight {
+                            scene_manager.request_transition(GameState::BossFight);
+                        } else {
+                            scene_manager.request_transition(GameState::InGame);
                         }
                     }
 
-                    // Check if player died
-                    if player.is_dead() {
-                        save_manager.data_mut().record_death();
-                        checkpoint_manager.start_respawn();
-                        scene_manager.request_transition(GameState::Checkpoint);
-                    }
-
-                    // Update player and camera
-                    player.update(dt);
-                    camera.update(&player);
-
-                    // Render 3D scene
-                    set_camera(camera.get_camera());
-
-                    terrain.draw();
-                    player.draw();
-                    boss_instance.draw();
-
-                    // Render 2D UI
-                    set_default_camera();
-
-                    if let Some(ref level_mgr) = level_manager {
-                        draw_hud_with_level(&player, scene_manager.scene_data().score, level_mgr, &checkpoint_manager, &asset_manager, current_continent, &save_manager, &drone);
-                        draw_boss_health_bar(boss_instance);
-                    }
+                    draw_checkpoint_screen(&sim.checkpoints);
+                }
 
-                    if input.back {
-                        scene_manager.request_transition(GameState::MainMenu);
-                        level_manager = None;
-                        boss = None;
+                if input.back || run.is_none() {
+                    if let Some(sim) = &run {
+                        suspend_on_leaving(&mut suspend_store, GameState::Checkpoint, sim);
                     }
-                } else {
-                    // No boss, transition back to game
-                    scene_manager.request_transition(GameState::InGame);
+                    scene_manager.request_transition(GameState::MainMenu);
+                    run = None;
                 }
             }
 
-            GameState::Checkpoint => {
-                // Update respawn timer
-                if checkpoint_manager.update_respawn(dt) {
-                    // Respawn ready
-                    checkpoint_manager.restore_player_state(&mut player, &mut scene_manager.scene_data_mut().score);
-
-                    // Deactivate drone companion on respawn
-                    drone.deactivate();
-
-                    // Reset terrain to checkpoint position and clear around player
-                    if let Some(checkpoint_pos) = checkpoint_manager.get_last_checkpoint_position() {
-                        terrain.reset_to_position(checkpoint_pos);
-                        // Clear obstacles and enemies in a LARGE safe radius around spawn point
-                        let clear_radius = 50.0; // Doubled safe zone radius for better recovery
-                        terrain.clear_around_position(player.position(), clear_radius);
-                        enemies.clear_around_position(player.position(), clear_radius);
+            // Time attack runs are judged on their time and medal; they don't go on
+            // the score leaderboards or unlock the next continent
+            GameState::LevelComplete if current_mode == GameMode::TimeAttack => {
+                if let Some(sim) = &run {
+                    let best = save_manager.data().get_best_time(&current_continent, GameMode::TimeAttack);
+                    draw_time_attack_complete(sim.run_time(), best, sim.level.time_attack());
+                }
 
-                        // Pause spawning for 3 seconds after respawn
-                        terrain.pause_spawning(3.0);
-                        enemies.pause_spawning(3.0);
+                if input.confirm {
+                    if let Some(sim) = run.take() {
+                        save_manager.data_mut().update_best_time(current_continent, GameMode::TimeAttack, sim.run_time());
+                        save_manager.auto_save();
                     }
+                    level_select_synced = false;
+                    scene_manager.request_transition(GameState::LevelSelect);
+                }
 
-                    // Go back to appropriate state
-                    if boss.is_some() && !boss.as_ref().unwrap().is_defeated() {
-                        scene_manager.request_transition(GameState::BossFight);
-                    } else {
-                        scene_manager.request_transition(GameState::InGame);
-                    }
+                if input.back {
+                    run = None;
+                    scene_manager.request_transition(GameState::MainMenu);
                 }
+            }
 
-                draw_checkpoint_screen(&checkpoint_manager);
+            // A cleared boss rush is recorded with its time and the damage it took
+            GameState::LevelComplete if current_mode == GameMode::BossRush => {
+                let cleared = run.as_ref().map(|sim| BossRushRecord { time: sim.run_time(), damage_taken: sim.damage_taken() });
+                if let Some(record) = &cleared {
+                    draw_boss_rush_complete(record, save_manager.data().boss_rush_best);
+                }
 
-                // Allow manual restart
                 if input.confirm {
-                    checkpoint_manager.cancel_respawn();
-                    checkpoint_manager.restore_player_state(&mut player, &mut scene_manager.scene_data_mut().score);
-
-                    // Deactivate drone companion on respawn
-                    drone.deactivate();
-
-                    // Reset terrain to checkpoint position and clear around player
-                    if let Some(checkpoint_pos) = checkpoint_manager.get_last_checkpoint_position() {
-                        terrain.reset_to_position(checkpoint_pos);
-                        // Clear obstacles and enemies in a LARGE safe radius around spawn point
-                        let clear_radius = 50.0; // Doubled safe zone radius for better recovery
-                        terrain.clear_around_position(player.position(), clear_radius);
-                        enemies.clear_around_position(player.position(), clear_radius);
-
-                        // Pause spawning for 3 seconds after respawn
-                        terrain.pause_spawning(3.0);
-                        enemies.pause_spawning(3.0);
-                    }
-
-                    if boss.is_some() && !boss.as_ref().unwrap().is_defeated() {
-                        scene_manager.request_transition(GameState::BossFight);
-                    } else {
-                        scene_manager.request_transition(GameState::InGame);
+                    if let Some(record) = cleared {
+                        save_manager.data_mut().update_boss_rush(record);
+                        save_manager.auto_save();
                     }
+                    run = None;
+                    level_select_synced = false;
+                    scene_manager.request_transition(GameState::LevelSelect);
                 }
 
                 if input.back {
+                    run = None;
                     scene_manager.request_transition(GameState::MainMenu);
-                    level_manager = None;
-                    checkpoint_manager.clear();
                 }
             }
 
             GameState::LevelComplete => {
-                draw_level_complete(scene_manager.scene_data().score);
+                // The run as it would go on its leaderboard, and where it would place
+                let finished_run = run.as_ref().map(|sim| {
+                    let entry = LeaderboardEntry {
+                        name: profiles.active().name.clone(),
+                        profile: profiles.active().name.clone(),
+                        score: sim.score.total(),
+                        time: sim.level.elapsed_time(),
+                        date: leaderboard::now(),
+                        seed: sim.seed,
+                        replay: None, // Stored with the run if it makes the board
+                        distance: 0.0,
+                    };
+                    let rank = leaderboards.rank(current_continent, sim.level.setting(), &entry);
+                    (sim.level.setting(), entry, rank)
+                });
+                if let Some(sim) = &run {
+                    draw_level_complete(&sim.score, finished_run.as_ref().and_then(|(_, _, rank)| *rank));
+                }
 
                 if input.confirm {
                     // Save progress
-                    let final_score = scene_manager.scene_data().score;
-                    if let Some(ref lvl_mgr) = level_manager {
-                        let completion_time = lvl_mgr.elapsed_time();
+                    if let Some(sim) = &run {
+                        let final_score = sim.score.total();
+                        let completion_time = sim.level.elapsed_time();
 
                         // Update high score
                         save_manager.data_mut().update_high_score(current_continent, final_score);
 
                         // Update best time
-                        save_manager.data_mut().update_best_time(current_continent, completion_time);
+                        save_manager.data_mut().update_best_time(current_continent, sim.mode, completion_time);
+
+                        #[cfg(debug_assertions)]
+                        if let Some(last) = sim.director.decisions().last() {
+                            println!(
+                                "Director: {} adjustments this level, ended at intensity {:+.2}",
+                                sim.director.decisions().len(),
+                                last.intensity_after
+                            );
+                        }
 
                         // Unlock next continent
                         let next_continent = get_next_continent(current_continent);
                         if let Some(next) = next_continent {
                             save_manager.data_mut().unlock_continent(next);
                         }
-
-                        // Record boss kill if defeated
-                        if boss.as_ref().map_or(false, |b| b.is_defeated()) {
-                            save_manager.data_mut().record_boss_kill();
-                        }
                     }
 
                     // Save to disk
@@ -500,63 +728,264 @@ async fn main() {
                     // Reset flag so level select resyncs
                     level_select_synced = false;
 
-                    // Reset for next level
-                    level_manager = None;
-                    checkpoint_manager.clear();
-                    boss = None;
-                    player = Player::new();
-                    terrain = TerrainManager::new();
-                    enemies = EnemyManager::new();
-                    powerups = PowerupManager::new();
+                    // The next level starts a new run
+                    pending_replay = run.take().and_then(|sim| sim.replay().map(Replay::to_text));
+
+                    // Show the continent's leaderboard, asking for a name if the run made it
+                    if let Some((setting, entry, rank)) = finished_run {
+                        leaderboard_screen.set_boards(&leaderboards, current_continent, setting);
+                        leaderboard_screen.set_online_available(online.is_some(), &profiles.active().name);
+                        match rank {
+                            Some(rank) => leaderboard_screen.enter_name(rank, entry),
+                            // Not a local best, but it still goes online under the profile name
+                            None => {
+                                if let Some(online) = &online {
+                                    online.submit(online_leaderboard::submission(leaderboard_screen.board(), &entry, pending_replay.take()));
+                                }
+                            }
+                        }
+                        scene_manager.request_transition(GameState::Leaderboard);
+                    } else {
+                        scene_manager.request_transition(GameState::LevelSelect);
+                    }
+                }
 
-                    scene_manager.request_transition(GameState::LevelSelect);
+                if input.back {
+                    run = None;
+                    scene_manager.request_transition(GameState::MainMenu);
+                }
+            }
+
+            // Endless runs end here, going on the endless board if they got far enough
+            GameState::GameOver if current_mode == GameMode::Endless => {
+                let ended_run = run.as_ref().map(|sim| {
+                    let entry = LeaderboardEntry {
+                        name: profiles.active().name.clone(),
+                        profile: profiles.active().name.clone(),
+                        score: sim.score.total(),
+                        time: sim.run_time(),
+                        date: leaderboard::now(),
+                        seed: sim.seed,
+                        replay: None,
+                        distance: sim.level.distance(),
+                    };
+                    let rank = leaderboards.endless_rank(sim.level.setting(), &entry);
+                    (sim.level.setting(), entry, rank)
+                });
+                if let Some((_, entry, rank)) = &ended_run {
+                    draw_endless_over(entry.distance, entry.score, *rank);
+                }
+
+                if input.confirm {
+                    run = None;
+                    if let Some((setting, entry, rank)) = ended_run {
+                        leaderboard_screen.set_endless_boards(&leaderboards, setting);
+                        if let Some(rank) = rank {
+                            leaderboard_screen.enter_name(rank, entry);
+                        }
+                        scene_manager.request_transition(GameState::Leaderboard);
+                    } else {
+                        scene_manager.request_transition(GameState::LevelSelect);
+                    }
+                } else if is_key_pressed(KeyCode::R) {
+                    // Set off again with a new run
+                    run = None;
+                    scene_manager.request_transition(GameState::InGame);
                 }
 
                 if input.back {
-                    level_manager = None;
-                    checkpoint_manager.clear();
-                    boss = None;
+                    run = None;
                     scene_manager.request_transition(GameState::MainMenu);
                 }
             }
 
             GameState::GameOver => {
-                draw_game_over(scene_manager.scene_data().score);
+                let can_continue = run.as_ref().is_some_and(Simulation::can_continue);
+                if let Some(sim) = &run {
+                    draw_game_over(sim.score.total(), &sim.lives, can_continue);
+                }
 
-                if input.confirm {
-                    // Reset game
-                    player = Player::new();
-                    terrain = TerrainManager::new();
-                    enemies = EnemyManager::new();
-                    powerups = PowerupManager::new();
-                    checkpoint_manager.clear();
-                    level_manager = Some(LevelManager::new(current_continent));
-                    boss = None;
-                    scene_manager.scene_data_mut().reset_score();
+                if input.confirm && can_continue {
+                    // Continue from the last checkpoint; the score penalty lands on respawn
+                    if let Some(sim) = run.as_mut() {
+                        sim.use_continue();
+                    }
+                    scene_manager.request_transition(GameState::Checkpoint);
+                } else if input.confirm || is_key_pressed(KeyCode::R) {
+                    // Restart the level with a new run
+                    run = None;
                     scene_manager.request_transition(GameState::InGame);
                 }
 
                 if input.back {
-                    level_manager = None;
-                    checkpoint_manager.clear();
-                    boss = None;
+                    if let Some(sim) = &run {
+                        suspend_on_leaving(&mut suspend_store, GameState::GameOver, sim);
+                    }
+                    run = None;
                     scene_manager.request_transition(GameState::MainMenu);
                 }
             }
         }
 
+        // Online leaderboard replies arrive whenever the server answers
+        if let Some(online) = &online {
+            while let Some(reply) = online.poll() {
+                match reply {
+                    OnlineReply::Submitted(Ok(response)) => {
+                        leaderboard_screen.show_message(&format!("ONLINE RANK #{}", response.rank));
+                    }
+                    OnlineReply::Submitted(Err(e)) => {
+                        leaderboard_screen.show_error(&format!("ONLINE SUBMIT FAILED: {}", e.to_uppercase()));
+                    }
+                    OnlineReply::Board { board, top, around } => leaderboard_screen.set_online_board(board, top, around),
+                }
+            }
+        }
+
+        // Deliver this frame's gameplay events to their subscribers
+        // (the run's own listeners heard them as they happened)
+        let mut listeners: [&mut dyn EventListener; 2] = [save_manager.data_mut(), &mut hud_effects];
+        events.dispatch(&mut listeners);
+
+        // Announce achievements unlocked by this frame's events, and save them right away
+        let unlocked = save_manager.data_mut().achievements.take_newly_unlocked();
+        if !unlocked.is_empty() {
+            for achievement in unlocked {
+                hud_effects.show_achievement(achievement);
+            }
+            save_manager.auto_save();
+        }
+        hud_effects.update_toasts(dt);
+        hud_effects.draw_toasts();
+
         // Draw mobile controls
         input_manager.draw();
 
         // Track play time during active gameplay
         if matches!(scene_manager.current_state(), GameState::InGame | GameState::Tutorial | GameState::BossFight) {
             save_manager.data_mut().add_play_time(dt);
+            if let Some(sim) = &run {
+                save_manager.data_mut().stats.add_distance(sim.player.velocity().length() * dt);
+            }
         }
 
         next_frame().await
     }
 }
 
+fn profile_name(profiles: &ProfileManager, id: &str) -> String {
+    profiles.profiles().iter().find(|p| p.id == id).map(|p| p.name.clone()).unwrap_or_default()
+}
+
+/// Copy an export to the clipboard as text and, on desktop, also write it to
+/// `~/.glidewars/exports`. Returns what to tell the player.
+fn export_profile(export: &ProfileExport) -> Result<String, String> {
+    macroquad::miniquad::window::clipboard_set(&export.to_text());
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let path = profile_export::files::write_export(export).map_err(|e| format!("EXPORT FAILED: {}", e))?;
+        let file = path.file_name().map(|f| f.to_string_lossy().into_owned()).unwrap_or_default();
+        Ok(format!("COPIED TO CLIPBOARD AND SAVED AS EXPORTS/{}", file.to_uppercase()))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        Ok("EXPORT COPIED TO CLIPBOARD".to_string())
+    }
+}
+
+/// An export pasted to the clipboard, or on desktop the newest file in
+/// `~/.glidewars/imports`
+fn find_import() -> Result<ProfileExport, String> {
+    let clipboard = macroquad::miniquad::window::clipboard_get()
+        .filter(|text| !text.trim().is_empty())
+        .map(|text| ProfileExport::from_text(&text));
+    if let Some(Ok(export)) = clipboard {
+        return Ok(export);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(file) = profile_export::files::read_newest_import() {
+        return file.map_err(|e| e.to_string());
+    }
+
+    match clipboard {
+        // Something that looked like an export but didn't load
+        Some(Err(e)) if !matches!(e, profile_export::ExportError::NotAnExport) => Err(e.to_string()),
+        _ => Err("NO EXPORT IN THE CLIPBOARD OR IMPORTS FOLDER".to_string()),
+    }
+}
+
+/// Open the active profile's save and suspended-run slot
+fn open_active_profile(profiles: &ProfileManager) -> (SaveManager, SuspendStore) {
+    let profile = profiles.active();
+    let mut save_manager = profiles.open_save(&profile.id);
+    save_manager.data_mut().player_name = Some(profile.name.clone());
+    (save_manager, SuspendStore::open(profiles.storage(), &profiles.suspend_key(&profile.id)))
+}
+
+fn apply_settings_to_options(options_menu: &mut OptionsMenu, settings: &Settings) {
+    options_menu.set_from_settings(
+        settings.sound_volume,
+        settings.music_volume,
+        settings.difficulty as usize,
+        settings.adaptive_difficulty,
+        settings.respawn_countdown,
+        settings.ghost,
+    );
+}
+
+/// The ghost to race on a new run: one picked from a board for this continent
+/// and mode, or else the profile's best run on the board it's about to play, if ghosts are on
+fn ghost_for_run(
+    chosen: Option<&(String, Replay)>,
+    leaderboards: &LeaderboardStore,
+    continent: Continent,
+    mode: GameMode,
+    settings: &Settings,
+    profile: &str,
+) -> Option<GhostRace> {
+    let (name, replay) = match chosen.filter(|(_, replay)| replay.continent == continent && replay.mode == mode) {
+        Some((name, replay)) => (name.clone(), replay.clone()),
+        // The boards only hold standard runs
+        None if settings.ghost && mode == GameMode::Standard => {
+            let board = leaderboards.top(continent, DifficultySetting::from_index(settings.difficulty));
+            let replay = board
+                .iter()
+                .filter(|entry| entry.profile == profile)
+                .find_map(|entry| leaderboards.replay(entry).and_then(|text| Replay::from_text(&text).ok()))?;
+            ("YOUR BEST".to_string(), replay)
+        }
+        None => return None,
+    };
+
+    match GhostTrack::record(&name, replay) {
+        Ok(track) => Some(GhostRace::new(track)),
+        Err(e) => {
+            eprintln!("Failed to replay ghost: {}", e);
+            None
+        }
+    }
+}
+
+/// Write the run to the suspend slot, for the main menu to offer to resume, when
+/// leaving it from `state` (quitting or going back to the menu). Runs that are
+/// over aren't kept, and the tutorial just ends.
+fn suspend_on_leaving(store: &mut SuspendStore, state: GameState, sim: &Simulation) {
+    let in_run = matches!(state, GameState::InGame | GameState::BossFight | GameState::Checkpoint | GameState::GameOver);
+    if in_run && sim.can_suspend() {
+        if let Err(e) = store.save(&sim.suspend()) {
+            eprintln!("Failed to suspend run: {}", e);
+        }
+    }
+}
+
+/// Pick a seed for a new run
+fn new_seed() -> u64 {
+    (miniquad::date::now() * 1000.0) as u64
+}
+
 fn get_next_continent(current: Continent) -> Option<Continent> {
     match current {
         Continent::Tutorial => Some(Continent::NorthAmerica),
@@ -589,16 +1018,35 @@ fn draw_hud(player: &Player, score: u32) {
     }
 }
 
-fn draw_hud_with_level(
-    player: &Player,
-    score: u32,
-    level_manager: &LevelManager,
-    checkpoint_manager: &CheckpointManager,
-    _asset_manager: &AssetManager,
-    continent: Continent,
-    save_manager: &SaveManager,
-    drone: &DroneCompanion,
-) {
+/// What the in-flight HUD shows, read off the running simulation and the save
+struct HudView<'a> {
+    player: &'a Player,
+    score: &'a ScoreKeeper,
+    level: &'a LevelManager,
+    checkpoints: &'a CheckpointManager,
+    drone: &'a DroneCompanion,
+    run_time: f32,
+    high_score: u32,
+    best_time: Option<f32>,
+}
+
+impl<'a> HudView<'a> {
+    fn new(sim: &'a Simulation, save_manager: &SaveManager) -> Self {
+        Self {
+            player: &sim.player,
+            score: &sim.score,
+            level: &sim.level,
+            checkpoints: &sim.checkpoints,
+            drone: &sim.drone,
+            run_time: sim.run_time(),
+            high_score: save_manager.data().get_high_score(&sim.continent),
+            best_time: save_manager.data().get_best_time(&sim.continent, sim.mode),
+        }
+    }
+}
+
+fn draw_hud_with_level(hud: &HudView) {
+    let HudView { player, score, level: level_manager, checkpoints: checkpoint_manager, drone, run_time, .. } = *hud;
     let hud_color = Color::from_rgba(0, 255, 255, 255);
     let screen_w = screen_width();
 
@@ -636,11 +1084,22 @@ fn draw_hud_with_level(
     draw_rectangle_lines(20.0, 73.0, 200.0, 12.0, 2.0, hud_color);
 
     // Score
-    draw_text(&format!("SCORE: {:08}", score), 20.0, 100.0, 18.0, hud_color);
+    draw_text(&format!("SCORE: {:08}", score.total()), 20.0, 100.0, 18.0, hud_color);
+    if score.combo() > 0 {
+        draw_text(
+            &format!("x{:.1} ({})", score.multiplier(), score.combo()),
+            160.0,
+            100.0,
+            18.0,
+            Color::from_rgba(255, 150, 0, 255),
+        );
+    }
 
-    // High score
-    let high_score = save_manager.data().get_high_score(&continent);
-    draw_text(&format!("HIGH:  {:08}", high_score), 20.0, 120.0, 16.0, Color::from_rgba(255, 215, 0, 255));
+    // High score (endless runs are ranked on their own board instead, and a
+    // boss rush on its time)
+    if matches!(level_manager.mode(), GameMode::Standard | GameMode::TimeAttack) {
+        draw_text(&format!("HIGH:  {:08}", hud.high_score), 20.0, 120.0, 16.0, Color::from_rgba(255, 215, 0, 255));
+    }
 
     // Weapon indicator
     if let Some(weapon) = player.current_weapon() {
@@ -663,7 +1122,8 @@ fn draw_hud_with_level(
     }
 
     // === TOP CENTER - CONTINENT PANEL ===
-    let continent_text = continent.name();
+    let flying_over = level_manager.continent(); // Changes leg by leg in endless
+    let continent_text = flying_over.name();
     let text_width = measure_text(continent_text, None, 32, 1.0).width;
     let panel_width = text_width + 40.0;
     let panel_x = screen_w / 2.0 - panel_width / 2.0;
@@ -685,28 +1145,52 @@ fn draw_hud_with_level(
     draw_rectangle(right_panel_x, 10.0, right_panel_width, 120.0, Color::from_rgba(0, 10, 20, 200));
     draw_rectangle_lines(right_panel_x, 10.0, right_panel_width, 120.0, 2.0, hud_color);
 
-    // Timer
-    let remaining = level_manager.remaining_time();
-    let minutes = (remaining / 60.0) as u32;
-    let seconds = (remaining % 60.0) as u32;
-    let timer_color = if remaining < 60.0 {
-        Color::from_rgba(255, 0, 0, 255) // Red warning
-    } else {
-        hud_color
+    // Timer: counting down the level, or up in time attack (in the colour of the
+    // best medal still in reach), endless and boss rush
+    let (timer_text, timer_color) = match level_manager.mode() {
+        GameMode::Standard => {
+            let remaining = level_manager.remaining_time();
+            let minutes = (remaining / 60.0) as u32;
+            let seconds = (remaining % 60.0) as u32;
+            let timer_color = if remaining < 60.0 {
+                Color::from_rgba(255, 0, 0, 255) // Red warning
+            } else {
+                hud_color
+            };
+            (format!("{:02}:{:02}", minutes, seconds), timer_color)
+        }
+        GameMode::TimeAttack => {
+            let medal = level_manager.time_attack().medal(run_time);
+            (level::format_race_time(run_time), medal_color(medal))
+        }
+        GameMode::Endless | GameMode::BossRush => (level::format_race_time(run_time), hud_color),
     };
     draw_text("TIME", right_panel_x + 10.0, 32.0, 18.0, hud_color);
     draw_text(
-        &format!("{:02}:{:02}", minutes, seconds),
+        &timer_text,
         right_panel_x + 10.0,
         55.0,
         28.0,
         timer_color,
     );
 
-    // Checkpoint
-    draw_text("CHECKPOINT", right_panel_x + 10.0, 80.0, 16.0, hud_color);
+    // Checkpoint, or the distance to the time attack finish, or flown in endless,
+    // or which boss rush fight it is
+    let (progress_label, progress_text) = match level_manager.mode() {
+        GameMode::Standard => (
+            "CHECKPOINT",
+            format!("{}/{}", checkpoint_manager.checkpoint_count(), level_manager.total_checkpoints()),
+        ),
+        GameMode::TimeAttack => ("TO GO", format!("{:.0}M", level_manager.remaining_distance())),
+        GameMode::Endless => ("DISTANCE", format!("{:.0}M", level_manager.distance())),
+        GameMode::BossRush => {
+            let fight = (level_manager.rush_fights_won() + 1).min(BOSS_RUSH_ROUTE.len());
+            ("BOSS", format!("{}/{}", fight, BOSS_RUSH_ROUTE.len()))
+        }
+    };
+    draw_text(progress_label, right_panel_x + 10.0, 80.0, 16.0, hud_color);
     draw_text(
-        &format!("{}/{}", checkpoint_manager.checkpoint_count(), level_manager.total_checkpoints()),
+        &progress_text,
         right_panel_x + 10.0,
         100.0,
         22.0,
@@ -714,11 +1198,13 @@ fn draw_hud_with_level(
     );
 
     // Best time (if exists)
-    if let Some(best_time) = save_manager.data().get_best_time(&continent) {
-        let best_mins = (best_time / 60.0) as u32;
-        let best_secs = (best_time % 60.0) as u32;
+    if let Some(best_time) = hud.best_time {
+        let best_text = match level_manager.mode() {
+            GameMode::Standard => format!("{:02}:{:02}", (best_time / 60.0) as u32, (best_time % 60.0) as u32),
+            GameMode::TimeAttack | GameMode::Endless | GameMode::BossRush => level::format_race_time(best_time),
+        };
         draw_text(
-            &format!("BEST: {:02}:{:02}", best_mins, best_secs),
+            &format!("BEST: {}", best_text),
             right_panel_x + 10.0,
             120.0,
             14.0,
@@ -821,21 +1307,34 @@ fn draw_checkpoint_screen(checkpoint_manager: &CheckpointManager) {
         );
     }
 
-    // Checkpoint info
-    if let Some(checkpoint_pos) = checkpoint_manager.get_last_checkpoint_position() {
-        let info_text = format!("Last checkpoint at Z={:.0}", checkpoint_pos);
-        let info_width = measure_text(&info_text, None, 20, 1.0).width;
+    // Recent checkpoints to choose from, selected one highlighted
+    let range = checkpoint_manager.selectable_range();
+    let choice_spacing = 110.0;
+    let first_x = screen_width / 2.0 - (range.len() as f32 - 1.0) * choice_spacing / 2.0;
+    for (slot, index) in range.enumerate() {
+        let Some(checkpoint) = checkpoint_manager.checkpoint(index) else {
+            continue;
+        };
+        let selected = index + 1 == checkpoint_manager.active_checkpoint_number();
+        let label = format!("#{} Z={:.0}", index + 1, checkpoint.position);
+        let size = if selected { 22.0 } else { 18.0 };
+        let label_width = measure_text(&label, None, size as u16, 1.0).width;
+        let color = if selected {
+            Color::from_rgba(255, 255, 0, 255)
+        } else {
+            Color::from_rgba(150, 150, 150, 255)
+        };
         draw_text(
-            &info_text,
-            screen_width / 2.0 - info_width / 2.0,
+            &label,
+            first_x + slot as f32 * choice_spacing - label_width / 2.0,
             screen_height / 2.0 + 40.0,
-            20.0,
-            Color::from_rgba(150, 150, 150, 255)
+            size,
+            color
         );
     }
 
     // Instructions
-    let restart_text = "PRESS SPACE TO RESPAWN NOW | ESC FOR MENU";
+    let restart_text = "←→ CHOOSE CHECKPOINT | SPACE TO RESPAWN NOW | ESC FOR MENU";
     let restart_width = measure_text(restart_text, None, 20, 1.0).width;
     draw_text(
         restart_text,
@@ -846,7 +1345,7 @@ fn draw_checkpoint_screen(checkpoint_manager: &CheckpointManager) {
     );
 }
 
-fn draw_level_complete(score: u32) {
+fn draw_level_complete(score: &ScoreKeeper, leaderboard_rank: Option<usize>) {
     let screen_width = screen_width();
     let screen_height = screen_height();
 
@@ -859,35 +1358,137 @@ fn draw_level_complete(score: u32) {
     draw_text(
         title_text,
         screen_width / 2.0 - text_width / 2.0,
-        screen_height / 2.0 - 60.0,
+        screen_height / 2.0 - 160.0,
         60.0,
         Color::from_rgba(0, 255, 0, 255)
     );
 
+    // Per-category breakdown
+    let column_x = screen_width / 2.0 - 160.0;
+    let mut y = screen_height / 2.0 - 100.0;
+    for category in ScoreCategory::all() {
+        let points = score.category_total(category);
+        let color = if points > 0 {
+            Color::from_rgba(255, 255, 255, 255)
+        } else {
+            Color::from_rgba(120, 120, 120, 255)
+        };
+        draw_text(category.name(), column_x, y, 24.0, color);
+        let points_text = format!("{:>8}", points);
+        let points_width = measure_text(&points_text, None, 24, 1.0).width;
+        draw_text(&points_text, column_x + 320.0 - points_width, y, 24.0, color);
+        y += 28.0;
+    }
+
+    if score.penalty() > 0 {
+        let color = Color::from_rgba(255, 80, 80, 255);
+        draw_text("Continue Penalty", column_x, y, 24.0, color);
+        let penalty_text = format!("{:>8}", format!("-{}", score.penalty()));
+        let penalty_width = measure_text(&penalty_text, None, 24, 1.0).width;
+        draw_text(&penalty_text, column_x + 320.0 - penalty_width, y, 24.0, color);
+        y += 28.0;
+    }
+
+    draw_text(
+        &format!("BEST COMBO: {}", score.best_combo()),
+        column_x,
+        y + 4.0,
+        20.0,
+        Color::from_rgba(255, 150, 0, 255)
+    );
+    draw_line(column_x, y + 16.0, column_x + 320.0, y + 16.0, 2.0, Color::from_rgba(0, 255, 255, 255));
+
     // Score
-    let score_text = format!("SCORE: {:08}", score);
+    let score_text = format!("SCORE: {:08}", score.total());
     let score_width = measure_text(&score_text, None, 40, 1.0).width;
     draw_text(
         &score_text,
         screen_width / 2.0 - score_width / 2.0,
-        screen_height / 2.0 + 10.0,
+        y + 56.0,
         40.0,
         Color::from_rgba(0, 255, 255, 255)
     );
 
+    if let Some(rank) = leaderboard_rank {
+        let rank_text = format!("NEW #{} ON THE LEADERBOARD!", rank + 1);
+        let rank_width = measure_text(&rank_text, None, 24, 1.0).width;
+        draw_text(
+            &rank_text,
+            screen_width / 2.0 - rank_width / 2.0,
+            y + 88.0,
+            24.0,
+            Color::from_rgba(255, 215, 0, 255)
+        );
+    }
+
     // Continue text
     let continue_text = "PRESS SPACE TO CONTINUE";
     let continue_width = measure_text(continue_text, None, 25, 1.0).width;
     draw_text(
         continue_text,
         screen_width / 2.0 - continue_width / 2.0,
-        screen_height / 2.0 + 80.0,
+        y + 126.0,
         25.0,
         Color::from_rgba(255, 255, 255, 255)
     );
 }
 
-fn draw_game_over(score: u32) {
+/// Gold, silver or bronze; red for a time outside every medal
+fn medal_color(medal: Option<Medal>) -> Color {
+    match medal {
+        Some(Medal::Gold) => Color::from_rgba(255, 215, 0, 255),
+        Some(Medal::Silver) => Color::from_rgba(200, 200, 210, 255),
+        Some(Medal::Bronze) => Color::from_rgba(205, 127, 50, 255),
+        None => Color::from_rgba(255, 80, 80, 255),
+    }
+}
+
+fn draw_time_attack_complete(time: f32, previous_best: Option<f32>, targets: &TimeAttackConfig) {
+    let screen_width = screen_width();
+    let screen_height = screen_height();
+
+    // Semi-transparent overlay
+    draw_rectangle(0.0, 0.0, screen_width, screen_height, Color::from_rgba(0, 0, 0, 180));
+
+    let centered = |text: &str, y: f32, size: f32, color: Color| {
+        let width = measure_text(text, None, size as u16, 1.0).width;
+        draw_text(text, screen_width / 2.0 - width / 2.0, y, size, color);
+    };
+
+    centered("FINISH!", screen_height / 2.0 - 160.0, 60.0, Color::from_rgba(0, 255, 0, 255));
+
+    // The run's time and the medal it earned
+    let medal = targets.medal(time);
+    centered(&level::format_race_time(time), screen_height / 2.0 - 90.0, 56.0, Color::from_rgba(0, 255, 255, 255));
+    let medal_text = match medal {
+        Some(medal) => format!("{} MEDAL", medal.name().to_uppercase()),
+        None => "NO MEDAL".to_string(),
+    };
+    centered(&medal_text, screen_height / 2.0 - 40.0, 32.0, medal_color(medal));
+
+    match previous_best {
+        Some(best) if best <= time => {
+            centered(&format!("BEST: {}", level::format_race_time(best)), screen_height / 2.0, 24.0, Color::from_rgba(200, 200, 200, 255));
+        }
+        _ => centered("NEW BEST TIME!", screen_height / 2.0, 24.0, Color::from_rgba(255, 215, 0, 255)),
+    }
+
+    // The targets, each lit once it's been beaten
+    let mut y = screen_height / 2.0 + 45.0;
+    for (target, target_time) in targets.targets() {
+        let color = if time <= target_time {
+            medal_color(Some(target))
+        } else {
+            Color::from_rgba(120, 120, 120, 255)
+        };
+        centered(&format!("{:<6} {}", target.name().to_uppercase(), level::format_race_time(target_time)), y, 22.0, color);
+        y += 26.0;
+    }
+
+    centered("PRESS SPACE TO CONTINUE", y + 30.0, 25.0, Color::from_rgba(255, 255, 255, 255));
+}
+
+fn draw_game_over(score: u32, lives: &LivesManager, can_continue: bool) {
     let screen_width = screen_width();
     let screen_height = screen_height();
 
@@ -915,14 +1516,108 @@ fn draw_game_over(score: u32) {
         Color::from_rgba(0, 255, 255, 255)
     );
 
-    let restart_text = "PRESS SPACE TO RESTART | ESC FOR MENU";
+    // Continue offer
+    if can_continue {
+        let continue_text = format!(
+            "SPACE: CONTINUE FROM CHECKPOINT (-{:.0}% SCORE, {} LEFT)",
+            lives.continue_penalty() * 100.0,
+            lives.continues_left()
+        );
+        let continue_width = measure_text(&continue_text, None, 22, 1.0).width;
+        draw_text(
+            &continue_text,
+            screen_width / 2.0 - continue_width / 2.0,
+            screen_height / 2.0 + 60.0,
+            22.0,
+            Color::from_rgba(255, 255, 0, 255)
+        );
+    }
+
+    let restart_text = if can_continue {
+        "R TO RESTART LEVEL | ESC FOR MENU"
+    } else {
+        "PRESS SPACE TO RESTART | ESC FOR MENU"
+    };
     let restart_width = measure_text(restart_text, None, 20, 1.0).width;
     draw_text(
         restart_text,
         screen_width / 2.0 - restart_width / 2.0,
-        screen_height / 2.0 + 60.0,
+        screen_height / 2.0 + 95.0,
         20.0,
         Color::from_rgba(255, 255, 255, 255)
     );
 }
 
+/// The boss rush's next boss, counting down to the fight
+fn draw_boss_rush_interlude(level_manager: &LevelManager) {
+    let boss_type = BossType::from_continent(level_manager.continent());
+    let text = format!("NEXT: {} IN {:.0}", boss_type.name().to_uppercase(), level_manager.interlude_remaining().ceil());
+    let width = measure_text(&text, None, 28, 1.0).width;
+    draw_text(&text, screen_width() / 2.0 - width / 2.0, 100.0, 28.0, Color::from_rgba(255, 200, 0, 255));
+}
+
+fn draw_boss_rush_complete(record: &BossRushRecord, previous_best: Option<BossRushRecord>) {
+    let screen_width = screen_width();
+    let screen_height = screen_height();
+
+    // Semi-transparent overlay
+    draw_rectangle(0.0, 0.0, screen_width, screen_height, Color::from_rgba(0, 0, 0, 180));
+
+    let centered = |text: &str, y: f32, size: f32, color: Color| {
+        let width = measure_text(text, None, size as u16, 1.0).width;
+        draw_text(text, screen_width / 2.0 - width / 2.0, y, size, color);
+    };
+
+    centered("BOSS RUSH CLEARED!", screen_height / 2.0 - 120.0, 56.0, Color::from_rgba(0, 255, 0, 255));
+    centered(&level::format_race_time(record.time), screen_height / 2.0 - 50.0, 56.0, Color::from_rgba(0, 255, 255, 255));
+    centered(&format!("DAMAGE TAKEN: {:.0}", record.damage_taken), screen_height / 2.0, 30.0, Color::from_rgba(255, 150, 0, 255));
+
+    match previous_best {
+        Some(best) if !record.beats(&best) => {
+            let best_text = format!("BEST: {} - {:.0} DAMAGE", level::format_race_time(best.time), best.damage_taken);
+            centered(&best_text, screen_height / 2.0 + 40.0, 24.0, Color::from_rgba(200, 200, 200, 255));
+        }
+        _ => centered("NEW BEST BOSS RUSH!", screen_height / 2.0 + 40.0, 24.0, Color::from_rgba(255, 215, 0, 255)),
+    }
+
+    centered("PRESS SPACE TO CONTINUE", screen_height / 2.0 + 100.0, 25.0, Color::from_rgba(255, 255, 255, 255));
+}
+
+fn draw_endless_over(distance: f32, score: u32, leaderboard_rank: Option<usize>) {
+    let screen_width = screen_width();
+    let screen_height = screen_height();
+
+    // Semi-transparent overlay
+    draw_rectangle(0.0, 0.0, screen_width, screen_height, Color::from_rgba(0, 0, 0, 180));
+
+    let centered = |text: &str, y: f32, size: f32, color: Color| {
+        let width = measure_text(text, None, size as u16, 1.0).width;
+        draw_text(text, screen_width / 2.0 - width / 2.0, y, size, color);
+    };
+
+    centered("GAME OVER", screen_height / 2.0 - 100.0, 60.0, Color::from_rgba(255, 0, 0, 255));
+    centered(&format!("DISTANCE: {:.0}M", distance), screen_height / 2.0 - 40.0, 36.0, Color::from_rgba(255, 255, 0, 255));
+    centered(&format!("FINAL SCORE: {:08}", score), screen_height / 2.0, 30.0, Color::from_rgba(0, 255, 255, 255));
+
+    if let Some(rank) = leaderboard_rank {
+        centered(&format!("NEW #{} ON THE ENDLESS LEADERBOARD!", rank + 1), screen_height / 2.0 + 40.0, 24.0, Color::from_rgba(255, 215, 0, 255));
+    }
+
+    centered("SPACE: LEADERBOARD | R TO FLY AGAIN | ESC FOR MENU", screen_height / 2.0 + 85.0, 20.0, Color::from_rgba(255, 255, 255, 255));
+}
+
+fn draw_lives(lives: &LivesManager) {
+    let panel_width = 200.0;
+    let x = screen_width() - panel_width - 10.0;
+    let y = 140.0;
+
+    draw_rectangle(x, y, panel_width, 30.0, Color::from_rgba(0, 10, 20, 200));
+    draw_rectangle_lines(x, y, panel_width, 30.0, 2.0, Color::from_rgba(0, 255, 255, 255));
+    draw_text("LIVES", x + 10.0, y + 21.0, 16.0, Color::from_rgba(0, 255, 255, 255));
+
+    // One pip per life
+    for i in 0..lives.lives() {
+        draw_rectangle(x + 70.0 + i as f32 * 14.0, y + 9.0, 10.0, 12.0, Color::from_rgba(255, 50, 80, 255));
+    }
+}
+
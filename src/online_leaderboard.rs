@@ -0,0 +1,275 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+use crate::assets::Continent;
+use crate::difficulty::DifficultySetting;
+use crate::leaderboard::LeaderboardEntry;
+use crate::leaderboard_api::{RankedEntry, ScoreSubmission, SubmitResponse};
+
+/// Names the online leaderboard server, e.g. `http://localhost:8081`. Online
+/// leaderboards are off when it isn't set.
+pub const SERVER_URL_VAR: &str = "GLIDEWARS_LEADERBOARD_URL";
+
+/// Runs around the player's best fetched along with the top of a board
+pub const AROUND_LIMIT: usize = 3;
+
+/// One continent and difficulty's board
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Board {
+    pub continent: Continent,
+    pub difficulty: DifficultySetting,
+}
+
+/// An online leaderboard. Calls block until the server answers, so the game
+/// reaches it through `OnlineLeaderboard` rather than directly.
+pub trait LeaderboardService: Send {
+    fn submit(&self, submission: &ScoreSubmission) -> Result<SubmitResponse, String>;
+    fn top(&self, board: Board, limit: usize) -> Result<Vec<RankedEntry>, String>;
+    fn around(&self, board: Board, name: &str, limit: usize) -> Result<Vec<RankedEntry>, String>;
+}
+
+/// The run as submitted to an online board, with its replay for the server to
+/// check. It goes up under the profile that flew it, as the replay records,
+/// whatever name it was given on the local board.
+pub fn submission(board: Board, entry: &LeaderboardEntry, replay: Option<String>) -> ScoreSubmission {
+    ScoreSubmission {
+        continent: board.continent.name().to_string(),
+        difficulty: board.difficulty.name().to_string(),
+        name: entry.profile.clone(),
+        score: entry.score,
+        time: entry.time,
+        seed: entry.seed,
+        replay,
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use http::HttpLeaderboard;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod http {
+    use std::time::Duration;
+    use super::{Board, LeaderboardService};
+    use crate::leaderboard_api::{ErrorResponse, RankedEntry, ScoreSubmission, SubmitResponse, MAX_LIMIT, SCORES_PATH};
+
+    /// A leaderboard server speaking the API in `leaderboard_api`
+    pub struct HttpLeaderboard {
+        scores_url: String,
+        agent: ureq::Agent,
+    }
+
+    impl HttpLeaderboard {
+        pub fn new(base_url: &str) -> Self {
+            Self {
+                scores_url: format!("{}{}", base_url.trim_end_matches('/'), SCORES_PATH),
+                agent: ureq::AgentBuilder::new().timeout(Duration::from_secs(5)).build(),
+            }
+        }
+
+        fn fetch(&self, board: Board, limit: usize, around: Option<&str>) -> Result<Vec<RankedEntry>, String> {
+            let mut request = self
+                .agent
+                .get(&self.scores_url)
+                .query("continent", board.continent.name())
+                .query("difficulty", board.difficulty.name())
+                .query("limit", &limit.min(MAX_LIMIT).to_string());
+            if let Some(name) = around {
+                request = request.query("around", name);
+            }
+            request.call().map_err(describe)?.into_json().map_err(|e| e.to_string())
+        }
+    }
+
+    impl LeaderboardService for HttpLeaderboard {
+        fn submit(&self, submission: &ScoreSubmission) -> Result<SubmitResponse, String> {
+            self.agent
+                .post(&self.scores_url)
+                .send_json(submission)
+                .map_err(describe)?
+                .into_json()
+                .map_err(|e| e.to_string())
+        }
+
+        fn top(&self, board: Board, limit: usize) -> Result<Vec<RankedEntry>, String> {
+            self.fetch(board, limit, None)
+        }
+
+        fn around(&self, board: Board, name: &str, limit: usize) -> Result<Vec<RankedEntry>, String> {
+            self.fetch(board, limit, Some(name))
+        }
+    }
+
+    /// The server's own message for a rejected request, or what went wrong getting there
+    fn describe(error: ureq::Error) -> String {
+        match error {
+            ureq::Error::Status(code, response) => match response.into_json::<ErrorResponse>() {
+                Ok(body) => body.error,
+                Err(_) => format!("server returned {}", code),
+            },
+            ureq::Error::Transport(e) => e.to_string(),
+        }
+    }
+}
+
+/// What came back from a request made through `OnlineLeaderboard`
+#[derive(Debug, Clone, PartialEq)]
+pub enum OnlineReply {
+    Submitted(Result<SubmitResponse, String>),
+    Board {
+        board: Board,
+        top: Result<Vec<RankedEntry>, String>,
+        around: Vec<RankedEntry>, // Empty if the player has no run there
+    },
+}
+
+enum Request {
+    Submit(ScoreSubmission),
+    Fetch { board: Board, name: String, limit: usize },
+}
+
+/// Makes leaderboard requests on a worker thread so the game never waits on the
+/// network. Replies are collected with `poll` each frame.
+pub struct OnlineLeaderboard {
+    requests: Sender<Request>,
+    replies: Receiver<OnlineReply>,
+}
+
+impl OnlineLeaderboard {
+    pub fn new(service: Box<dyn LeaderboardService>) -> Self {
+        let (requests, request_rx) = mpsc::channel();
+        let (reply_tx, replies) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            for request in request_rx {
+                let reply = match request {
+                    // Don't bother the server with a run it would turn away
+                    Request::Submit(submission) => {
+                        OnlineReply::Submitted(submission.validate().and_then(|()| service.submit(&submission)))
+                    }
+                    Request::Fetch { board, name, limit } => OnlineReply::Board {
+                        board,
+                        top: service.top(board, limit),
+                        around: service.around(board, &name, AROUND_LIMIT).unwrap_or_default(),
+                    },
+                };
+                if reply_tx.send(reply).is_err() {
+                    break; // The game has gone
+                }
+            }
+        });
+
+        Self { requests, replies }
+    }
+
+    /// Connect to the server named by `SERVER_URL_VAR`, if it's set. The web build
+    /// has no online leaderboards.
+    pub fn from_env() -> Option<Self> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let url = std::env::var(SERVER_URL_VAR).ok().filter(|url| !url.trim().is_empty())?;
+            println!("Online leaderboard: {}", url);
+            Some(Self::new(Box::new(HttpLeaderboard::new(&url))))
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            None
+        }
+    }
+
+    pub fn submit(&self, submission: ScoreSubmission) {
+        self.requests.send(Request::Submit(submission)).ok();
+    }
+
+    /// Fetch the top `limit` runs of a board, plus the runs around `name`'s best
+    pub fn fetch(&self, board: Board, name: &str, limit: usize) {
+        self.requests.send(Request::Fetch { board, name: name.to_string(), limit }).ok();
+    }
+
+    /// The next reply that's arrived, if any
+    pub fn poll(&self) -> Option<OnlineReply> {
+        self.replies.try_recv().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::leaderboard_api::{ScoreSubmission, CONTINENT_NAMES, DIFFICULTY_NAMES};
+    use std::time::{Duration, Instant};
+
+    /// Answers from a fixed board without any network
+    struct FakeService;
+
+    impl LeaderboardService for FakeService {
+        fn submit(&self, _submission: &ScoreSubmission) -> Result<SubmitResponse, String> {
+            Ok(SubmitResponse { rank: 4 })
+        }
+
+        fn top(&self, _board: Board, limit: usize) -> Result<Vec<RankedEntry>, String> {
+            Ok((1..=limit)
+                .map(|rank| RankedEntry { rank, name: format!("P{}", rank), score: 1000, time: 1.0, date: 0, seed: 0, has_replay: false })
+                .collect())
+        }
+
+        fn around(&self, _board: Board, _name: &str, _limit: usize) -> Result<Vec<RankedEntry>, String> {
+            Err("not on this board".to_string())
+        }
+    }
+
+    fn wait_for_reply(online: &OnlineLeaderboard) -> OnlineReply {
+        let start = Instant::now();
+        loop {
+            if let Some(reply) = online.poll() {
+                return reply;
+            }
+            assert!(start.elapsed() < Duration::from_secs(5), "no reply from the worker");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_requests_answered_from_worker() {
+        let online = OnlineLeaderboard::new(Box::new(FakeService));
+        let board = Board { continent: Continent::NorthAmerica, difficulty: DifficultySetting::Hard };
+
+        let entry = LeaderboardEntry {
+            name: "ACE".to_string(),
+            profile: "PLAYER".to_string(),
+            score: 4200,
+            time: 250.0,
+            date: 0,
+            seed: 9,
+            replay: None,
+            distance: 0.0,
+        };
+        online.submit(submission(board, &entry, Some("replay".to_string())));
+        assert_eq!(wait_for_reply(&online), OnlineReply::Submitted(Ok(SubmitResponse { rank: 4 })));
+
+        // Runs the server would reject never leave the game
+        let mut nameless = submission(board, &entry, Some("replay".to_string()));
+        nameless.name.clear();
+        online.submit(nameless);
+        assert!(matches!(wait_for_reply(&online), OnlineReply::Submitted(Err(_))));
+        online.submit(submission(board, &entry, None));
+        assert!(matches!(wait_for_reply(&online), OnlineReply::Submitted(Err(_))));
+
+        online.fetch(board, "ACE", 2);
+        match wait_for_reply(&online) {
+            OnlineReply::Board { board: replied, top, around } => {
+                assert_eq!(replied, board);
+                assert_eq!(top.unwrap().len(), 2);
+                assert!(around.is_empty());
+            }
+            reply => panic!("unexpected reply {:?}", reply),
+        }
+    }
+
+    #[test]
+    fn test_api_names_match_game() {
+        let all = Continent::all();
+        let continents: Vec<&str> = all.iter().map(|c| c.name()).collect();
+        assert_eq!(continents, CONTINENT_NAMES);
+
+        let difficulties: Vec<&str> = (0..3).map(|i| DifficultySetting::from_index(i).name()).collect();
+        assert_eq!(difficulties, DIFFICULTY_NAMES);
+    }
+}
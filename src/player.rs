@@ -1,6 +1,11 @@
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::events::{DamageSource, EventBus, EventListener, GameEvent};
 
-#[derive(Clone, Copy)]
+/// Radius of the glider's collision sphere
+pub const COLLISION_RADIUS: f32 = 0.7;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Weapon {
     None,
     Laser,
@@ -17,49 +22,107 @@ impl Weapon {
             Weapon::Spread => "SPREAD",
         }
     }
+
+    /// Ammo granted when the weapon is picked up
+    pub fn pickup_ammo(&self) -> u32 {
+        match self {
+            Weapon::None => 0,
+            Weapon::Laser => 50,
+            Weapon::Missile => 20,
+            Weapon::Spread => 30,
+        }
+    }
+}
+
+/// What the player is asking the glider to do for one update. Read from the
+/// keyboard during play, or from a replay when a run is simulated again.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlayerControls {
+    pub left: bool,
+    pub right: bool,
+    pub up: bool,
+    pub down: bool,
+    pub boost: bool,
+    pub shoot: bool,
+}
+
+impl PlayerControls {
+    pub fn from_keyboard() -> Self {
+        Self {
+            left: is_key_down(KeyCode::Left) || is_key_down(KeyCode::A),
+            right: is_key_down(KeyCode::Right) || is_key_down(KeyCode::D),
+            up: is_key_down(KeyCode::Up) || is_key_down(KeyCode::W),
+            down: is_key_down(KeyCode::Down) || is_key_down(KeyCode::S),
+            // Speed boost (Tab as well, since Shift has issues in macroquad)
+            boost: is_key_down(KeyCode::Tab) || is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift),
+            shoot: is_key_down(KeyCode::Space),
+        }
+    }
 }
 
 pub struct Projectile {
     pub position: Vec3,
     pub velocity: Vec3,
     pub lifetime: f32,
+    pub weapon: Weapon, // What fired it
+    pub landed: bool,   // Has hit something (it keeps flying either way)
+}
+
+impl Projectile {
+    /// Note that this projectile hit something, reporting it the first time for accuracy stats
+    pub fn land(&mut self, events: &mut EventBus) {
+        if !self.landed {
+            self.landed = true;
+            events.emit(GameEvent::ShotLanded { weapon: self.weapon });
+        }
+    }
 }
 
 pub struct Player {
     position: Vec3,
+    previous_position: Vec3, // Position at the start of the last update (for swept collision)
     velocity: Vec3,
     health: f32,
     max_health: f32,
     weapon: Weapon,
     ammo: u32,
-    projectiles: Vec<Projectile>,
+    pub(crate) projectiles: Vec<Projectile>,
+    unreported_shots: Vec<Weapon>, // Projectiles fired since the last `report_shots`
     shoot_cooldown: f32,
     boost_energy: f32,
     boost_max_energy: f32,
     boost_recharge_rate: f32,
+    damage_taken: f32, // Difficulty multiplier on incoming hits
+    invulnerable_timer: f32, // Hits are ignored while this counts down (after respawn)
 }
 
 impl Player {
     pub fn new() -> Self {
         Self {
             position: vec3(0.0, 0.0, 0.0),
+            previous_position: vec3(0.0, 0.0, 0.0),
             velocity: vec3(0.0, 0.0, 0.0),
             health: 100.0,
             max_health: 100.0,
             weapon: Weapon::None,
             ammo: 0,
             projectiles: Vec::new(),
+            unreported_shots: Vec::new(),
             shoot_cooldown: 0.0,
             boost_energy: 100.0,
             boost_max_energy: 100.0,
             boost_recharge_rate: 20.0, // Recharges 20 per second
+            damage_taken: 1.0,
+            invulnerable_timer: 0.0,
         }
     }
 
-    pub fn update(&mut self, dt: f32) {
-        // Speed boost system (using Tab key for now - Shift keys have issues in macroquad)
-        let is_boosting = is_key_down(KeyCode::Tab) || is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
-        let speed_multiplier = if is_boosting && self.boost_energy > 0.0 {
+    pub fn update(&mut self, dt: f32, controls: PlayerControls) {
+        self.previous_position = self.position;
+        self.invulnerable_timer = (self.invulnerable_timer - dt).max(0.0);
+
+        // Speed boost system
+        let speed_multiplier = if controls.boost && self.boost_energy > 0.0 {
             // Drain boost energy when boosting
             self.boost_energy -= 50.0 * dt; // Drains 50 per second
             self.boost_energy = self.boost_energy.max(0.0);
@@ -77,9 +140,9 @@ impl Player {
 
         // Horizontal controls (also affected by boost)
         let horizontal_speed = 5.0 * speed_multiplier;
-        if is_key_down(KeyCode::Left) || is_key_down(KeyCode::A) {
+        if controls.left {
             self.velocity.x = -horizontal_speed;
-        } else if is_key_down(KeyCode::Right) || is_key_down(KeyCode::D) {
+        } else if controls.right {
             self.velocity.x = horizontal_speed;
         } else {
             self.velocity.x *= 0.9; // Damping
@@ -90,16 +153,17 @@ impl Player {
         self.velocity.y += gravity * dt;
 
         // Vertical controls (work against gravity)
-        if is_key_down(KeyCode::Up) || is_key_down(KeyCode::W) {
+        if controls.up {
             // Lift force to counter gravity and climb
             self.velocity.y += 8.0 * dt;
-        } else if is_key_down(KeyCode::Down) || is_key_down(KeyCode::S) {
+        } else if controls.down {
             // Dive faster
             self.velocity.y -= 5.0 * dt;
         }
 
-        // Cap vertical velocity to prevent excessive speeds
+        // Cap velocity to prevent excessive speeds
         self.velocity.y = self.velocity.y.clamp(-8.0, 5.0);
+        self.velocity.x = self.velocity.x.clamp(-10.0, 10.0);
 
         // Update position
         self.position += self.velocity * dt;
@@ -131,7 +195,7 @@ impl Player {
             self.shoot_cooldown -= dt;
         }
 
-        if is_key_down(KeyCode::Space) && self.shoot_cooldown <= 0.0 {
+        if controls.shoot && self.shoot_cooldown <= 0.0 {
             self.shoot();
         }
 
@@ -143,7 +207,10 @@ impl Player {
         });
     }
 
-    fn shoot(&mut self) {
+    /// Fire the current weapon once if there's ammo; `update` calls this on the
+    /// trigger, minding the cooldown
+    pub fn shoot(&mut self) {
+        let before = self.projectiles.len();
         match self.weapon {
             Weapon::None => {}
             Weapon::Laser => {
@@ -152,6 +219,8 @@ impl Player {
                         position: self.position + vec3(0.0, 0.0, 1.0),
                         velocity: vec3(0.0, 0.0, 30.0),
                         lifetime: 2.0,
+                        weapon: Weapon::Laser,
+                        landed: false,
                     });
                     self.ammo -= 1;
                     self.shoot_cooldown = 0.15;
@@ -163,6 +232,8 @@ impl Player {
                         position: self.position + vec3(0.0, 0.0, 1.0),
                         velocity: vec3(0.0, 0.0, 20.0),
                         lifetime: 3.0,
+                        weapon: Weapon::Missile,
+                        landed: false,
                     });
                     self.ammo -= 1;
                     self.shoot_cooldown = 0.5;
@@ -175,6 +246,8 @@ impl Player {
                             position: self.position + vec3(0.0, 0.0, 1.0),
                             velocity: vec3(i as f32 * 5.0, 0.0, 25.0),
                             lifetime: 2.0,
+                            weapon: Weapon::Spread,
+                            landed: false,
                         });
                     }
                     self.ammo -= 1;
@@ -182,26 +255,41 @@ impl Player {
                 }
             }
         }
+
+        let fired = self.projectiles.len() - before;
+        self.unreported_shots.extend(std::iter::repeat_n(self.weapon, fired));
+    }
+
+    /// Report projectiles fired during `update` on the event bus
+    pub fn report_shots(&mut self, events: &mut EventBus) {
+        for weapon in self.unreported_shots.drain(..) {
+            events.emit(GameEvent::ShotFired { weapon });
+        }
     }
 
     pub fn draw(&self) {
-        // Draw glider (low-poly retro style)
-        // Body
-        draw_cube(self.position, vec3(0.5, 0.3, 1.0), None, Color::from_rgba(0, 200, 255, 255));
-
-        // Wings
-        draw_cube(
-            self.position + vec3(-1.0, 0.0, 0.0),
-            vec3(1.0, 0.1, 0.5),
-            None,
-            Color::from_rgba(0, 150, 200, 255)
-        );
-        draw_cube(
-            self.position + vec3(1.0, 0.0, 0.0),
-            vec3(1.0, 0.1, 0.5),
-            None,
-            Color::from_rgba(0, 150, 200, 255)
-        );
+        // Blink while invulnerable (glider hidden on alternate tenths of a second)
+        let blink_hidden = self.is_invulnerable() && ((self.invulnerable_timer * 10.0) as u32).is_multiple_of(2);
+
+        if !blink_hidden {
+            // Draw glider (low-poly retro style)
+            // Body
+            draw_cube(self.position, vec3(0.5, 0.3, 1.0), None, Color::from_rgba(0, 200, 255, 255));
+
+            // Wings
+            draw_cube(
+                self.position + vec3(-1.0, 0.0, 0.0),
+                vec3(1.0, 0.1, 0.5),
+                None,
+                Color::from_rgba(0, 150, 200, 255)
+            );
+            draw_cube(
+                self.position + vec3(1.0, 0.0, 0.0),
+                vec3(1.0, 0.1, 0.5),
+                None,
+                Color::from_rgba(0, 150, 200, 255)
+            );
+        }
 
         // Draw projectiles
         for proj in &self.projectiles {
@@ -219,6 +307,10 @@ impl Player {
         self.position
     }
 
+    pub fn previous_position(&self) -> Vec3 {
+        self.previous_position
+    }
+
     pub fn velocity(&self) -> Vec3 {
         self.velocity
     }
@@ -230,6 +322,35 @@ impl Player {
         }
     }
 
+    /// Take damage from a gameplay source (scaled by difficulty) and report it on the event bus
+    pub fn take_hit(&mut self, damage: f32, source: DamageSource, events: &mut EventBus) {
+        if self.is_invulnerable() {
+            return;
+        }
+
+        let was_alive = !self.is_dead();
+        let damage = damage * self.damage_taken;
+        self.take_damage(damage);
+
+        events.emit(GameEvent::PlayerDamaged { amount: damage, source });
+        if was_alive && self.is_dead() {
+            events.emit(GameEvent::PlayerDied { position: self.position, cause: source });
+        }
+    }
+
+    pub fn set_damage_taken(&mut self, multiplier: f32) {
+        self.damage_taken = multiplier;
+    }
+
+    /// Ignore gameplay hits for `duration` seconds (the glider blinks meanwhile)
+    pub fn start_invulnerability(&mut self, duration: f32) {
+        self.invulnerable_timer = duration;
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable_timer > 0.0
+    }
+
     pub fn heal(&mut self, amount: f32) {
         self.health += amount;
         if self.health > self.max_health {
@@ -241,6 +362,10 @@ impl Player {
         self.health <= 0.0
     }
 
+    pub fn pickup_weapon(&mut self, weapon: Weapon) {
+        self.set_weapon(weapon, weapon.pickup_ammo());
+    }
+
     pub fn set_weapon(&mut self, weapon: Weapon, ammo: u32) {
         self.weapon = weapon;
         self.ammo = ammo;
@@ -250,6 +375,10 @@ impl Player {
         self.ammo += amount;
     }
 
+    pub fn weapon(&self) -> Weapon {
+        self.weapon
+    }
+
     pub fn current_weapon(&self) -> Option<&str> {
         match self.weapon {
             Weapon::None => None,
@@ -273,8 +402,16 @@ impl Player {
         self.boost_max_energy
     }
 
-    pub fn get_projectiles(&self) -> &Vec<Projectile> {
-        &self.projectiles
+    pub fn refill_boost(&mut self, amount: f32) {
+        self.boost_energy = (self.boost_energy + amount).min(self.boost_max_energy);
+    }
+
+    pub fn set_boost_energy(&mut self, energy: f32) {
+        self.boost_energy = energy.clamp(0.0, self.boost_max_energy);
+    }
+
+    pub fn projectiles_mut(&mut self) -> &mut [Projectile] {
+        &mut self.projectiles
     }
 
     pub fn clear_projectile(&mut self, index: usize) {
@@ -285,6 +422,7 @@ impl Player {
 
     pub fn restore_from_checkpoint(&mut self, position: Vec3, health: f32, weapon: Weapon, ammo: u32) {
         self.position = position;
+        self.previous_position = position;
         self.health = health;
         self.weapon = weapon;
         self.ammo = ammo;
@@ -295,6 +433,20 @@ impl Player {
 
     pub fn set_position(&mut self, position: Vec3) {
         self.position = position;
+        self.previous_position = position;
+    }
+}
+
+/// Boost energy reward for each ring passed; a perfect course refills it completely
+const RING_BOOST_REFILL: f32 = 15.0;
+
+impl EventListener for Player {
+    fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::RingPassed { .. } => self.refill_boost(RING_BOOST_REFILL),
+            GameEvent::RingCourseCompleted { perfect: true, .. } => self.refill_boost(self.boost_max_energy),
+            _ => {}
+        }
     }
 }
 
@@ -319,6 +471,28 @@ mod tests {
         assert!(!player.is_dead());
     }
 
+    #[test]
+    fn test_take_hit_scales_with_difficulty() {
+        let mut player = Player::new();
+        player.set_damage_taken(0.5);
+
+        let mut events = EventBus::new();
+        player.take_hit(20.0, DamageSource::BossProjectile, &mut events);
+        assert_eq!(player.health(), 90.0);
+        assert_eq!(events.drain(), vec![GameEvent::PlayerDamaged { amount: 10.0, source: DamageSource::BossProjectile }]);
+    }
+
+    #[test]
+    fn test_invulnerable_player_ignores_hits() {
+        let mut player = Player::new();
+        player.start_invulnerability(2.0);
+
+        let mut events = EventBus::new();
+        player.take_hit(50.0, DamageSource::BossContact, &mut events);
+        assert_eq!(player.health(), 100.0);
+        assert!(events.drain().is_empty());
+    }
+
     #[test]
     fn test_player_death() {
         let mut player = Player::new();
@@ -362,13 +536,13 @@ mod tests {
 
         // Pickup laser
         player.pickup_weapon(Weapon::Laser);
-        assert_eq!(player.current_weapon(), Some("Laser"));
-        assert_eq!(player.ammo(), 100);
+        assert_eq!(player.current_weapon(), Some("LASER"));
+        assert_eq!(player.ammo(), 50);
 
         // Pickup missiles
         player.pickup_weapon(Weapon::Missile);
-        assert_eq!(player.current_weapon(), Some("Missile"));
-        assert_eq!(player.ammo(), 50);
+        assert_eq!(player.current_weapon(), Some("MISSILE"));
+        assert_eq!(player.ammo(), 20);
     }
 
     #[test]
@@ -388,7 +562,7 @@ mod tests {
 
         player.shoot();
         assert_eq!(player.projectiles.len(), 1);
-        assert_eq!(player.ammo(), 99);
+        assert_eq!(player.ammo(), 49);
     }
 
     #[test]
@@ -414,7 +588,7 @@ mod tests {
 
         assert_eq!(player.health(), 80.0);
         assert_eq!(player.position(), checkpoint_pos);
-        assert_eq!(player.current_weapon(), Some("Laser"));
+        assert_eq!(player.current_weapon(), Some("LASER"));
         assert_eq!(player.ammo(), 75);
         assert_eq!(player.velocity, vec3(0.0, 0.0, 0.0));
         assert_eq!(player.projectiles.len(), 0);
@@ -426,7 +600,7 @@ mod tests {
         let initial_y = player.position.y;
 
         // Update with no input (gravity should pull down)
-        player.update(0.1);
+        player.update(0.1, PlayerControls::default());
 
         // Y velocity should be negative (downward)
         assert!(player.velocity.y < 0.0);
@@ -440,7 +614,7 @@ mod tests {
         player.velocity.y = 100.0;
         player.velocity.x = 50.0;
 
-        player.update(0.1);
+        player.update(0.1, PlayerControls::default());
 
         // Velocity should be clamped
         assert!(player.velocity.y <= 5.0);
@@ -454,7 +628,7 @@ mod tests {
         let mut player = Player::new();
         player.position.y = 10.0; // Above ceiling
 
-        player.update(0.1);
+        player.update(0.1, PlayerControls::default());
 
         // Should be clamped to ceiling
         assert!(player.position.y <= 6.0);
@@ -466,7 +640,7 @@ mod tests {
         player.position.y = -2.0; // Below ground
         player.velocity.y = -5.0; // Moving down
 
-        player.update(0.1);
+        player.update(0.1, PlayerControls::default());
 
         // Should be at ground level
         assert!(player.position.y >= -1.0);
@@ -481,9 +655,25 @@ mod tests {
         // Move projectile far away
         player.projectiles[0].position.z = player.position.z + 100.0;
 
-        player.update(0.1);
+        player.update(0.1, PlayerControls::default());
 
         // Projectile should be removed (beyond 80 unit range)
         assert_eq!(player.projectiles.len(), 0);
     }
+
+    #[test]
+    fn test_shots_reported_per_projectile() {
+        let mut player = Player::new();
+        player.set_weapon(Weapon::Spread, 10);
+        player.shoot();
+
+        let mut events = EventBus::new();
+        player.report_shots(&mut events);
+        assert_eq!(events.drain(), vec![GameEvent::ShotFired { weapon: Weapon::Spread }; 3]);
+
+        // A projectile's first hit is reported, later ones aren't
+        player.projectiles[0].land(&mut events);
+        player.projectiles[0].land(&mut events);
+        assert_eq!(events.drain(), vec![GameEvent::ShotLanded { weapon: Weapon::Spread }]);
+    }
 }
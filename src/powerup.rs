@@ -1,8 +1,12 @@
 use macroquad::prelude::*;
-use macroquad::rand::gen_range;
+use crate::rng::gen_range;
+use serde::{Deserialize, Serialize};
+use crate::events::{EventBus, GameEvent};
+use crate::fmath;
 use crate::player::{Player, Weapon};
+use crate::spatial_grid::SpatialGrid;
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum PowerupType {
     HealthSmall,
     HealthLarge,
@@ -13,11 +17,40 @@ pub enum PowerupType {
     DroneCompanion,
 }
 
+impl PowerupType {
+    pub fn name(&self) -> &str {
+        match self {
+            PowerupType::HealthSmall => "Small Health",
+            PowerupType::HealthLarge => "Large Health",
+            PowerupType::WeaponLaser => "Laser",
+            PowerupType::WeaponMissile => "Missile",
+            PowerupType::WeaponSpread => "Spread",
+            PowerupType::AmmoRefill => "Ammo",
+            PowerupType::DroneCompanion => "Drone",
+        }
+    }
+
+    /// Points awarded for picking this powerup up
+    pub fn score_value(&self) -> u32 {
+        match self {
+            PowerupType::HealthSmall => 50,
+            PowerupType::HealthLarge => 100,
+            PowerupType::WeaponLaser | PowerupType::WeaponSpread => 200,
+            PowerupType::WeaponMissile => 250,
+            PowerupType::AmmoRefill => 75,
+            PowerupType::DroneCompanion => 300,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Powerup {
+    #[serde(with = "crate::snapshot::vec3")]
     position: Vec3,
     powerup_type: PowerupType,
     rotation: f32,
     time_alive: f32,
+    #[serde(with = "crate::snapshot::vec3")]
     velocity: Vec3,
 }
 
@@ -37,7 +70,7 @@ impl Powerup {
         self.time_alive += dt;
 
         // Floating animation
-        self.position.y += (self.time_alive * 3.0).sin() * 0.01;
+        self.position.y += fmath::sin(self.time_alive * 3.0) * 0.01;
 
         // Magnetic pull towards player
         let distance = (self.position - player_pos).length();
@@ -45,7 +78,7 @@ impl Powerup {
 
         if distance < magnetic_range && distance > 0.1 {
             // Calculate direction to player
-            let direction = (player_pos - self.position).normalize();
+            let direction = fmath::normalize(player_pos - self.position);
 
             // Stronger pull the closer we are (inverse square-ish)
             let pull_strength = (1.0 - (distance / magnetic_range)).powi(2);
@@ -105,44 +138,33 @@ impl Powerup {
         }
     }
 
-    fn collect(&self, player: &mut Player, score: &mut u32) {
+    fn collect(&self, player: &mut Player) {
         match self.powerup_type {
-            PowerupType::HealthSmall => {
-                player.heal(25.0);
-                *score += 50;
-            }
-            PowerupType::HealthLarge => {
-                player.heal(50.0);
-                *score += 100;
-            }
-            PowerupType::WeaponLaser => {
-                player.set_weapon(Weapon::Laser, 50);
-                *score += 200;
-            }
-            PowerupType::WeaponMissile => {
-                player.set_weapon(Weapon::Missile, 20);
-                *score += 250;
-            }
-            PowerupType::WeaponSpread => {
-                player.set_weapon(Weapon::Spread, 30);
-                *score += 200;
-            }
-            PowerupType::AmmoRefill => {
-                player.add_ammo(25);
-                *score += 75;
-            }
+            PowerupType::HealthSmall => player.heal(25.0),
+            PowerupType::HealthLarge => player.heal(50.0),
+            PowerupType::WeaponLaser => player.pickup_weapon(Weapon::Laser),
+            PowerupType::WeaponMissile => player.pickup_weapon(Weapon::Missile),
+            PowerupType::WeaponSpread => player.pickup_weapon(Weapon::Spread),
+            PowerupType::AmmoRefill => player.add_ammo(25),
             PowerupType::DroneCompanion => {
-                // Handled externally in main.rs
-                *score += 300;
+                // Drone activation is handled by the drone's event listener
             }
         }
     }
 }
 
+/// Uncollected powerups and the spawn timer at a checkpoint
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PowerupSnapshot {
+    powerups: Vec<Powerup>,
+    spawn_timer: f32,
+}
+
 pub struct PowerupManager {
     powerups: Vec<Powerup>,
     spawn_timer: f32,
     spawn_interval: f32,
+    grid: SpatialGrid,
 }
 
 impl PowerupManager {
@@ -151,10 +173,11 @@ impl PowerupManager {
             powerups: Vec::new(),
             spawn_timer: 0.0,
             spawn_interval: 5.0,
+            grid: SpatialGrid::new(5.0),
         }
     }
 
-    pub fn update(&mut self, dt: f32, player: &Player, _score: &mut u32) {
+    pub fn update(&mut self, dt: f32, player: &Player) {
         let player_pos = player.position();
 
         // Update spawn timer
@@ -171,6 +194,37 @@ impl PowerupManager {
 
         // Remove powerups that are behind the player
         self.powerups.retain(|powerup| powerup.position.z > player_pos.z - 20.0);
+        self.rebuild_grid();
+    }
+
+    /// Scale how often powerups appear (1.0 = every 5 seconds)
+    pub fn set_generosity(&mut self, generosity: f32) {
+        self.spawn_interval = 5.0 / generosity;
+    }
+
+    /// The broad-phase index, rebuilt on every update
+    pub fn grid(&self) -> &SpatialGrid {
+        &self.grid
+    }
+
+    pub fn snapshot(&self) -> PowerupSnapshot {
+        PowerupSnapshot {
+            powerups: self.powerups.clone(),
+            spawn_timer: self.spawn_timer,
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &PowerupSnapshot) {
+        self.powerups = snapshot.powerups.clone();
+        self.spawn_timer = snapshot.spawn_timer;
+        self.rebuild_grid();
+    }
+
+    fn rebuild_grid(&mut self) {
+        self.grid.clear();
+        for (index, powerup) in self.powerups.iter().enumerate() {
+            self.grid.insert(index, powerup.position.z, powerup.position.z);
+        }
     }
 
     fn spawn_powerup(&mut self, player_pos: Vec3) {
@@ -202,31 +256,37 @@ impl PowerupManager {
         }
     }
 
-    pub fn check_collection(&mut self, player: &mut Player, score: &mut u32) -> Option<PowerupType> {
+    pub fn check_collection(&mut self, player: &mut Player, events: &mut EventBus) -> Option<PowerupType> {
         let player_pos = player.position();
         let collection_distance = 2.0; // Increased from 1.5 to work with magnetic pull
 
         let mut collected_type = None;
 
-        self.powerups.retain(|powerup| {
-            let distance = (powerup.position - player_pos).length();
-            if distance < collection_distance {
-                powerup.collect(player, score);
-                collected_type = Some(powerup.powerup_type);
-                #[cfg(debug_assertions)]
-                println!("Collected powerup! +{} points", match powerup.powerup_type {
-                    PowerupType::HealthSmall => 50,
-                    PowerupType::HealthLarge => 100,
-                    PowerupType::WeaponLaser | PowerupType::WeaponSpread => 200,
-                    PowerupType::WeaponMissile => 250,
-                    PowerupType::AmmoRefill => 75,
-                    PowerupType::DroneCompanion => 300,
-                });
-                false // Remove collected powerup
-            } else {
-                true
+        let collected: Vec<usize> = self
+            .grid
+            .candidates(player_pos.z - collection_distance, player_pos.z + collection_distance)
+            .into_iter()
+            .filter(|&index| (self.powerups[index].position - player_pos).length() < collection_distance)
+            .collect();
+
+        for &index in &collected {
+            let powerup = &self.powerups[index];
+            powerup.collect(player);
+            collected_type = Some(powerup.powerup_type);
+            events.emit(GameEvent::PowerupCollected {
+                powerup_type: powerup.powerup_type,
+                position: powerup.position,
+            });
+            log::debug!("Collected powerup! +{} points", powerup.powerup_type.score_value());
+        }
+
+        // Remove collected powerups (highest index first so the rest stay valid)
+        if !collected.is_empty() {
+            for &index in collected.iter().rev() {
+                self.powerups.remove(index);
             }
-        });
+            self.rebuild_grid();
+        }
 
         collected_type
     }
@@ -0,0 +1,231 @@
+//! Moving a profile between installs, such as from the desktop build to the browser.
+//!
+//! An export is `GWPROF`, a format version byte, the CRC32 of the payload and the
+//! gzipped JSON payload (profile name and save). It's written as a `.gwprofile` file
+//! on desktop and copied to the clipboard as base64 text, which is what the browser
+//! build can import.
+
+use base64::Engine;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{Read, Write};
+use crate::save_system::SaveData;
+
+const MAGIC: &[u8; 6] = b"GWPROF";
+
+/// Bump when the payload layout changes; the save inside has its own version
+pub const EXPORT_FORMAT_VERSION: u8 = 1;
+
+pub const EXPORT_EXTENSION: &str = "gwprofile";
+
+/// Bytes before the compressed payload: magic, version, checksum
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4;
+
+#[derive(Serialize, Deserialize)]
+struct ExportPayload {
+    profile_name: String,
+    save: serde_json::Value, // Migrated like a save file on import
+}
+
+/// How an import combines with the profile it's imported into
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportMode {
+    Merge,     // Keep the best of both (see `SaveData::merge`)
+    Overwrite, // Replace the profile's progress entirely
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    NotAnExport,
+    UnsupportedVersion(u8),
+    ChecksumMismatch,
+    Corrupt(String),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExportError::NotAnExport => write!(f, "NOT A GLIDE WARS PROFILE EXPORT"),
+            ExportError::UnsupportedVersion(v) => write!(f, "EXPORT FORMAT {} NEEDS A NEWER VERSION OF THE GAME", v),
+            ExportError::ChecksumMismatch => write!(f, "EXPORT IS DAMAGED (CHECKSUM MISMATCH)"),
+            ExportError::Corrupt(e) => write!(f, "EXPORT IS DAMAGED: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+/// A profile's name and full save (progress, stats and settings)
+#[derive(Debug, Clone)]
+pub struct ProfileExport {
+    pub profile_name: String,
+    pub save: SaveData,
+}
+
+impl ProfileExport {
+    pub fn new(profile_name: &str, save: &SaveData) -> Self {
+        Self {
+            profile_name: profile_name.to_string(),
+            save: save.clone(),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let payload = ExportPayload {
+            profile_name: self.profile_name.clone(),
+            save: serde_json::to_value(&self.save).expect("save data always serializes"),
+        };
+        let json = serde_json::to_vec(&payload).expect("save data always serializes");
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&json).expect("writing to a Vec can't fail");
+        let compressed = encoder.finish().expect("writing to a Vec can't fail");
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + compressed.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(EXPORT_FORMAT_VERSION);
+        bytes.extend_from_slice(&crc32fast::hash(&json).to_le_bytes());
+        bytes.extend_from_slice(&compressed);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ExportError> {
+        if bytes.len() < HEADER_LEN || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(ExportError::NotAnExport);
+        }
+        let version = bytes[MAGIC.len()];
+        if version != EXPORT_FORMAT_VERSION {
+            return Err(ExportError::UnsupportedVersion(version));
+        }
+        let checksum = u32::from_le_bytes(bytes[MAGIC.len() + 1..HEADER_LEN].try_into().unwrap());
+
+        let mut json = Vec::new();
+        GzDecoder::new(&bytes[HEADER_LEN..])
+            .read_to_end(&mut json)
+            .map_err(|e| ExportError::Corrupt(e.to_string()))?;
+        if crc32fast::hash(&json) != checksum {
+            return Err(ExportError::ChecksumMismatch);
+        }
+
+        let payload: ExportPayload = serde_json::from_slice(&json).map_err(|e| ExportError::Corrupt(e.to_string()))?;
+        let save = SaveData::from_value(payload.save).map_err(|e| ExportError::Corrupt(e.to_string()))?;
+        Ok(Self {
+            profile_name: payload.profile_name,
+            save,
+        })
+    }
+
+    /// The export as base64 text, for the clipboard
+    pub fn to_text(&self) -> String {
+        base64::engine::general_purpose::STANDARD.encode(self.to_bytes())
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, ExportError> {
+        let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(compact)
+            .map_err(|_| ExportError::NotAnExport)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// One-line preview shown before importing
+    pub fn summary(&self) -> String {
+        let total_score: u32 = self.save.high_scores.values().sum();
+        let minutes = (self.save.total_play_time / 60.0) as u32;
+        format!(
+            "{} CONTINENTS | {} PTS | {}H {:02}M PLAYED",
+            self.save.unlocked_continents.len(),
+            total_score,
+            minutes / 60,
+            minutes % 60
+        )
+    }
+}
+
+/// Export files live in `~/.glidewars/exports`, and imports are picked up from
+/// `~/.glidewars/imports`
+#[cfg(not(target_arch = "wasm32"))]
+pub mod files {
+    use super::{ExportError, ProfileExport, EXPORT_EXTENSION};
+    use std::fs;
+    use std::path::PathBuf;
+
+    pub fn write_export(export: &ProfileExport) -> std::io::Result<PathBuf> {
+        let dir = crate::storage::data_dir().join("exports");
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.{}", export.profile_name.replace(' ', "_"), EXPORT_EXTENSION));
+        fs::write(&path, export.to_bytes())?;
+        Ok(path)
+    }
+
+    /// The most recently modified export in the imports folder, if there is one
+    pub fn read_newest_import() -> Option<Result<ProfileExport, ExportError>> {
+        let dir = crate::storage::data_dir().join("imports");
+        let newest = fs::read_dir(dir)
+            .ok()?
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == EXPORT_EXTENSION))
+            .max_by_key(|entry| entry.metadata().and_then(|m| m.modified()).ok())?;
+        let bytes = fs::read(newest.path()).ok()?;
+        Some(ProfileExport::from_bytes(&bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::Continent;
+
+    fn sample() -> ProfileExport {
+        let mut save = SaveData::default();
+        save.unlock_continent(Continent::NorthAmerica);
+        save.update_high_score(Continent::Tutorial, 5000);
+        save.total_play_time = 3720.0;
+        ProfileExport::new("ANA", &save)
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let export = ProfileExport::from_bytes(&sample().to_bytes()).unwrap();
+        assert_eq!(export.profile_name, "ANA");
+        assert!(export.save.is_continent_unlocked(&Continent::NorthAmerica));
+        assert_eq!(export.save.get_high_score(&Continent::Tutorial), 5000);
+    }
+
+    #[test]
+    fn test_text_round_trip_ignores_whitespace() {
+        let text = sample().to_text();
+        let wrapped: String = text
+            .as_bytes()
+            .chunks(40)
+            .map(|chunk| format!("{}\n", std::str::from_utf8(chunk).unwrap()))
+            .collect();
+        assert_eq!(ProfileExport::from_text(&wrapped).unwrap().profile_name, "ANA");
+    }
+
+    #[test]
+    fn test_damage_is_detected() {
+        let bytes = sample().to_bytes();
+
+        let mut wrong_checksum = bytes.clone();
+        wrong_checksum[MAGIC.len() + 1] ^= 0xFF;
+        assert!(matches!(ProfileExport::from_bytes(&wrong_checksum), Err(ExportError::ChecksumMismatch)));
+
+        let truncated = &bytes[..bytes.len() - 10];
+        assert!(matches!(ProfileExport::from_bytes(truncated), Err(ExportError::Corrupt(_))));
+
+        assert!(matches!(ProfileExport::from_text("hello there"), Err(ExportError::NotAnExport)));
+
+        let mut newer = bytes;
+        newer[MAGIC.len()] = EXPORT_FORMAT_VERSION + 1;
+        assert!(matches!(ProfileExport::from_bytes(&newer), Err(ExportError::UnsupportedVersion(_))));
+    }
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(sample().summary(), "2 CONTINENTS | 5000 PTS | 1H 02M PLAYED");
+    }
+}
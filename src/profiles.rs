@@ -0,0 +1,316 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::save_system::{SaveManager, SAVE_BACKUPS};
+use crate::storage::{self, SharedStorage};
+
+/// Longest profile name the picker accepts
+pub const MAX_PROFILE_NAME: usize = 12;
+
+/// Name given to the first profile, and to the one made from a pre-profiles save
+const DEFAULT_PROFILE_NAME: &str = "PLAYER";
+
+/// One player's slot. Each profile keeps its own save (progress, settings, stats)
+/// and suspended run under `profiles/<id>/`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+}
+
+/// `profiles.json`: every profile plus the one last played
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ProfileIndex {
+    profiles: Vec<Profile>,
+    active: String,
+    next_id: u32,
+}
+
+#[derive(Debug)]
+pub enum ProfileError {
+    EmptyName,
+    NameTooLong,
+    NameTaken,
+    NotFound,
+    LastProfile,
+    Io(std::io::Error),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProfileError::EmptyName => write!(f, "NAME CAN'T BE EMPTY"),
+            ProfileError::NameTooLong => write!(f, "NAME IS LIMITED TO {} CHARACTERS", MAX_PROFILE_NAME),
+            ProfileError::NameTaken => write!(f, "A PROFILE WITH THAT NAME EXISTS"),
+            ProfileError::NotFound => write!(f, "PROFILE NOT FOUND"),
+            ProfileError::LastProfile => write!(f, "CAN'T DELETE THE ONLY PROFILE"),
+            ProfileError::Io(e) => write!(f, "COULDN'T WRITE PROFILES: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {}
+
+impl From<std::io::Error> for ProfileError {
+    fn from(e: std::io::Error) -> Self {
+        ProfileError::Io(e)
+    }
+}
+
+/// Named player profiles. There is always at least one, and one is always active.
+pub struct ProfileManager {
+    storage: SharedStorage,
+    index: ProfileIndex,
+}
+
+impl ProfileManager {
+    pub fn new() -> Self {
+        Self::open(storage::platform_storage())
+    }
+
+    /// Load the profiles in `storage`, creating the first one if there are none.
+    /// A save from before profiles existed becomes that first profile.
+    pub fn open(storage: SharedStorage) -> Self {
+        let index = storage
+            .read("profiles.json")
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str::<ProfileIndex>(&json).ok())
+            .filter(|index| index.profiles.iter().any(|p| p.id == index.active));
+
+        let mut manager = Self {
+            storage,
+            index: index.unwrap_or(ProfileIndex {
+                profiles: Vec::new(),
+                active: String::new(),
+                next_id: 1,
+            }),
+        };
+
+        if manager.index.profiles.is_empty() {
+            let profile = manager.add_profile(DEFAULT_PROFILE_NAME.to_string());
+            manager.index.active = profile.id.clone();
+            manager.adopt_legacy_save(&profile.id);
+            manager.write_index();
+        }
+        manager
+    }
+
+    pub fn profiles(&self) -> &[Profile] {
+        &self.index.profiles
+    }
+
+    pub fn active(&self) -> &Profile {
+        self.find(&self.index.active).expect("active profile always exists")
+    }
+
+    pub fn storage(&self) -> SharedStorage {
+        self.storage.clone()
+    }
+
+    /// Directory holding a profile's save and suspended run
+    fn profile_dir(id: &str) -> String {
+        format!("profiles/{}", id)
+    }
+
+    pub fn save_key(&self, id: &str) -> String {
+        format!("{}/save.json", Self::profile_dir(id))
+    }
+
+    pub fn suspend_key(&self, id: &str) -> String {
+        format!("{}/suspend.json", Self::profile_dir(id))
+    }
+
+    /// Open a profile's save
+    pub fn open_save(&self, id: &str) -> SaveManager {
+        SaveManager::open(self.storage(), &self.save_key(id))
+    }
+
+    /// Add a new profile (it isn't selected)
+    pub fn create(&mut self, name: &str) -> Result<Profile, ProfileError> {
+        let name = self.validate_name(name, None)?;
+        let profile = self.add_profile(name);
+        self.try_write_index()?;
+        Ok(profile)
+    }
+
+    pub fn rename(&mut self, id: &str, name: &str) -> Result<(), ProfileError> {
+        let name = self.validate_name(name, Some(id))?;
+        let profile = self.index.profiles.iter_mut().find(|p| p.id == id).ok_or(ProfileError::NotFound)?;
+        profile.name = name;
+        self.try_write_index()
+    }
+
+    /// Delete a profile and everything saved in it. Deleting the active profile
+    /// makes the first remaining one active.
+    pub fn delete(&mut self, id: &str) -> Result<(), ProfileError> {
+        let position = self.index.profiles.iter().position(|p| p.id == id).ok_or(ProfileError::NotFound)?;
+        if self.index.profiles.len() == 1 {
+            return Err(ProfileError::LastProfile);
+        }
+
+        self.index.profiles.remove(position);
+        if self.index.active == id {
+            self.index.active = self.index.profiles[0].id.clone();
+        }
+        self.try_write_index()?;
+
+        self.storage.remove_all(&Self::profile_dir(id))?;
+        Ok(())
+    }
+
+    pub fn select(&mut self, id: &str) -> Result<(), ProfileError> {
+        self.find(id).ok_or(ProfileError::NotFound)?;
+        self.index.active = id.to_string();
+        self.try_write_index()
+    }
+
+    fn find(&self, id: &str) -> Option<&Profile> {
+        self.index.profiles.iter().find(|p| p.id == id)
+    }
+
+    /// Trimmed, upper-cased name, checked for length and clashes with other profiles
+    fn validate_name(&self, name: &str, renaming: Option<&str>) -> Result<String, ProfileError> {
+        let name = name.trim().to_uppercase();
+        if name.is_empty() {
+            return Err(ProfileError::EmptyName);
+        }
+        if name.chars().count() > MAX_PROFILE_NAME {
+            return Err(ProfileError::NameTooLong);
+        }
+        if self.index.profiles.iter().any(|p| p.name == name && Some(p.id.as_str()) != renaming) {
+            return Err(ProfileError::NameTaken);
+        }
+        Ok(name)
+    }
+
+    fn add_profile(&mut self, name: String) -> Profile {
+        let profile = Profile {
+            id: format!("profile{}", self.index.next_id),
+            name,
+        };
+        self.index.next_id += 1;
+        self.index.profiles.push(profile.clone());
+        profile
+    }
+
+    /// Move `save.json` (and its backups and suspended run) from before profiles
+    /// existed into the given profile
+    fn adopt_legacy_save(&self, id: &str) {
+        let mut files = vec!["save.json".to_string(), "suspend.json".to_string()];
+        files.extend((1..=SAVE_BACKUPS).map(|n| format!("save.json.bak{}", n)));
+
+        for file in files {
+            if self.storage.exists(&file) {
+                match self.storage.rename(&file, &format!("{}/{}", Self::profile_dir(id), file)) {
+                    Ok(()) => println!("Moved {} into profile {}", file, id),
+                    Err(e) => eprintln!("Failed to move {} into profile {}: {}", file, id, e),
+                }
+            }
+        }
+    }
+
+    fn try_write_index(&self) -> Result<(), ProfileError> {
+        let json = serde_json::to_string_pretty(&self.index).map_err(std::io::Error::from)?;
+        self.storage.write("profiles.json", &json)?;
+        Ok(())
+    }
+
+    fn write_index(&self) {
+        if let Err(e) = self.try_write_index() {
+            eprintln!("Failed to write profiles: {}", e);
+        }
+    }
+}
+
+impl Default for ProfileManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::FileStorage;
+    use std::rc::Rc;
+
+    fn temp_storage(name: &str) -> SharedStorage {
+        let dir = std::env::temp_dir().join(format!("glidewars_profiles_{}_{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        Rc::new(FileStorage::new(dir))
+    }
+
+    #[test]
+    fn test_first_launch_creates_a_profile() {
+        let storage = temp_storage("first");
+        let manager = ProfileManager::open(storage.clone());
+        assert_eq!(manager.profiles().len(), 1);
+        assert_eq!(manager.active().name, DEFAULT_PROFILE_NAME);
+        assert!(storage.exists("profiles.json"));
+    }
+
+    #[test]
+    fn test_legacy_save_becomes_first_profile() {
+        let storage = temp_storage("legacy");
+        storage.write("save.json", r#"{"total_deaths": 4}"#).unwrap();
+
+        let manager = ProfileManager::open(storage.clone());
+        assert_eq!(manager.open_save(&manager.active().id).data().total_deaths, 4);
+        assert!(!storage.exists("save.json"));
+    }
+
+    #[test]
+    fn test_create_rename_select_delete() {
+        let storage = temp_storage("crud");
+        let mut manager = ProfileManager::open(storage.clone());
+        let first = manager.active().id.clone();
+
+        let ana = manager.create(" ana ").unwrap();
+        assert_eq!(ana.name, "ANA");
+        manager.select(&ana.id).unwrap();
+        manager.rename(&ana.id, "Bea").unwrap();
+        manager.open_save(&ana.id).save().unwrap();
+
+        // Survives a restart
+        let mut manager = ProfileManager::open(storage.clone());
+        assert_eq!(manager.profiles().len(), 2);
+        assert_eq!(manager.active().name, "BEA");
+
+        manager.delete(&ana.id).unwrap();
+        assert_eq!(manager.active().id, first);
+        assert!(!storage.exists(&manager.save_key(&ana.id)));
+    }
+
+    #[test]
+    fn test_invalid_names_rejected() {
+        let mut manager = ProfileManager::open(temp_storage("names"));
+        assert!(matches!(manager.create("   "), Err(ProfileError::EmptyName)));
+        assert!(matches!(manager.create("ABCDEFGHIJKLM"), Err(ProfileError::NameTooLong)));
+        assert!(matches!(manager.create("player"), Err(ProfileError::NameTaken)));
+
+        // Renaming a profile to its own name is fine
+        let id = manager.active().id.clone();
+        assert!(manager.rename(&id, "Player").is_ok());
+    }
+
+    #[test]
+    fn test_cannot_delete_last_profile() {
+        let mut manager = ProfileManager::open(temp_storage("last"));
+        let id = manager.active().id.clone();
+        assert!(matches!(manager.delete(&id), Err(ProfileError::LastProfile)));
+    }
+
+    #[test]
+    fn test_profiles_keep_separate_saves() {
+        let mut manager = ProfileManager::open(temp_storage("separate"));
+        let first = manager.active().id.clone();
+        let second = manager.create("TWO").unwrap().id;
+
+        let mut save = manager.open_save(&first);
+        save.data_mut().total_deaths = 9;
+        save.save().unwrap();
+
+        manager.select(&second).unwrap();
+        assert_eq!(manager.open_save(&second).data().total_deaths, 0);
+    }
+}
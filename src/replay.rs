@@ -0,0 +1,239 @@
+use crate::assets::Continent;
+use crate::difficulty::DifficultySetting;
+use crate::game_mode::GameMode;
+use crate::player::PlayerControls;
+
+/// First line of every replay's text form
+pub const REPLAY_HEADER: &str = "glidewars-replay 1";
+
+/// Something the player did during a run, in the order it happened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayAction {
+    Controls { controls: PlayerControls, ticks: u32 }, // Held for this many simulation ticks
+    Continue,                                          // Spent a continue at game over
+    Respawn { checkpoint: usize },                     // Respawned at this checkpoint index
+    Resume,                                            // Suspended to the menu, then resumed
+}
+
+/// A run's inputs, enough to simulate it again from its seed and get the same
+/// score. Written as compact text (see `to_text`) so it can go anywhere a string can.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub continent: Continent,
+    pub mode: GameMode,
+    pub difficulty: DifficultySetting,
+    pub adaptive: bool, // Whether the difficulty director was on
+    pub seed: u64,
+    pub player: String, // Profile that flew it, which the online board ranks it under
+    actions: Vec<ReplayAction>,
+}
+
+impl Replay {
+    pub fn new(continent: Continent, difficulty: DifficultySetting, adaptive: bool, seed: u64) -> Self {
+        Self { continent, mode: GameMode::Standard, difficulty, adaptive, seed, player: String::new(), actions: Vec::new() }
+    }
+
+    /// Add one tick of input, merged with the last if it's unchanged
+    pub fn record_tick(&mut self, controls: PlayerControls) {
+        match self.actions.last_mut() {
+            Some(ReplayAction::Controls { controls: last, ticks }) if *last == controls => *ticks += 1,
+            _ => self.actions.push(ReplayAction::Controls { controls, ticks: 1 }),
+        }
+    }
+
+    pub fn record(&mut self, action: ReplayAction) {
+        self.actions.push(action);
+    }
+
+    pub fn actions(&self) -> &[ReplayAction] {
+        &self.actions
+    }
+
+    /// Simulation ticks covered by the replay (saturating, as a replay from
+    /// elsewhere can claim any number)
+    pub fn ticks(&self) -> u32 {
+        self.actions.iter().fold(0, |total: u32, action| match action {
+            ReplayAction::Controls { ticks, .. } => total.saturating_add(*ticks),
+            _ => total,
+        })
+    }
+
+    /// ```text
+    /// glidewars-replay 1
+    /// <continent index> <difficulty index> <adaptive 0|1> <seed> <mode index> <player>
+    /// 00*120 21*35 20 R1 C ...
+    /// ```
+    /// Controls are a hex bitmask (see `controls_bits`) with `*ticks` when held
+    /// for more than one tick, `C` is a continue, `R<n>` a respawn at checkpoint n
+    /// and `S` a suspend and resume. Replays from before modes leave the mode out
+    /// and are standard runs; ones from before players were named leave the player
+    /// out. The player's name runs to the end of the line, spaces and all.
+    pub fn to_text(&self) -> String {
+        let continent = Continent::all().iter().position(|c| *c == self.continent).unwrap_or(0);
+        let mode = GameMode::all().iter().position(|m| *m == self.mode).unwrap_or(0);
+        let tokens: Vec<String> = self
+            .actions
+            .iter()
+            .map(|action| match action {
+                ReplayAction::Controls { controls, ticks: 1 } => format!("{:02x}", controls_bits(*controls)),
+                ReplayAction::Controls { controls, ticks } => format!("{:02x}*{}", controls_bits(*controls), ticks),
+                ReplayAction::Continue => "C".to_string(),
+                ReplayAction::Respawn { checkpoint } => format!("R{}", checkpoint),
+                ReplayAction::Resume => "S".to_string(),
+            })
+            .collect();
+        format!(
+            "{}\n{} {} {} {} {} {}\n{}",
+            REPLAY_HEADER,
+            continent,
+            self.difficulty as u8,
+            u8::from(self.adaptive),
+            self.seed,
+            mode,
+            self.player,
+            tokens.join(" ")
+        )
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines();
+        if lines.next().map(str::trim) != Some(REPLAY_HEADER) {
+            return Err("not a replay".to_string());
+        }
+
+        let header: Vec<&str> = lines.next().unwrap_or("").trim().splitn(6, ' ').collect();
+        let (continent, difficulty, adaptive, seed, mode, player) = match header[..] {
+            [continent, difficulty, adaptive, seed] => (continent, difficulty, adaptive, seed, "0", ""),
+            [continent, difficulty, adaptive, seed, mode] => (continent, difficulty, adaptive, seed, mode, ""),
+            [continent, difficulty, adaptive, seed, mode, player] => (continent, difficulty, adaptive, seed, mode, player),
+            _ => return Err("replay header is incomplete".to_string()),
+        };
+        let continent = continent
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| Continent::all().get(i).copied())
+            .ok_or("replay names an unknown continent")?;
+        let difficulty = match difficulty {
+            "0" | "1" | "2" => DifficultySetting::from_index(difficulty.parse().unwrap_or(1)),
+            _ => return Err("replay names an unknown difficulty".to_string()),
+        };
+        let adaptive = match adaptive {
+            "0" => false,
+            "1" => true,
+            _ => return Err("replay header is invalid".to_string()),
+        };
+        let seed = seed.parse().map_err(|_| "replay seed is invalid")?;
+        let mode = mode
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| GameMode::all().get(i).copied())
+            .ok_or("replay names an unknown mode")?;
+
+        let mut replay = Replay::new(continent, difficulty, adaptive, seed);
+        replay.mode = mode;
+        replay.player = player.trim().to_string();
+        for token in lines.flat_map(str::split_whitespace) {
+            let action = match token.as_bytes()[0] {
+                b'C' if token == "C" => ReplayAction::Continue,
+                b'S' if token == "S" => ReplayAction::Resume,
+                b'R' => ReplayAction::Respawn {
+                    checkpoint: token[1..].parse().map_err(|_| format!("invalid respawn {:?}", token))?,
+                },
+                _ => {
+                    let (bits, ticks) = token.split_once('*').unwrap_or((token, "1"));
+                    let bits = u8::from_str_radix(bits, 16).ok().filter(|bits| *bits < 1 << 6);
+                    let ticks = ticks.parse::<u32>().ok().filter(|ticks| *ticks > 0);
+                    match (bits, ticks) {
+                        (Some(bits), Some(ticks)) => ReplayAction::Controls { controls: controls_from_bits(bits), ticks },
+                        _ => return Err(format!("invalid input {:?}", token)),
+                    }
+                }
+            };
+            replay.actions.push(action);
+        }
+        Ok(replay)
+    }
+}
+
+/// Left, right, up, down, boost and shoot as bits 0 to 5
+fn controls_bits(controls: PlayerControls) -> u8 {
+    [controls.left, controls.right, controls.up, controls.down, controls.boost, controls.shoot]
+        .iter()
+        .enumerate()
+        .fold(0, |bits, (i, held)| bits | (u8::from(*held) << i))
+}
+
+fn controls_from_bits(bits: u8) -> PlayerControls {
+    let held = |i: u8| bits & (1 << i) != 0;
+    PlayerControls {
+        left: held(0),
+        right: held(1),
+        up: held(2),
+        down: held(3),
+        boost: held(4),
+        shoot: held(5),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_round_trip() {
+        let climb = PlayerControls { up: true, shoot: true, ..Default::default() };
+        let mut replay = Replay::new(Continent::Asia, DifficultySetting::Hard, true, 987_654_321);
+        for _ in 0..120 {
+            replay.record_tick(PlayerControls::default());
+        }
+        replay.record_tick(climb);
+        replay.record(ReplayAction::Continue);
+        replay.record(ReplayAction::Respawn { checkpoint: 2 });
+        replay.record(ReplayAction::Resume);
+        replay.record_tick(climb);
+
+        assert_eq!(replay.actions().len(), 6);
+        assert_eq!(replay.ticks(), 122);
+
+        let text = replay.to_text();
+        assert!(text.ends_with("00*120 24 C R2 S 24"));
+        assert_eq!(Replay::from_text(&text).unwrap(), replay);
+    }
+
+    #[test]
+    fn test_rejects_malformed_text() {
+        assert!(Replay::from_text("hello").is_err());
+        assert!(Replay::from_text("glidewars-replay 1\n9 1 0 5\n").is_err());
+        assert!(Replay::from_text("glidewars-replay 1\n1 1 0\n").is_err());
+        assert!(Replay::from_text("glidewars-replay 1\n1 1 0 5\n00*0").is_err());
+        assert!(Replay::from_text("glidewars-replay 1\n1 1 0 5\nff").is_err());
+        assert!(Replay::from_text("glidewars-replay 1\n1 1 0 5\nRx").is_err());
+        assert!(Replay::from_text("glidewars-replay 1\n1 1 0 5 9\n").is_err());
+        assert!(Replay::from_text("glidewars-replay 1\n1 1 0 5\n").is_ok());
+    }
+
+    #[test]
+    fn test_mode_in_header() {
+        let mut replay = Replay::new(Continent::Europe, DifficultySetting::Normal, false, 5);
+        replay.mode = GameMode::TimeAttack;
+        assert_eq!(Replay::from_text(&replay.to_text()).unwrap().mode, GameMode::TimeAttack);
+
+        // Written before modes: a standard run
+        let old = Replay::from_text("glidewars-replay 1\n3 1 0 5\n00*10").unwrap();
+        assert_eq!(old.mode, GameMode::Standard);
+    }
+
+    #[test]
+    fn test_player_in_header() {
+        let mut replay = Replay::new(Continent::Europe, DifficultySetting::Normal, false, 5);
+        replay.player = "SKY ACE".to_string();
+        replay.record_tick(PlayerControls::default());
+        let text = replay.to_text();
+        assert!(text.contains("\n3 1 0 5 0 SKY ACE\n"));
+        assert_eq!(Replay::from_text(&text).unwrap(), replay);
+
+        // Written before players were named
+        let old = Replay::from_text("glidewars-replay 1\n3 1 0 5 1\n00*10").unwrap();
+        assert_eq!(old.player, "");
+    }
+}
@@ -1,36 +1,123 @@
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::events::{EventBus, GameEvent};
+use crate::fmath;
 use crate::player::Player;
+use crate::rng;
+use crate::spatial_grid::SpatialGrid;
+
+/// Passing within this fraction of the radius counts as a center pass
+pub const CENTER_PASS_FRACTION: f32 = 0.3;
+/// How far behind the player an unpassed gate must fall before it counts as missed
+const MISS_DISTANCE: f32 = 3.0;
+const GATE_SPACING: f32 = 18.0;
+
+/// Shape a course of gates follows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoursePattern {
+    Curve,  // Sweeping arc from one side to the other
+    Spiral, // Gates rotate around a central axis
+    Slalom, // Alternating left/right
+}
+
+impl CoursePattern {
+    fn random() -> Self {
+        match rng::gen_range(0, 3) {
+            0 => CoursePattern::Curve,
+            1 => CoursePattern::Spiral,
+            _ => CoursePattern::Slalom,
+        }
+    }
 
+    /// X/Y offset of gate `index` in a course of `count` gates
+    fn offset(&self, index: usize, count: usize) -> Vec2 {
+        let t = index as f32 / (count - 1).max(1) as f32;
+        match self {
+            CoursePattern::Curve => vec2(
+                fmath::sin(t * std::f32::consts::PI - std::f32::consts::FRAC_PI_2) * 4.5,
+                2.0 + fmath::sin(t * std::f32::consts::PI) * 1.5,
+            ),
+            CoursePattern::Spiral => {
+                let angle = index as f32 * std::f32::consts::FRAC_PI_3;
+                vec2(fmath::cos(angle) * 3.0, 2.0 + fmath::sin(angle) * 2.0)
+            }
+            CoursePattern::Slalom => {
+                let side = if index.is_multiple_of(2) { -1.0 } else { 1.0 };
+                vec2(side * 3.5, 2.0)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Ring {
+    #[serde(with = "crate::snapshot::vec3")]
     pub position: Vec3,
     pub radius: f32,
     pub collected: bool,
+    pub missed: bool,
     pub rotation: f32,
+    pub course_id: u32,
+}
+
+impl Ring {
+    fn is_resolved(&self) -> bool {
+        self.collected || self.missed
+    }
+}
+
+/// Progress through one course of gates
+#[derive(Clone, Serialize, Deserialize)]
+struct CourseProgress {
+    gates: u32,
+    resolved: u32,
+    missed: u32,
+}
+
+/// Gates, course progress and the current chain at a checkpoint
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RingSnapshot {
+    rings: Vec<Ring>,
+    courses: HashMap<u32, CourseProgress>,
+    next_course_id: u32,
+    chain: u32,
+    spawn_timer: f32,
+    last_spawn_z: f32,
 }
 
 pub struct RingManager {
     rings: Vec<Ring>,
+    courses: HashMap<u32, CourseProgress>,
+    next_course_id: u32,
+    chain: u32, // Gates passed in a row without a miss
     spawn_timer: f32,
     spawn_interval: f32,
     last_spawn_z: f32,
+    grid: SpatialGrid,
 }
 
 impl RingManager {
     pub fn new() -> Self {
         Self {
             rings: Vec::new(),
+            courses: HashMap::new(),
+            next_course_id: 0,
+            chain: 0,
             spawn_timer: 0.0,
-            spawn_interval: 5.0, // Spawn ring every 5 seconds
+            spawn_interval: 8.0, // Try to start a new course every 8 seconds
             last_spawn_z: 0.0,
+            grid: SpatialGrid::new(10.0),
         }
     }
 
     pub fn update(&mut self, dt: f32, player: &Player) {
         self.spawn_timer += dt;
 
-        // Spawn new ring ahead of player
+        // Lay out a new course ahead of player
         if self.spawn_timer >= self.spawn_interval {
-            self.spawn_ring(player.position().z);
+            let gates = rng::gen_range(5, 9);
+            self.spawn_course(player.position().z, CoursePattern::random(), gates);
             self.spawn_timer = 0.0;
         }
 
@@ -43,41 +130,73 @@ impl RingManager {
         self.rings.retain(|ring| {
             ring.position.z > player.position().z - 30.0
         });
+
+        self.rebuild_grid();
+    }
+
+    /// The broad-phase index, rebuilt on every update
+    pub fn grid(&self) -> &SpatialGrid {
+        &self.grid
+    }
+
+    fn rebuild_grid(&mut self) {
+        self.grid.clear();
+        for (index, ring) in self.rings.iter().enumerate() {
+            self.grid.insert(index, ring.position.z, ring.position.z);
+        }
     }
 
-    fn spawn_ring(&mut self, player_z: f32) {
-        // Spawn ring 80-120 units ahead of player
-        let spawn_z = player_z + 80.0 + rand::gen_range(0.0, 40.0);
+    /// Lay out a course of gates starting 80-120 units ahead of the player
+    pub fn spawn_course(&mut self, player_z: f32, pattern: CoursePattern, gates: usize) {
+        let start_z = player_z + 80.0 + rng::gen_range(0.0, 40.0);
 
-        // Avoid spawning too close to previous ring
-        if spawn_z - self.last_spawn_z < 50.0 {
+        // Leave a gap after the previous course
+        if start_z - self.last_spawn_z < 40.0 {
             return;
         }
 
-        // Random position within flight area
-        let x = rand::gen_range(-6.0, 6.0);
-        let y = rand::gen_range(0.0, 4.0);
+        let course_id = self.next_course_id;
+        self.next_course_id += 1;
+
+        let center_x = rng::gen_range(-1.5, 1.5);
+        for i in 0..gates {
+            let offset = pattern.offset(i, gates);
+            self.rings.push(Ring {
+                position: vec3(
+                    (center_x + offset.x).clamp(-6.0, 6.0),
+                    offset.y.clamp(0.0, 5.0),
+                    start_z + i as f32 * GATE_SPACING,
+                ),
+                radius: 3.0,
+                collected: false,
+                missed: false,
+                rotation: 0.0,
+                course_id,
+            });
+        }
 
-        self.rings.push(Ring {
-            position: vec3(x, y, spawn_z),
-            radius: 4.0,
-            collected: false,
-            rotation: 0.0,
+        self.courses.insert(course_id, CourseProgress {
+            gates: gates as u32,
+            resolved: 0,
+            missed: 0,
         });
-
-        self.last_spawn_z = spawn_z;
+        self.last_spawn_z = start_z + (gates - 1) as f32 * GATE_SPACING;
+        self.rebuild_grid();
     }
 
-    pub fn check_collection(&mut self, player: &Player, score: &mut u32) -> bool {
+    /// Resolve gates the player flew through or left behind this frame
+    pub fn check_collection(&mut self, player: &Player, events: &mut EventBus) -> bool {
         let mut collected_any = false;
+        let player_pos = player.position();
+        let mut resolved_courses = Vec::new();
 
-        for ring in &mut self.rings {
-            if ring.collected {
+        for index in self.grid.candidates(player_pos.z - 3.0, player_pos.z + 3.0) {
+            let ring = &mut self.rings[index];
+            if ring.is_resolved() {
                 continue;
             }
 
             // Check if player flew through the ring
-            let player_pos = player.position();
             let distance = vec3(
                 player_pos.x - ring.position.x,
                 player_pos.y - ring.position.y,
@@ -93,24 +212,67 @@ impl RingManager {
 
             if distance < collection_radius && z_distance < 3.0 {
                 ring.collected = true;
-                *score += 100; // Bonus points for flying through ring
+                self.chain += 1;
+                events.emit(GameEvent::RingPassed {
+                    position: ring.position,
+                    chain: self.chain,
+                    precision: 1.0 - (distance / ring.radius).min(1.0),
+                });
+                resolved_courses.push(ring.course_id);
                 collected_any = true;
             }
         }
 
+        // Anything still unpassed once it is behind the player was missed
+        for ring in &mut self.rings {
+            if !ring.is_resolved() && ring.position.z < player_pos.z - MISS_DISTANCE {
+                ring.missed = true;
+                self.chain = 0;
+                events.emit(GameEvent::RingMissed { position: ring.position });
+                if let Some(course) = self.courses.get_mut(&ring.course_id) {
+                    course.missed += 1;
+                }
+                resolved_courses.push(ring.course_id);
+            }
+        }
+
+        for course_id in resolved_courses {
+            if let Some(course) = self.courses.get_mut(&course_id) {
+                course.resolved += 1;
+                if course.resolved == course.gates {
+                    events.emit(GameEvent::RingCourseCompleted {
+                        gates: course.gates,
+                        perfect: course.missed == 0,
+                    });
+                    self.courses.remove(&course_id);
+                }
+            }
+        }
+
         collected_any
     }
 
     pub fn draw(&self) {
+        // Highlight the next gate to fly through
+        let next_gate_z = self
+            .rings
+            .iter()
+            .filter(|ring| !ring.is_resolved())
+            .map(|ring| ring.position.z)
+            .fold(f32::MAX, f32::min);
+
         for ring in &self.rings {
-            if ring.collected {
-                continue; // Don't draw collected rings
+            if ring.is_resolved() {
+                continue; // Don't draw collected or missed rings
             }
 
             // Draw ring using multiple segments
             let segments = 32;
-            let ring_color = Color::from_rgba(0, 255, 255, 200); // Cyan semi-transparent
-            let ring_inner_color = Color::from_rgba(0, 200, 200, 100); // Darker cyan
+            let (ring_color, ring_inner_color) = if ring.position.z == next_gate_z {
+                (Color::from_rgba(255, 255, 0, 230), Color::from_rgba(255, 200, 0, 120)) // Yellow
+            } else {
+                (Color::from_rgba(0, 255, 255, 200), Color::from_rgba(0, 200, 200, 100)) // Cyan
+            };
 
             for i in 0..segments {
                 let angle1 = (i as f32 / segments as f32) * std::f32::consts::PI * 2.0 + ring.rotation;
@@ -128,8 +290,8 @@ impl RingManager {
                 // Draw outer edge
                 draw_line_3d(p1, p2, ring_color);
 
-                // Inner ring (slightly smaller)
-                let inner_radius = ring.radius * 0.7;
+                // Inner ring marks the center-pass zone
+                let inner_radius = ring.radius * CENTER_PASS_FRACTION;
                 let ix1 = ring.position.x + angle1.cos() * inner_radius;
                 let iy1 = ring.position.y + angle1.sin() * inner_radius;
                 let ix2 = ring.position.x + angle2.cos() * inner_radius;
@@ -148,8 +310,8 @@ impl RingManager {
             let num_spokes = 8;
             for i in 0..num_spokes {
                 let angle = (i as f32 / num_spokes as f32) * std::f32::consts::PI * 2.0 + ring.rotation;
-                let x = ring.position.x + angle.cos() * ring.radius * 0.7;
-                let y = ring.position.y + angle.sin() * ring.radius * 0.7;
+                let x = ring.position.x + angle.cos() * ring.radius * CENTER_PASS_FRACTION;
+                let y = ring.position.y + angle.sin() * ring.radius * CENTER_PASS_FRACTION;
                 let outer_x = ring.position.x + angle.cos() * ring.radius;
                 let outer_y = ring.position.y + angle.sin() * ring.radius;
 
@@ -162,13 +324,96 @@ impl RingManager {
         }
     }
 
-    pub fn clear(&mut self) {
-        self.rings.clear();
-        self.spawn_timer = 0.0;
-        self.last_spawn_z = 0.0;
+    pub fn snapshot(&self) -> RingSnapshot {
+        RingSnapshot {
+            rings: self.rings.clone(),
+            courses: self.courses.clone(),
+            next_course_id: self.next_course_id,
+            chain: self.chain,
+            spawn_timer: self.spawn_timer,
+            last_spawn_z: self.last_spawn_z,
+        }
     }
 
-    pub fn reset(&mut self) {
-        self.clear();
+    pub fn restore(&mut self, snapshot: &RingSnapshot) {
+        self.rings = snapshot.rings.clone();
+        self.courses = snapshot.courses.clone();
+        self.next_course_id = snapshot.next_course_id;
+        self.chain = snapshot.chain;
+        self.spawn_timer = snapshot.spawn_timer;
+        self.last_spawn_z = snapshot.last_spawn_z;
+        self.rebuild_grid();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fly down the course, steering through the gates listed in `pass` and wide of the rest
+    fn fly_course(manager: &mut RingManager, pass: &[usize]) -> Vec<GameEvent> {
+        let mut events = EventBus::new();
+        let mut player = Player::new();
+        let gates: Vec<Vec3> = manager.rings.iter().map(|ring| ring.position).collect();
+        let end_z = gates.last().unwrap().z + 10.0;
+
+        let mut z = gates[0].z - 10.0;
+        while z < end_z {
+            let target = gates
+                .iter()
+                .enumerate()
+                .find(|(_, gate)| gate.z + 3.0 >= z)
+                .filter(|(i, _)| pass.contains(i))
+                .map(|(_, gate)| vec3(gate.x, gate.y, z))
+                .unwrap_or(vec3(20.0, 20.0, z));
+            player.set_position(target);
+            manager.check_collection(&player, &mut events);
+            z += 0.5;
+        }
+        events.drain()
+    }
+
+    #[test]
+    fn test_course_lays_out_gates_in_order() {
+        let mut manager = RingManager::new();
+        manager.spawn_course(0.0, CoursePattern::Spiral, 6);
+
+        assert_eq!(manager.rings.len(), 6);
+        for pair in manager.rings.windows(2) {
+            assert!((pair[1].position.z - pair[0].position.z - GATE_SPACING).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_perfect_course_builds_chain() {
+        let mut manager = RingManager::new();
+        manager.spawn_course(0.0, CoursePattern::Slalom, 5);
+
+        let events = fly_course(&mut manager, &[0, 1, 2, 3, 4]);
+        let chains: Vec<u32> = events
+            .iter()
+            .filter_map(|event| match event {
+                GameEvent::RingPassed { chain, precision, .. } => {
+                    assert!(*precision > 0.99); // Dead center
+                    Some(*chain)
+                }
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(chains, vec![1, 2, 3, 4, 5]);
+        assert!(events.contains(&GameEvent::RingCourseCompleted { gates: 5, perfect: true }));
+    }
+
+    #[test]
+    fn test_missed_gate_breaks_chain() {
+        let mut manager = RingManager::new();
+        manager.spawn_course(0.0, CoursePattern::Curve, 5);
+
+        let events = fly_course(&mut manager, &[0, 1, 3, 4]);
+        let misses = events.iter().filter(|event| matches!(event, GameEvent::RingMissed { .. })).count();
+        assert_eq!(misses, 1);
+        assert_eq!(manager.chain, 2);
+        assert!(events.contains(&GameEvent::RingCourseCompleted { gates: 5, perfect: false }));
     }
 }
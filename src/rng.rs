@@ -0,0 +1,92 @@
+use std::cell::Cell;
+
+// Gameplay randomness (spawns, boss attacks) comes from its own generator rather
+// than macroquad's global one, so a run seeded with the same value plays out the
+// same way whatever the renderer and menus draw for their own effects. That is
+// what lets a replay be simulated again without a window. It's per thread so
+// simulations running side by side (tests, the leaderboard server) can't disturb
+// each other, and its state can be read back so a suspended run resumes exactly.
+thread_local! {
+    static STATE: Cell<u64> = const { Cell::new(0) };
+}
+
+// PCG, as macroquad's generator uses
+const MULTIPLIER: u64 = 6364136223846793005;
+const INCREMENT: u64 = 1442695040888963407;
+
+/// Restart gameplay randomness from `seed`
+pub fn srand(seed: u64) {
+    set_state(0);
+    next_u32();
+    set_state(state().wrapping_add(seed));
+    next_u32();
+}
+
+/// Where the generator is, for `set_state` to return to
+pub fn state() -> u64 {
+    STATE.with(Cell::get)
+}
+
+pub fn set_state(state: u64) {
+    STATE.with(|cell| cell.set(state));
+}
+
+fn next_u32() -> u32 {
+    let old = state();
+    set_state(old.wrapping_mul(MULTIPLIER).wrapping_add(INCREMENT));
+    let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+    xorshifted.rotate_right((old >> 59) as u32)
+}
+
+/// Numbers `gen_range` can produce
+pub trait GenRange: Copy {
+    fn from_fraction(low: Self, high: Self, fraction: f64) -> Self;
+}
+
+macro_rules! impl_gen_range {
+    ($($ty:ty),*) => {
+        $(
+            impl GenRange for $ty {
+                fn from_fraction(low: Self, high: Self, fraction: f64) -> Self {
+                    (low as f64 + (high as f64 - low as f64) * fraction) as Self
+                }
+            }
+        )*
+    };
+}
+impl_gen_range!(f32, f64, i32, u32, usize);
+
+/// A value in `low..high` from the gameplay generator
+pub fn gen_range<T: GenRange>(low: T, high: T) -> T {
+    T::from_fraction(low, high, next_u32() as f64 / (u32::MAX as f64 + 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        srand(1234);
+        let first: Vec<i32> = (0..5).map(|_| gen_range(0, 1000)).collect();
+
+        // Drawing from macroquad's generator in between doesn't matter
+        macroquad::rand::gen_range(0, 10);
+        srand(1234);
+        let second: Vec<i32> = (0..5).map(|_| gen_range(0, 1000)).collect();
+        assert_eq!(first, second);
+        assert!(first.iter().all(|n| (0..1000).contains(n)));
+    }
+
+    #[test]
+    fn test_state_restores_sequence() {
+        srand(99);
+        gen_range(0.0, 1.0);
+        let saved = state();
+        let expected = gen_range(0.0, 1.0);
+        gen_range(0.0, 1.0);
+
+        set_state(saved);
+        assert_eq!(gen_range(0.0, 1.0), expected);
+    }
+}
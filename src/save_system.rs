@@ -1,19 +1,44 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
+use serde_json::Value;
+use crate::achievements::AchievementProgress;
 use crate::assets::Continent;
+use crate::events::{EventListener, GameEvent};
+use crate::game_mode::GameMode;
+use crate::profile_export::{ImportMode, ProfileExport};
+use crate::stats::PlayerStats;
+use crate::storage::{self, SharedStorage, Storage};
+
+/// Current layout of `save.json`. Bump it and add a step to `MIGRATIONS` whenever
+/// a change can't be covered by `#[serde(default)]` on a new field.
+pub const SAVE_FORMAT_VERSION: u32 = 2;
+
+/// How many previous saves are kept as `save.json.bak1` (newest) to `.bakN`
+pub const SAVE_BACKUPS: usize = 3;
+
+/// `MIGRATIONS[n]` upgrades a version `n + 1` save to version `n + 2`
+const MIGRATIONS: [fn(&mut Value); SAVE_FORMAT_VERSION as usize - 1] = [
+    migrate_v1_to_v2,
+];
+
+/// Version 1 saves predate the version field; the layout is otherwise unchanged
+fn migrate_v1_to_v2(_save: &mut Value) {}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)] // Fields added later load from older saves with their default
 pub struct SaveData {
+    pub version: u32,
     pub player_name: Option<String>,
     pub unlocked_continents: Vec<String>,
     pub high_scores: HashMap<String, u32>,
     pub best_times: HashMap<String, f32>,
+    pub boss_rush_best: Option<BossRushRecord>,
     pub settings: Settings,
     pub total_play_time: f32,
     pub total_deaths: u32,
     pub total_boss_kills: u32,
+    pub achievements: AchievementProgress,
+    pub stats: PlayerStats,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +46,30 @@ pub struct Settings {
     pub sound_volume: f32,
     pub music_volume: f32,
     pub difficulty: u8, // 0=Easy, 1=Normal, 2=Hard
+    #[serde(default)]
+    pub adaptive_difficulty: bool,
+    #[serde(default = "default_respawn_countdown")]
+    pub respawn_countdown: f32, // Seconds on the checkpoint screen before respawning
+    #[serde(default)]
+    pub ghost: bool, // Race a ghost of the best run on the continent's board
+}
+
+/// A cleared boss rush: how long it took and how much health it cost
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BossRushRecord {
+    pub time: f32, // Seconds, respawns included
+    pub damage_taken: f32,
+}
+
+impl BossRushRecord {
+    /// Whether this clear is better than `other`: faster, then less damage
+    pub fn beats(&self, other: &BossRushRecord) -> bool {
+        self.time < other.time || (self.time == other.time && self.damage_taken < other.damage_taken)
+    }
+}
+
+fn default_respawn_countdown() -> f32 {
+    crate::checkpoint::DEFAULT_RESPAWN_COUNTDOWN
 }
 
 impl Default for SaveData {
@@ -29,29 +78,68 @@ impl Default for SaveData {
         unlocked.push("Tutorial".to_string()); // Tutorial always unlocked
 
         Self {
+            version: SAVE_FORMAT_VERSION,
             player_name: None,
             unlocked_continents: unlocked,
             high_scores: HashMap::new(),
             best_times: HashMap::new(),
+            boss_rush_best: None,
             settings: Settings::default(),
             total_play_time: 0.0,
             total_deaths: 0,
             total_boss_kills: 0,
+            achievements: AchievementProgress::default(),
+            stats: PlayerStats::default(),
         }
     }
 }
 
+/// `best_times` key: the continent's name for standard runs (as saves have always
+/// had it), prefixed with the mode for the others, e.g. "Time Attack/Europe"
+fn best_time_key(continent: Continent, mode: GameMode) -> String {
+    match mode {
+        GameMode::Standard => continent.name().to_string(),
+        _ => format!("{}/{}", mode.name(), continent.name()),
+    }
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
             sound_volume: 100.0,
             music_volume: 100.0,
             difficulty: 1, // Normal
+            adaptive_difficulty: false,
+            respawn_countdown: default_respawn_countdown(),
+            ghost: false,
         }
     }
 }
 
 impl SaveData {
+    /// Read a save of any known version, running it through the migration chain
+    pub fn from_value(mut value: Value) -> Result<Self, Box<dyn std::error::Error>> {
+        if !value.is_object() {
+            return Err("save is not a JSON object".into());
+        }
+
+        // Saves without a version field were written before versioning (version 1)
+        let version = match value.get("version") {
+            None => 1,
+            Some(v) => v.as_u64().ok_or("save version is not a number")? as u32,
+        };
+        if version == 0 || version > SAVE_FORMAT_VERSION {
+            return Err(format!("unsupported save version {} (this build reads up to {})", version, SAVE_FORMAT_VERSION).into());
+        }
+
+        for migration in &MIGRATIONS[version as usize - 1..] {
+            migration(&mut value);
+        }
+        value["version"] = SAVE_FORMAT_VERSION.into();
+
+        Ok(serde_json::from_value(value)?)
+    }
+
     /// Check if a continent is unlocked
     pub fn is_continent_unlocked(&self, continent: &Continent) -> bool {
         let continent_name = continent.name();
@@ -81,30 +169,44 @@ impl SaveData {
         }
     }
 
-    /// Update best time for a continent
-    pub fn update_best_time(&mut self, continent: Continent, time: f32) -> bool {
-        let continent_name = continent.name().to_string();
-        let current_best = self.best_times.get(&continent_name).copied().unwrap_or(f32::MAX);
+    /// Update best time for a continent in a mode
+    pub fn update_best_time(&mut self, continent: Continent, mode: GameMode, time: f32) -> bool {
+        let key = best_time_key(continent, mode);
+        let current_best = self.best_times.get(&key).copied().unwrap_or(f32::MAX);
 
         if time < current_best {
-            self.best_times.insert(continent_name.clone(), time);
-            println!("New best time for {}: {:.1}s", continent_name, time);
+            self.best_times.insert(key.clone(), time);
+            println!("New best time for {}: {:.1}s", key, time);
             true
         } else {
             false
         }
     }
 
+    /// Whether the continent's level has been finished in standard mode
+    pub fn has_cleared(&self, continent: &Continent) -> bool {
+        self.get_best_time(continent, GameMode::Standard).is_some()
+    }
+
+    /// Keep a boss rush clear if it's the best yet
+    pub fn update_boss_rush(&mut self, record: BossRushRecord) -> bool {
+        if self.boss_rush_best.is_some_and(|best| !record.beats(&best)) {
+            return false;
+        }
+        self.boss_rush_best = Some(record);
+        println!("New best boss rush: {:.1}s, {:.0} damage", record.time, record.damage_taken);
+        true
+    }
+
     /// Get high score for a continent
     pub fn get_high_score(&self, continent: &Continent) -> u32 {
         let continent_name = continent.name().to_string();
         self.high_scores.get(&continent_name).copied().unwrap_or(0)
     }
 
-    /// Get best time for a continent
-    pub fn get_best_time(&self, continent: &Continent) -> Option<f32> {
-        let continent_name = continent.name().to_string();
-        self.best_times.get(&continent_name).copied()
+    /// Get best time for a continent in a mode
+    pub fn get_best_time(&self, continent: &Continent, mode: GameMode) -> Option<f32> {
+        self.best_times.get(&best_time_key(*continent, mode)).copied()
     }
 
     /// Update settings
@@ -114,6 +216,47 @@ impl SaveData {
         self.settings.difficulty = difficulty;
     }
 
+    /// Fold another copy of this player's progress in, keeping the best of both:
+    /// every unlock and achievement, the higher score and faster time per continent, and
+    /// the larger lifetime stats. Settings are left as they are.
+    pub fn merge(&mut self, other: &SaveData) {
+        for continent in &other.unlocked_continents {
+            if !self.unlocked_continents.contains(continent) {
+                self.unlocked_continents.push(continent.clone());
+            }
+        }
+        for (continent, &score) in &other.high_scores {
+            let best = self.high_scores.entry(continent.clone()).or_insert(0);
+            *best = (*best).max(score);
+        }
+        for (continent, &time) in &other.best_times {
+            let best = self.best_times.entry(continent.clone()).or_insert(f32::MAX);
+            *best = best.min(time);
+        }
+        if let Some(record) = other.boss_rush_best {
+            self.update_boss_rush(record);
+        }
+
+        // Both copies usually share history, so adding would count it twice
+        self.total_play_time = self.total_play_time.max(other.total_play_time);
+        self.total_deaths = self.total_deaths.max(other.total_deaths);
+        self.total_boss_kills = self.total_boss_kills.max(other.total_boss_kills);
+        self.achievements.merge(&other.achievements);
+        self.stats.merge(&other.stats);
+    }
+
+    /// Start recording stats and achievement progress for a level played from the start
+    pub fn start_level(&mut self, continent: Continent) {
+        self.stats.start_level(continent);
+        self.achievements.start_level(continent);
+    }
+
+    /// Carry on recording for a resumed run
+    pub fn resume_level(&mut self, continent: Continent) {
+        self.stats.resume_level(continent);
+        self.achievements.resume_level(continent);
+    }
+
     /// Add play time
     pub fn add_play_time(&mut self, time: f32) {
         self.total_play_time += time;
@@ -130,60 +273,176 @@ impl SaveData {
     }
 }
 
+/// Stats and achievements are kept up to date from gameplay events
+impl EventListener for SaveData {
+    fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::PlayerDied { .. } => self.record_death(),
+            GameEvent::BossDefeated { .. } => self.record_boss_kill(),
+            _ => {}
+        }
+        self.stats.on_event(event);
+        self.achievements.on_event(event);
+    }
+}
+
 pub struct SaveManager {
-    save_path: PathBuf,
+    storage: SharedStorage,
+    key: String,
     current_save: SaveData,
 }
 
 impl SaveManager {
     pub fn new() -> Self {
-        let save_path = Self::get_save_path();
+        Self::open(storage::platform_storage(), "save.json")
+    }
 
-        // Try to load existing save, otherwise use default
-        let current_save = Self::load_from_path(&save_path).unwrap_or_default();
+    /// Load the save stored under `key`. An unreadable one is quarantined next to it so
+    /// the next save can't overwrite it; either way the newest valid backup is used in its
+    /// place. With no save and no backups, a fresh save is started.
+    pub fn open(storage: SharedStorage, key: &str) -> Self {
+        let current_save = match Self::load(storage.as_ref(), key) {
+            Ok(save) => save,
+            Err(error) => {
+                if let LoadError::Unreadable(e) = error {
+                    eprintln!("Save {} is unreadable: {}", key, e);
+                    Self::quarantine(storage.as_ref(), key);
+                }
+                Self::recover_from_backups(storage.as_ref(), key).unwrap_or_default()
+            }
+        };
 
         Self {
-            save_path,
+            storage,
+            key: key.to_string(),
             current_save,
         }
     }
 
-    /// Get the save file path (platform-specific)
-    fn get_save_path() -> PathBuf {
-        // For desktop: use user data directory
-        // For web: this will be handled differently (localStorage)
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            let mut path = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-            path.push(".glidewars");
-            fs::create_dir_all(&path).ok();
-            path.push("save.json");
-            path
+    /// Key of the `n`th backup (1 is the newest)
+    fn backup_key(key: &str, n: usize) -> String {
+        format!("{}.bak{}", key, n)
+    }
+
+    /// Load save data from storage
+    fn load(storage: &dyn Storage, key: &str) -> Result<SaveData, LoadError> {
+        // Only a save that isn't there is missing; one that's there but can't be
+        // read (not UTF-8, no permission) is as unreadable as one that won't parse
+        let contents = match storage.read(key) {
+            Ok(Some(contents)) => contents,
+            Ok(None) => return Err(LoadError::Missing),
+            Err(e) => return Err(LoadError::Unreadable(e.into())),
+        };
+        let save_data = Self::decode(&contents).map_err(LoadError::Unreadable)?;
+        println!("Loaded save: {}", key);
+        Ok(save_data)
+    }
+
+    /// Newest backup that still passes its checksum
+    fn recover_from_backups(storage: &dyn Storage, key: &str) -> Option<SaveData> {
+        (1..=SAVE_BACKUPS).find_map(|n| {
+            let backup = Self::backup_key(key, n);
+            match Self::load(storage, &backup) {
+                Ok(save) => {
+                    println!("Recovered save from backup: {}", backup);
+                    Some(save)
+                }
+                Err(LoadError::Unreadable(e)) => {
+                    eprintln!("Skipping unreadable backup {}: {}", backup, e);
+                    None
+                }
+                Err(LoadError::Missing) => None,
+            }
+        })
+    }
+
+    /// Wrap the save with a CRC32 of its contents: `{"checksum": ..., "data": {...}}`.
+    /// The checksum covers `data` as compact JSON with sorted keys, so it doesn't
+    /// depend on the pretty-printing or on `HashMap` iteration order.
+    fn encode(save: &SaveData) -> Result<String, Box<dyn std::error::Error>> {
+        let data = serde_json::to_value(save)?;
+        let checksum = crc32fast::hash(serde_json::to_string(&data)?.as_bytes());
+        Ok(serde_json::to_string_pretty(&serde_json::json!({ "checksum": checksum, "data": data }))?)
+    }
+
+    /// Verify and unwrap a save written by `encode`. Saves from before checksums were
+    /// added are the bare save object and load unverified.
+    fn decode(contents: &str) -> Result<SaveData, Box<dyn std::error::Error>> {
+        let mut value: Value = serde_json::from_str(contents)?;
+        if value.get("checksum").is_none() || value.get("data").is_none() {
+            return SaveData::from_value(value);
         }
 
-        #[cfg(target_arch = "wasm32")]
-        {
-            // For WASM, we'll use localStorage through JS
-            PathBuf::from("save.json")
+        let expected = value["checksum"].as_u64().ok_or("save checksum is not a number")?;
+        let data = value["data"].take();
+        let actual = crc32fast::hash(serde_json::to_string(&data)?.as_bytes());
+        if u64::from(actual) != expected {
+            return Err(format!("checksum mismatch (expected {:08x}, found {:08x})", expected, actual).into());
         }
+        SaveData::from_value(data)
     }
 
-    /// Load save data from file
-    fn load_from_path(path: &PathBuf) -> Result<SaveData, Box<dyn std::error::Error>> {
-        let contents = fs::read_to_string(path)?;
-        let save_data = serde_json::from_str(&contents)?;
-        println!("Loaded save from: {}", path.display());
-        Ok(save_data)
+    /// Move an unreadable save aside as `save.json.corrupt-<unix time>`
+    fn quarantine(storage: &dyn Storage, key: &str) {
+        // miniquad's clock, since std's isn't available on the web
+        let quarantined = format!("{}.corrupt-{}", key, macroquad::miniquad::date::now() as u64);
+        match storage.rename(key, &quarantined) {
+            Ok(()) => eprintln!("Moved unreadable save to: {}", quarantined),
+            Err(e) => eprintln!("Failed to quarantine unreadable save: {}", e),
+        }
     }
 
-    /// Save data to file
+    /// Save data to storage. The write replaces the save in one step (a synced temp file
+    /// renamed over `save.json` on desktop), so a crash part way through leaves the old
+    /// save intact. The previous save is kept as `save.json.bak1`, pushing older backups down.
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let json = serde_json::to_string_pretty(&self.current_save)?;
-        fs::write(&self.save_path, json)?;
-        println!("Saved game to: {}", self.save_path.display());
+        let json = Self::encode(&self.current_save)?;
+        self.rotate_backups()?;
+        self.storage.write(&self.key, &json)?;
+        println!("Saved game: {}", self.key);
         Ok(())
     }
 
+    /// Shift `.bak1..` down one slot (dropping the oldest) and copy the current save
+    /// into `.bak1`. Copying rather than renaming keeps `save.json` in place throughout.
+    /// A save that went bad since it was opened isn't worth backing up; it's moved
+    /// aside like one found unreadable when opening, so saving can carry on.
+    fn rotate_backups(&self) -> std::io::Result<()> {
+        let current = match self.storage.read(&self.key) {
+            Ok(Some(current)) => current,
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                eprintln!("Save {} is unreadable: {}", self.key, e);
+                Self::quarantine(self.storage.as_ref(), &self.key);
+                return Ok(());
+            }
+        };
+        for n in (1..SAVE_BACKUPS).rev() {
+            self.storage.rename(&Self::backup_key(&self.key, n), &Self::backup_key(&self.key, n + 1))?;
+        }
+        self.storage.write(&Self::backup_key(&self.key, 1), &current)
+    }
+
+    /// Package this save for moving to another install
+    pub fn export(&self, profile_name: &str) -> ProfileExport {
+        ProfileExport::new(profile_name, &self.current_save)
+    }
+
+    /// Bring in an exported save (call `save` afterwards to keep it). The profile
+    /// keeps its own name either way.
+    pub fn import(&mut self, export: &ProfileExport, mode: ImportMode) {
+        match mode {
+            ImportMode::Merge => self.current_save.merge(&export.save),
+            ImportMode::Overwrite => {
+                let player_name = self.current_save.player_name.take();
+                self.current_save = SaveData {
+                    player_name,
+                    ..export.save.clone()
+                };
+            }
+        }
+    }
+
     /// Get reference to current save data
     pub fn data(&self) -> &SaveData {
         &self.current_save
@@ -202,6 +461,11 @@ impl SaveManager {
     }
 }
 
+enum LoadError {
+    Missing,
+    Unreadable(Box<dyn std::error::Error>),
+}
+
 impl Default for SaveManager {
     fn default() -> Self {
         Self::new()
@@ -263,16 +527,39 @@ mod tests {
         let mut save = SaveData::default();
 
         // First time is always new
-        assert!(save.update_best_time(Continent::Tutorial, 100.0));
-        assert_eq!(save.get_best_time(&Continent::Tutorial), Some(100.0));
+        assert!(save.update_best_time(Continent::Tutorial, GameMode::Standard, 100.0));
+        assert_eq!(save.get_best_time(&Continent::Tutorial, GameMode::Standard), Some(100.0));
 
         // Faster time updates
-        assert!(save.update_best_time(Continent::Tutorial, 80.0));
-        assert_eq!(save.get_best_time(&Continent::Tutorial), Some(80.0));
+        assert!(save.update_best_time(Continent::Tutorial, GameMode::Standard, 80.0));
+        assert_eq!(save.get_best_time(&Continent::Tutorial, GameMode::Standard), Some(80.0));
 
         // Slower time doesn't update
-        assert!(!save.update_best_time(Continent::Tutorial, 90.0));
-        assert_eq!(save.get_best_time(&Continent::Tutorial), Some(80.0));
+        assert!(!save.update_best_time(Continent::Tutorial, GameMode::Standard, 90.0));
+        assert_eq!(save.get_best_time(&Continent::Tutorial, GameMode::Standard), Some(80.0));
+
+        // Each mode keeps its own
+        assert_eq!(save.get_best_time(&Continent::Tutorial, GameMode::TimeAttack), None);
+        assert!(save.update_best_time(Continent::Tutorial, GameMode::TimeAttack, 95.0));
+        assert_eq!(save.get_best_time(&Continent::Tutorial, GameMode::TimeAttack), Some(95.0));
+        assert_eq!(save.get_best_time(&Continent::Tutorial, GameMode::Standard), Some(80.0));
+    }
+
+    #[test]
+    fn test_boss_rush_record() {
+        let mut save = SaveData::default();
+        assert!(!save.has_cleared(&Continent::Oceania));
+        save.update_best_time(Continent::Oceania, GameMode::TimeAttack, 250.0);
+        assert!(!save.has_cleared(&Continent::Oceania), "a time attack doesn't count");
+        save.update_best_time(Continent::Oceania, GameMode::Standard, 300.0);
+        assert!(save.has_cleared(&Continent::Oceania));
+
+        let record = |time, damage_taken| BossRushRecord { time, damage_taken };
+        assert!(save.update_boss_rush(record(400.0, 120.0)));
+        assert!(!save.update_boss_rush(record(410.0, 10.0)));
+        assert!(save.update_boss_rush(record(400.0, 80.0)), "as fast, with less damage");
+        assert!(save.update_boss_rush(record(390.0, 200.0)));
+        assert_eq!(save.boss_rush_best, Some(record(390.0, 200.0)));
     }
 
     #[test]
@@ -308,4 +595,269 @@ mod tests {
         assert_eq!(save.unlocked_continents.len(), loaded.unlocked_continents.len());
         assert_eq!(save.settings.difficulty, loaded.settings.difficulty);
     }
+
+    #[test]
+    fn test_settings_from_older_saves_load() {
+        let json = r#"{"sound_volume":80.0,"music_volume":60.0,"difficulty":0}"#;
+        let settings: Settings = serde_json::from_str(json).unwrap();
+        assert_eq!(settings.difficulty, 0);
+        assert!(!settings.adaptive_difficulty);
+        assert_eq!(settings.respawn_countdown, crate::checkpoint::DEFAULT_RESPAWN_COUNTDOWN);
+    }
+
+    use crate::storage::FileStorage;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::rc::Rc;
+
+    fn open(path: &Path) -> SaveManager {
+        SaveManager::open(Rc::new(FileStorage::new(path.parent().unwrap().to_path_buf())), "save.json")
+    }
+
+    fn backup(path: &Path, n: usize) -> PathBuf {
+        path.with_extension(format!("json.bak{}", n))
+    }
+
+    fn temp_save_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("glidewars_save_{}_{}", name, std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("save.json")
+    }
+
+    fn quarantined_files(path: &Path) -> usize {
+        fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().contains(".corrupt-"))
+            .count()
+    }
+
+    #[test]
+    fn test_unversioned_save_migrates() {
+        let json = r#"{
+            "player_name": null,
+            "unlocked_continents": ["Tutorial", "North America"],
+            "high_scores": {"Tutorial": 1200},
+            "best_times": {},
+            "settings": {"sound_volume": 80.0, "music_volume": 60.0, "difficulty": 2},
+            "total_play_time": 42.0,
+            "total_deaths": 3,
+            "total_boss_kills": 1
+        }"#;
+        let save = SaveManager::decode(json).unwrap();
+
+        assert_eq!(save.version, SAVE_FORMAT_VERSION);
+        assert!(save.is_continent_unlocked(&Continent::NorthAmerica));
+        assert_eq!(save.get_high_score(&Continent::Tutorial), 1200);
+        assert_eq!(save.settings.difficulty, 2);
+        assert_eq!(save.total_deaths, 3);
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let save = SaveManager::decode(r#"{"version": 2, "total_deaths": 5}"#).unwrap();
+        assert_eq!(save.total_deaths, 5);
+        assert!(save.is_continent_unlocked(&Continent::Tutorial));
+        assert_eq!(save.settings.difficulty, 1);
+    }
+
+    #[test]
+    fn test_newer_save_version_is_rejected() {
+        let json = format!(r#"{{"version": {}}}"#, SAVE_FORMAT_VERSION + 1);
+        assert!(SaveManager::decode(&json).is_err());
+    }
+
+    #[test]
+    fn test_unreadable_save_is_quarantined() {
+        let path = temp_save_path("quarantine");
+        fs::write(&path, r#"{"unlocked_continents": 7}"#).unwrap();
+
+        let manager = open(&path);
+        assert_eq!(manager.data().unlocked_continents.len(), 1);
+        assert!(!path.exists());
+        assert_eq!(quarantined_files(&path), 1);
+
+        // Saving afterwards writes a fresh file without touching the quarantined one
+        manager.save().unwrap();
+        assert!(path.exists());
+        assert_eq!(quarantined_files(&path), 1);
+    }
+
+    #[test]
+    fn test_save_that_is_not_utf8_is_quarantined() {
+        let path = temp_save_path("not_utf8");
+        saved_with_deaths(&path, 1);
+        saved_with_deaths(&path, 2);
+        fs::write(&path, [0xff, 0xfe, b'{', 0x80]).unwrap();
+
+        let manager = open(&path);
+        assert_eq!(manager.data().total_deaths, 1);
+        assert!(!path.exists());
+        assert_eq!(quarantined_files(&path), 1);
+    }
+
+    #[test]
+    fn test_saving_over_a_save_that_went_bad_still_works() {
+        let path = temp_save_path("went_bad");
+        saved_with_deaths(&path, 1);
+        let mut manager = open(&path);
+        fs::write(&path, [0xff, 0xfe, 0x80]).unwrap();
+
+        manager.data_mut().total_deaths = 2;
+        manager.save().unwrap();
+        manager.data_mut().total_deaths = 3;
+        manager.save().unwrap();
+        assert_eq!(open(&path).data().total_deaths, 3);
+        assert_eq!(quarantined_files(&path), 1);
+    }
+
+    fn saved_with_deaths(path: &Path, deaths: u32) {
+        let mut manager = open(path);
+        manager.data_mut().total_deaths = deaths;
+        manager.save().unwrap();
+    }
+
+    #[test]
+    fn test_save_round_trips_with_checksum() {
+        let path = temp_save_path("round_trip");
+        saved_with_deaths(&path, 7);
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("\"checksum\""));
+        assert_eq!(open(&path).data().total_deaths, 7);
+        assert!(!path.with_extension("json.tmp").exists());
+    }
+
+    #[test]
+    fn test_backups_rotate() {
+        let path = temp_save_path("rotate");
+        for deaths in 1..=SAVE_BACKUPS as u32 + 2 {
+            saved_with_deaths(&path, deaths);
+        }
+
+        // Newest save in place, the ones before it in .bak1, .bak2, ...
+        let newest = SAVE_BACKUPS as u32 + 2;
+        assert_eq!(open(&path).data().total_deaths, newest);
+        for n in 1..=SAVE_BACKUPS {
+            let backup = SaveManager::decode(&fs::read_to_string(backup(&path, n)).unwrap()).unwrap();
+            assert_eq!(backup.total_deaths, newest - n as u32);
+        }
+        assert!(!backup(&path, SAVE_BACKUPS + 1).exists());
+    }
+
+    #[test]
+    fn test_truncated_save_recovers_from_backup() {
+        let path = temp_save_path("truncated");
+        saved_with_deaths(&path, 1);
+        saved_with_deaths(&path, 2);
+
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::write(&path, &contents[..contents.len() / 2]).unwrap();
+
+        let manager = open(&path);
+        assert_eq!(manager.data().total_deaths, 1);
+        assert_eq!(quarantined_files(&path), 1);
+    }
+
+    #[test]
+    fn test_corrupted_save_fails_checksum() {
+        let path = temp_save_path("corrupted");
+        saved_with_deaths(&path, 1);
+        saved_with_deaths(&path, 20);
+
+        // Still valid JSON, but no longer what was written
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::write(&path, contents.replace("\"total_deaths\": 20", "\"total_deaths\": 99")).unwrap();
+        assert!(SaveManager::decode(&fs::read_to_string(&path).unwrap()).is_err());
+
+        assert_eq!(open(&path).data().total_deaths, 1);
+    }
+
+    #[test]
+    fn test_skips_corrupted_backups() {
+        let path = temp_save_path("bad_backup");
+        saved_with_deaths(&path, 1);
+        saved_with_deaths(&path, 2);
+        saved_with_deaths(&path, 3);
+
+        fs::write(&path, "").unwrap();
+        fs::write(backup(&path, 1), "{\"checksum\": 1, \"data\": {}}").unwrap();
+
+        assert_eq!(open(&path).data().total_deaths, 1);
+    }
+
+    #[test]
+    fn test_missing_save_recovers_from_backup() {
+        // A crash can't leave save.json missing, but a user deleting it by hand can
+        let path = temp_save_path("deleted");
+        saved_with_deaths(&path, 1);
+        saved_with_deaths(&path, 2);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(open(&path).data().total_deaths, 1);
+        assert_eq!(quarantined_files(&path), 0);
+    }
+
+    #[test]
+    fn test_missing_save_starts_fresh() {
+        let path = temp_save_path("missing");
+        let manager = open(&path);
+        assert_eq!(manager.data().version, SAVE_FORMAT_VERSION);
+        assert_eq!(quarantined_files(&path), 0);
+    }
+
+    #[test]
+    fn test_merge_keeps_best_of_both() {
+        let mut save = SaveData::default();
+        save.update_high_score(Continent::Tutorial, 3000);
+        save.update_best_time(Continent::Tutorial, GameMode::Standard, 90.0);
+        save.total_deaths = 10;
+        save.settings.difficulty = 0;
+
+        let mut other = SaveData::default();
+        other.unlock_continent(Continent::NorthAmerica);
+        other.update_high_score(Continent::Tutorial, 2000);
+        other.update_high_score(Continent::NorthAmerica, 4000);
+        other.update_best_time(Continent::Tutorial, GameMode::Standard, 80.0);
+        other.total_deaths = 4;
+        other.settings.difficulty = 2;
+
+        save.merge(&other);
+        assert!(save.is_continent_unlocked(&Continent::NorthAmerica));
+        assert_eq!(save.unlocked_continents.len(), 2);
+        assert_eq!(save.get_high_score(&Continent::Tutorial), 3000);
+        assert_eq!(save.get_high_score(&Continent::NorthAmerica), 4000);
+        assert_eq!(save.get_best_time(&Continent::Tutorial, GameMode::Standard), Some(80.0));
+        assert_eq!(save.total_deaths, 10);
+        assert_eq!(save.settings.difficulty, 0);
+    }
+
+    #[test]
+    fn test_import_overwrite_keeps_profile_name() {
+        let path = temp_save_path("import");
+        let mut manager = open(&path);
+        manager.data_mut().player_name = Some("ANA".to_string());
+        manager.data_mut().total_deaths = 50;
+
+        let other = SaveData {
+            player_name: Some("BEA".to_string()),
+            total_deaths: 2,
+            ..Default::default()
+        };
+        manager.import(&ProfileExport::new("BEA", &other), ImportMode::Overwrite);
+
+        assert_eq!(manager.data().total_deaths, 2);
+        assert_eq!(manager.data().player_name.as_deref(), Some("ANA"));
+    }
+
+    #[test]
+    fn test_stats_follow_events() {
+        let mut save = SaveData::default();
+        save.on_event(&GameEvent::PlayerDied { position: macroquad::prelude::Vec3::ZERO, cause: crate::events::DamageSource::BossContact });
+        save.on_event(&GameEvent::BossDefeated { boss_type: crate::boss::BossType::TutorialBoss });
+        save.on_event(&GameEvent::RingPassed { position: macroquad::prelude::Vec3::ZERO, chain: 1, precision: 0.0 });
+
+        assert_eq!(save.total_deaths, 1);
+        assert_eq!(save.total_boss_kills, 1);
+    }
 }
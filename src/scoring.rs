@@ -0,0 +1,351 @@
+use serde::{Deserialize, Serialize};
+use crate::events::{EventListener, GameEvent, HitSource};
+use crate::rings::CENTER_PASS_FRACTION;
+
+/// Seconds a combo survives without another kill, ring or near miss before it starts decaying
+pub const COMBO_WINDOW: f32 = 4.0;
+/// Once decaying, the chain loses one link every this many seconds
+const COMBO_DECAY_INTERVAL: f32 = 0.5;
+/// Chain links needed for each +0.5x step
+const COMBO_STEP: u32 = 3;
+const MAX_MULTIPLIER: f32 = 4.0;
+/// Each gate in an unbroken ring chain is worth this much more than the last
+const RING_CHAIN_STEP: u32 = 50;
+const MAX_RING_CHAIN_STEPS: u32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreCategory {
+    Combat,
+    Powerups,
+    Rings,
+    NearMiss,
+    Bosses,
+    LevelBonus,
+}
+
+impl ScoreCategory {
+    pub fn all() -> [ScoreCategory; 6] {
+        [
+            ScoreCategory::Combat,
+            ScoreCategory::Powerups,
+            ScoreCategory::Rings,
+            ScoreCategory::NearMiss,
+            ScoreCategory::Bosses,
+            ScoreCategory::LevelBonus,
+        ]
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            ScoreCategory::Combat => "Combat",
+            ScoreCategory::Powerups => "Powerups",
+            ScoreCategory::Rings => "Rings",
+            ScoreCategory::NearMiss => "Near Misses",
+            ScoreCategory::Bosses => "Bosses",
+            ScoreCategory::LevelBonus => "Level Bonus",
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Kinds of event that are worth points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScoreKind {
+    EnemyKill,
+    DroneHit,
+    Powerup,
+    Ring,
+    RingCenter,
+    PerfectCourse,
+    NearMiss,
+    BossHit,
+    BossDefeat,
+    LevelSurvival,
+}
+
+/// One row of the scoring table
+#[derive(Debug, Clone, Copy)]
+pub struct ScoreRule {
+    pub kind: ScoreKind,
+    pub category: ScoreCategory,
+    pub points: u32,      // Base points (powerups use their own value instead)
+    pub builds_combo: bool,
+    pub multiplied: bool, // Whether the combo multiplier applies
+}
+
+pub const SCORE_RULES: [ScoreRule; 10] = [
+    ScoreRule { kind: ScoreKind::EnemyKill, category: ScoreCategory::Combat, points: 100, builds_combo: true, multiplied: true },
+    ScoreRule { kind: ScoreKind::DroneHit, category: ScoreCategory::Combat, points: 25, builds_combo: false, multiplied: false },
+    ScoreRule { kind: ScoreKind::Powerup, category: ScoreCategory::Powerups, points: 0, builds_combo: false, multiplied: false },
+    // Rings escalate with the gate chain on top of the combo multiplier
+    ScoreRule { kind: ScoreKind::Ring, category: ScoreCategory::Rings, points: 100, builds_combo: true, multiplied: true },
+    ScoreRule { kind: ScoreKind::RingCenter, category: ScoreCategory::Rings, points: 100, builds_combo: false, multiplied: true },
+    ScoreRule { kind: ScoreKind::PerfectCourse, category: ScoreCategory::Rings, points: 1000, builds_combo: false, multiplied: false },
+    ScoreRule { kind: ScoreKind::NearMiss, category: ScoreCategory::NearMiss, points: 150, builds_combo: true, multiplied: true },
+    ScoreRule { kind: ScoreKind::BossHit, category: ScoreCategory::Bosses, points: 50, builds_combo: false, multiplied: true },
+    ScoreRule { kind: ScoreKind::BossDefeat, category: ScoreCategory::Bosses, points: 5000, builds_combo: false, multiplied: false },
+    // Surviving until the timer runs out without beating the boss
+    ScoreRule { kind: ScoreKind::LevelSurvival, category: ScoreCategory::LevelBonus, points: 5000, builds_combo: false, multiplied: false },
+];
+
+pub fn rule_for(kind: ScoreKind) -> &'static ScoreRule {
+    SCORE_RULES
+        .iter()
+        .find(|rule| rule.kind == kind)
+        .expect("every ScoreKind has a rule")
+}
+
+/// Running score for a level: total, per-category breakdown and combo state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreKeeper {
+    total: u32,
+    breakdown: [u32; 6],
+    combo: u32,
+    combo_timer: f32,
+    best_combo: u32,
+    penalty: u32, // Points lost to continues
+}
+
+impl ScoreKeeper {
+    pub fn new() -> Self {
+        Self {
+            total: 0,
+            breakdown: [0; 6],
+            combo: 0,
+            combo_timer: 0.0,
+            best_combo: 0,
+            penalty: 0,
+        }
+    }
+
+    /// Tick the combo timer; an expired combo sheds links one at a time
+    pub fn update(&mut self, dt: f32) {
+        if self.combo == 0 {
+            return;
+        }
+
+        self.combo_timer -= dt;
+        while self.combo_timer <= 0.0 && self.combo > 0 {
+            self.combo -= 1;
+            self.combo_timer += COMBO_DECAY_INTERVAL;
+        }
+    }
+
+    /// Award points for a scoring event, returning what was actually added
+    pub fn award(&mut self, kind: ScoreKind, base_override: Option<u32>) -> u32 {
+        let rule = rule_for(kind);
+
+        if rule.builds_combo {
+            self.combo += 1;
+            self.combo_timer = COMBO_WINDOW;
+            self.best_combo = self.best_combo.max(self.combo);
+        }
+
+        let base = base_override.unwrap_or(rule.points);
+        let points = if rule.multiplied {
+            (base as f32 * self.multiplier()).round() as u32
+        } else {
+            base
+        };
+
+        self.total += points;
+        self.breakdown[rule.category.index()] += points;
+        points
+    }
+
+    /// Taking damage breaks the chain
+    pub fn break_combo(&mut self) {
+        self.combo = 0;
+        self.combo_timer = 0.0;
+    }
+
+    /// Deduct a fraction of the total (continuing after game over), returning the points lost
+    pub fn apply_penalty(&mut self, fraction: f32) -> u32 {
+        let lost = (self.total as f32 * fraction).round() as u32;
+        self.total -= lost;
+        self.penalty += lost;
+        lost
+    }
+
+    pub fn penalty(&self) -> u32 {
+        self.penalty
+    }
+
+    pub fn total(&self) -> u32 {
+        self.total
+    }
+
+    pub fn category_total(&self, category: ScoreCategory) -> u32 {
+        self.breakdown[category.index()]
+    }
+
+    pub fn combo(&self) -> u32 {
+        self.combo
+    }
+
+    pub fn best_combo(&self) -> u32 {
+        self.best_combo
+    }
+
+    pub fn multiplier(&self) -> f32 {
+        (1.0 + (self.combo / COMBO_STEP) as f32 * 0.5).min(MAX_MULTIPLIER)
+    }
+}
+
+impl EventListener for ScoreKeeper {
+    fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::EnemyKilled { .. } => {
+                self.award(ScoreKind::EnemyKill, None);
+            }
+            GameEvent::EnemyHit { source: HitSource::Drone, .. } => {
+                self.award(ScoreKind::DroneHit, None);
+            }
+            GameEvent::PowerupCollected { powerup_type, .. } => {
+                self.award(ScoreKind::Powerup, Some(powerup_type.score_value()));
+            }
+            GameEvent::RingPassed { chain, precision, .. } => {
+                let steps = chain.saturating_sub(1).min(MAX_RING_CHAIN_STEPS);
+                self.award(ScoreKind::Ring, Some(rule_for(ScoreKind::Ring).points + steps * RING_CHAIN_STEP));
+                if *precision >= 1.0 - CENTER_PASS_FRACTION {
+                    self.award(ScoreKind::RingCenter, None);
+                }
+            }
+            GameEvent::RingCourseCompleted { perfect: true, .. } => {
+                self.award(ScoreKind::PerfectCourse, None);
+            }
+            GameEvent::NearMiss { .. } => {
+                self.award(ScoreKind::NearMiss, None);
+            }
+            GameEvent::BossHit { .. } => {
+                self.award(ScoreKind::BossHit, None);
+            }
+            GameEvent::BossDefeated { .. } => {
+                self.award(ScoreKind::BossDefeat, None);
+            }
+            GameEvent::LevelCompleted { outlasted_boss: true } => {
+                self.award(ScoreKind::LevelSurvival, None);
+            }
+            GameEvent::PlayerDamaged { .. } => self.break_combo(),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::DamageSource;
+    use crate::powerup::PowerupType;
+    use macroquad::prelude::Vec3;
+
+    #[test]
+    fn test_every_kind_has_one_rule() {
+        for rule in SCORE_RULES.iter() {
+            let matches = SCORE_RULES.iter().filter(|other| other.kind == rule.kind).count();
+            assert_eq!(matches, 1);
+        }
+    }
+
+    #[test]
+    fn test_combo_builds_multiplier() {
+        let mut score = ScoreKeeper::new();
+        for _ in 0..3 {
+            score.on_event(&GameEvent::RingPassed { position: Vec3::ZERO, chain: 1, precision: 0.0 });
+        }
+
+        // Third ring already scores at 1.5x
+        assert_eq!(score.total(), 100 + 100 + 150);
+        assert_eq!(score.combo(), 3);
+        assert_eq!(score.multiplier(), 1.5);
+    }
+
+    #[test]
+    fn test_damage_breaks_combo() {
+        let mut score = ScoreKeeper::new();
+        for _ in 0..4 {
+            score.on_event(&GameEvent::RingPassed { position: Vec3::ZERO, chain: 1, precision: 0.0 });
+        }
+        score.on_event(&GameEvent::PlayerDamaged { amount: 10.0, source: DamageSource::BossProjectile });
+
+        assert_eq!(score.combo(), 0);
+        assert_eq!(score.multiplier(), 1.0);
+        assert_eq!(score.best_combo(), 4);
+    }
+
+    #[test]
+    fn test_combo_decays_after_window() {
+        let mut score = ScoreKeeper::new();
+        for _ in 0..4 {
+            score.on_event(&GameEvent::RingPassed { position: Vec3::ZERO, chain: 1, precision: 0.0 });
+        }
+
+        score.update(COMBO_WINDOW - 0.1);
+        assert_eq!(score.combo(), 4);
+
+        // Past the window the chain sheds a link per decay interval
+        score.update(0.1 + COMBO_DECAY_INTERVAL);
+        assert_eq!(score.combo(), 2);
+
+        score.update(10.0);
+        assert_eq!(score.combo(), 0);
+    }
+
+    #[test]
+    fn test_breakdown_by_category() {
+        let mut score = ScoreKeeper::new();
+        score.on_event(&GameEvent::PowerupCollected {
+            powerup_type: PowerupType::WeaponMissile,
+            position: Vec3::ZERO,
+        });
+        score.on_event(&GameEvent::NearMiss { position: Vec3::ZERO });
+        score.on_event(&GameEvent::LevelCompleted { outlasted_boss: true });
+
+        assert_eq!(score.category_total(ScoreCategory::Powerups), 250);
+        assert_eq!(score.category_total(ScoreCategory::NearMiss), 150);
+        assert_eq!(score.category_total(ScoreCategory::LevelBonus), 5000);
+        let sum: u32 = ScoreCategory::all().iter().map(|c| score.category_total(*c)).sum();
+        assert_eq!(sum, score.total());
+    }
+
+    #[test]
+    fn test_survival_bonus_only_when_boss_outlasted() {
+        let mut score = ScoreKeeper::new();
+        score.on_event(&GameEvent::LevelCompleted { outlasted_boss: false });
+        assert_eq!(score.total(), 0);
+
+        score.on_event(&GameEvent::LevelCompleted { outlasted_boss: true });
+        assert_eq!(score.category_total(ScoreCategory::LevelBonus), 5000);
+    }
+
+    #[test]
+    fn test_continue_penalty() {
+        let mut score = ScoreKeeper::new();
+        score.on_event(&GameEvent::LevelCompleted { outlasted_boss: true });
+
+        assert_eq!(score.apply_penalty(0.5), 2500);
+        assert_eq!(score.total(), 2500);
+        assert_eq!(score.penalty(), 2500);
+        // Breakdown keeps what was earned; the penalty is shown separately
+        assert_eq!(score.category_total(ScoreCategory::LevelBonus), 5000);
+    }
+
+    #[test]
+    fn test_ring_chain_and_center_bonus() {
+        let mut score = ScoreKeeper::new();
+        score.on_event(&GameEvent::RingPassed { position: Vec3::ZERO, chain: 1, precision: 0.0 });
+        assert_eq!(score.total(), 100);
+
+        // Fourth gate in the chain, dead center, on a 1.0x combo
+        let mut score = ScoreKeeper::new();
+        score.on_event(&GameEvent::RingPassed { position: Vec3::ZERO, chain: 4, precision: 0.9 });
+        assert_eq!(score.total(), 250 + 100);
+
+        score.on_event(&GameEvent::RingCourseCompleted { gates: 5, perfect: true });
+        score.on_event(&GameEvent::RingCourseCompleted { gates: 5, perfect: false });
+        assert_eq!(score.category_total(ScoreCategory::Rings), 350 + 1000);
+    }
+}
@@ -0,0 +1,938 @@
+use macroquad::prelude::*;
+use crate::assets::Continent;
+use crate::boss::{Boss, BossType};
+use crate::checkpoint::{CheckpointManager, WorldSnapshot};
+use crate::difficulty::DifficultySetting;
+use crate::director::DifficultyDirector;
+use crate::drone_companion::DroneCompanion;
+use crate::enemy::EnemyManager;
+use crate::game_mode::GameMode;
+use crate::events::{DamageSource, EventBus, EventListener, GameEvent, HitSource};
+use crate::level::{LevelManager, BOSS_RUSH_INTERLUDE};
+use crate::lives::{LivesConfig, LivesManager};
+use crate::player::{Player, PlayerControls};
+use crate::powerup::PowerupManager;
+use crate::replay::{Replay, ReplayAction};
+use crate::rings::RingManager;
+use crate::rng;
+use crate::scoring::ScoreKeeper;
+use crate::suspend::{Awaiting, SuspendedRun};
+use crate::terrain::TerrainManager;
+use std::time::Instant;
+
+/// Length of one simulation step. Gameplay always advances in steps of this size,
+/// however fast frames are drawn, so a replay plays out the same everywhere.
+pub const TICK: f32 = 1.0 / 60.0;
+
+/// Radius cleared of obstacles and enemies around a respawn
+const RESPAWN_CLEAR_RADIUS: f32 = 50.0;
+/// Seconds spawning stays paused after a respawn
+const RESPAWN_SPAWN_PAUSE: f32 = 3.0;
+/// Share of a full boss's health an endless mini-boss has
+const MINI_BOSS_HEALTH: f32 = 0.4;
+
+/// Where a run is up to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Flying,
+    BossFight,
+    Respawning, // Died with lives left, waiting for `respawn`
+    GameOver,   // Out of lives, waiting for `use_continue` (or a new run)
+    Complete,
+}
+
+/// One run of a level: the player, the world and everything that decides the
+/// score, without any drawing or input handling. The game steps it every tick
+/// with the keyboard's controls; the leaderboard server steps it with a replay's.
+pub struct Simulation {
+    pub continent: Continent,
+    pub mode: GameMode,
+    pub seed: u64,
+    pub player: Player,
+    pub level: LevelManager,
+    pub terrain: TerrainManager,
+    pub enemies: EnemyManager,
+    pub powerups: PowerupManager,
+    pub rings: RingManager,
+    pub drone: DroneCompanion,
+    pub boss: Option<Boss>,
+    pub score: ScoreKeeper,
+    pub lives: LivesManager,
+    pub checkpoints: CheckpointManager,
+    pub director: DifficultyDirector,
+    events: EventBus, // Emitted during the current step
+    phase: Phase,
+    ticks: u32, // Steps taken since the run started, counting across respawns
+    damage_taken: f32, // Health lost since the run started, counting across respawns
+    replay: Option<Replay>, // Everything the player has done so far, while it's being recorded
+}
+
+impl Simulation {
+    /// Start a level, seeding gameplay randomness with `seed`
+    pub fn new(continent: Continent, mode: GameMode, setting: DifficultySetting, adaptive: bool, seed: u64) -> Self {
+        rng::srand(seed);
+        let mut director = DifficultyDirector::new();
+        director.set_enabled(adaptive);
+        let mut level = LevelManager::new(continent, setting);
+        level.set_mode(mode);
+        let mut replay = Replay::new(continent, setting, adaptive, seed);
+        replay.mode = mode;
+
+        let mut sim = Self {
+            continent,
+            mode,
+            seed,
+            player: Player::new(),
+            level,
+            terrain: TerrainManager::new(),
+            enemies: EnemyManager::new(),
+            powerups: PowerupManager::new(),
+            rings: RingManager::new(),
+            drone: DroneCompanion::new(),
+            boss: None,
+            score: ScoreKeeper::new(),
+            lives: LivesManager::new(LivesConfig::for_setting(setting)),
+            checkpoints: CheckpointManager::new(),
+            director,
+            events: EventBus::new(),
+            phase: Phase::Flying,
+            ticks: 0,
+            damage_taken: 0.0,
+            replay: Some(replay),
+        };
+
+        // The boss rush's interludes are a breather, clear of anything new
+        if mode == GameMode::BossRush {
+            sim.terrain.pause_spawning(BOSS_RUSH_INTERLUDE);
+            sim.enemies.pause_spawning(BOSS_RUSH_INTERLUDE);
+        }
+        sim
+    }
+
+    /// Record the run as flown by the profile named `player`, the name its replay
+    /// is accepted under on the online boards
+    pub fn set_player(&mut self, player: &str) {
+        if let Some(replay) = &mut self.replay {
+            replay.player = player.to_string();
+        }
+    }
+
+    /// Pick up a run left by `suspend`. It keeps the director setting it started
+    /// with when it has a replay, so the replay still describes it.
+    pub fn resume(run: SuspendedRun, adaptive: bool) -> Self {
+        let replay = run.replay.as_deref().and_then(|text| Replay::from_text(text).ok());
+        let adaptive = replay.as_ref().map_or(adaptive, |replay| replay.adaptive);
+
+        let mut sim = Self::new(run.continent, run.mode, run.difficulty, adaptive, run.seed);
+        sim.boss = run.world.restore(&mut sim.level, &mut sim.terrain, &mut sim.enemies, &mut sim.powerups, &mut sim.rings, &mut sim.drone);
+        run.world.player.apply_to(&mut sim.player);
+        sim.score = run.world.score.clone();
+        sim.director.restore(&run.world.director);
+        sim.lives = run.lives.clone();
+        sim.checkpoints.restore_checkpoints(run.checkpoints.clone(), run.checkpoints_reached);
+        sim.phase = match run.awaiting {
+            Awaiting::Respawn => {
+                sim.checkpoints.start_respawn();
+                Phase::Respawning
+            }
+            Awaiting::Continue => Phase::GameOver,
+            Awaiting::Nothing if run.in_boss_fight() => Phase::BossFight,
+            Awaiting::Nothing => Phase::Flying,
+        };
+        rng::set_state(run.rng_state);
+        sim.ticks = replay.as_ref().map_or(0, Replay::ticks);
+        sim.damage_taken = run.damage_taken;
+
+        // Runs suspended before replays were recorded carry on without one
+        sim.replay = replay.map(|mut replay| {
+            replay.record(ReplayAction::Resume);
+            replay
+        });
+        sim
+    }
+
+    /// The run as it stands, to be resumed later
+    pub fn suspend(&self) -> SuspendedRun {
+        let mut run = SuspendedRun::new(
+            self.continent,
+            self.level.setting(),
+            self.seed,
+            self.snapshot(),
+            self.checkpoints.checkpoints().to_vec(),
+            self.lives.clone(),
+        );
+        run.mode = self.mode;
+        run.checkpoints_reached = self.checkpoints.checkpoint_count();
+        run.damage_taken = self.damage_taken;
+        run.awaiting = match self.phase {
+            Phase::Respawning => Awaiting::Respawn,
+            Phase::GameOver => Awaiting::Continue,
+            Phase::Flying | Phase::BossFight | Phase::Complete => Awaiting::Nothing,
+        };
+        run.rng_state = rng::state();
+        run.replay = self.replay.as_ref().map(Replay::to_text);
+        run
+    }
+
+    /// Simulate a recorded run from its seed, returning it as it ended. Fails if
+    /// the replay doesn't fit the run, e.g. input after the level was over.
+    pub fn play(replay: &Replay) -> Result<Self, String> {
+        Self::play_observed(replay, |_| {})
+    }
+
+    /// `play`, calling `observe` after every tick
+    pub fn play_observed(replay: &Replay, mut observe: impl FnMut(&Simulation)) -> Result<Self, String> {
+        Self::play_checked(replay, |sim| {
+            observe(sim);
+            Ok(())
+        })
+    }
+
+    /// `play`, giving up once `deadline` passes. Not for the web build, which
+    /// has no clock to read.
+    pub fn play_until(replay: &Replay, deadline: Instant) -> Result<Self, String> {
+        Self::play_checked(replay, |_| {
+            if Instant::now() < deadline {
+                Ok(())
+            } else {
+                Err("replay took too long to play".to_string())
+            }
+        })
+    }
+
+    /// `play`, calling `check` after every tick and stopping at its first error
+    fn play_checked(replay: &Replay, mut check: impl FnMut(&Simulation) -> Result<(), String>) -> Result<Self, String> {
+        let mut sim = Self::new(replay.continent, replay.mode, replay.difficulty, replay.adaptive, replay.seed);
+        sim.replay = None;
+        let mut events = EventBus::new();
+
+        for action in replay.actions() {
+            match *action {
+                ReplayAction::Controls { controls, ticks } => {
+                    for _ in 0..ticks {
+                        if !sim.is_running() {
+                            return Err(format!("input continues after the run stopped ({:?})", sim.phase));
+                        }
+                        sim.step(controls, &mut events);
+                        events.clear();
+                        check(&sim)?;
+                    }
+                }
+                ReplayAction::Continue => {
+                    if !sim.use_continue() {
+                        return Err("continue without a game over".to_string());
+                    }
+                }
+                ReplayAction::Respawn { checkpoint } => {
+                    let chosen = checkpoint == sim.checkpoints.active_index() || sim.checkpoints.select(checkpoint);
+                    if sim.phase != Phase::Respawning || !chosen {
+                        return Err(format!("respawn at checkpoint {} isn't possible", checkpoint + 1));
+                    }
+                    sim.respawn();
+                }
+                ReplayAction::Resume => {
+                    if !sim.can_suspend() {
+                        return Err("resume while the run is over".to_string());
+                    }
+                    // Through JSON, exactly as a suspended run goes to disk and back
+                    let json = serde_json::to_string(&sim.suspend()).map_err(|e| e.to_string())?;
+                    let run = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+                    let ticks = sim.ticks;
+                    sim = Self::resume(run, replay.adaptive);
+                    sim.ticks = ticks;
+                }
+            }
+        }
+        Ok(sim)
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    /// Seconds flown since the run started. Unlike the level timer it isn't wound
+    /// back by respawns, so it's the time a time attack is judged on.
+    pub fn run_time(&self) -> f32 {
+        self.ticks as f32 * TICK
+    }
+
+    /// Health the player has lost over the whole run, which a boss rush is
+    /// recorded with alongside its time
+    pub fn damage_taken(&self) -> f32 {
+        self.damage_taken
+    }
+
+    /// Whether `step` moves the run on (it doesn't while the player is down or after the end)
+    pub fn is_running(&self) -> bool {
+        matches!(self.phase, Phase::Flying | Phase::BossFight)
+    }
+
+    /// Whether the run can be left and picked up later: anything but over.
+    /// Waiting to respawn or to continue is picked up on that screen.
+    pub fn can_suspend(&self) -> bool {
+        match self.phase {
+            Phase::Flying | Phase::BossFight | Phase::Respawning => true,
+            Phase::GameOver => self.can_continue(),
+            Phase::Complete => false,
+        }
+    }
+
+    /// The run's replay so far, unless it isn't being recorded
+    pub fn replay(&self) -> Option<&Replay> {
+        self.replay.as_ref()
+    }
+
+    pub fn snapshot(&self) -> WorldSnapshot {
+        WorldSnapshot::capture(
+            &self.player,
+            &self.score,
+            &self.level,
+            &self.terrain,
+            &self.enemies,
+            &self.powerups,
+            &self.rings,
+            self.boss.as_ref(),
+            &self.drone,
+            &self.director,
+        )
+    }
+
+    /// Advance the run by one `TICK` with the player holding `controls`. The tick's
+    /// events go to the run's own listeners (score, drone, player, director) and
+    /// are then passed on through `events` for everything outside it.
+    pub fn step(&mut self, controls: PlayerControls, events: &mut EventBus) {
+        match self.phase {
+            Phase::Flying => self.fly(controls),
+            Phase::BossFight => self.fight_boss(controls),
+            Phase::Respawning | Phase::GameOver | Phase::Complete => return,
+        }
+        self.ticks += 1;
+        if let Some(replay) = &mut self.replay {
+            replay.record_tick(controls);
+        }
+
+        for event in self.events.drain() {
+            if let GameEvent::PlayerDamaged { amount, .. } = event {
+                self.damage_taken += amount;
+            }
+            let listeners: [&mut dyn EventListener; 4] = [&mut self.score, &mut self.drone, &mut self.player, &mut self.director];
+            for listener in listeners {
+                listener.on_event(&event);
+            }
+            events.emit(event);
+        }
+    }
+
+    fn fly(&mut self, controls: PlayerControls) {
+        let dt = TICK;
+        self.level.update(dt, self.player.position().z);
+
+        // Everything reads its tuning from the level's current difficulty,
+        // nudged by the adaptive director when it's enabled
+        self.director.update(dt, self.player.health());
+        let difficulty = self.level.difficulty().with_director(self.director.tuning());
+        self.terrain.set_difficulty(difficulty);
+        self.enemies.set_difficulty(difficulty);
+        self.powerups.set_generosity(self.director.tuning().powerup_generosity);
+        self.player.set_damage_taken(difficulty.damage_taken);
+
+        // Marked before the snapshot, so respawning here doesn't take it again
+        if self.level.should_create_checkpoint() {
+            self.level.mark_checkpoint_created();
+            let world = self.snapshot();
+            self.checkpoints.create_checkpoint(self.player.position().z, world, self.level.elapsed_time(), &mut self.events);
+        }
+
+        if self.level.should_spawn_boss() && self.boss.is_none() {
+            let boss_type = BossType::from_continent(self.continent);
+            let spawn_pos = self.player.position() + vec3(0.0, 5.0, 30.0);
+            let mut boss = Boss::new(boss_type, spawn_pos);
+            boss.scale_health(difficulty.boss_health());
+            self.boss = Some(boss);
+            log::debug!("=== BOSS SPAWNED: {} at {:.1}s ===", boss_type.name(), self.level.elapsed_time());
+            self.phase = Phase::BossFight;
+        }
+
+        // Endless runs meet a smaller version of any of the bosses every so often
+        if self.level.should_spawn_mini_boss() {
+            let boss_types = BossType::all();
+            let boss_type = boss_types[rng::gen_range(0, boss_types.len())];
+            let spawn_pos = self.player.position() + vec3(0.0, 5.0, 30.0);
+            let mut boss = Boss::new(boss_type, spawn_pos);
+            boss.scale_health(difficulty.boss_health() * MINI_BOSS_HEALTH);
+            self.boss = Some(boss);
+            self.level.mark_mini_boss_spawned();
+            log::debug!("=== MINI-BOSS SPAWNED: {} at {:.0}m ===", boss_type.name(), self.level.distance());
+            self.phase = Phase::BossFight;
+        }
+
+        // A boss rush's next fight, from a checkpoint so a lost life goes back to
+        // the start of it (not taken again when that's where the player already is)
+        if self.level.should_spawn_rush_boss() {
+            let z = self.player.position().z;
+            if self.checkpoints.get_last_checkpoint_position() != Some(z) {
+                let world = self.snapshot();
+                self.checkpoints.create_checkpoint(z, world, self.level.elapsed_time(), &mut self.events);
+            }
+            let boss_type = BossType::from_continent(self.level.continent());
+            let spawn_pos = self.player.position() + vec3(0.0, 5.0, 30.0);
+            let mut boss = Boss::new(boss_type, spawn_pos);
+            boss.scale_health(difficulty.boss_health());
+            self.boss = Some(boss);
+            log::debug!("=== BOSS RUSH FIGHT {}: {} ===", self.level.rush_fights_won() + 1, boss_type.name());
+            self.phase = Phase::BossFight;
+        }
+
+        // The run ends here; nothing later in the tick can undo the finish
+        if self.level.is_complete() && self.boss.iter().all(|b| b.is_defeated()) {
+            self.events.emit(GameEvent::LevelCompleted { outlasted_boss: false });
+            self.phase = Phase::Complete;
+            return;
+        }
+
+        self.player.update(dt, controls);
+        self.player.report_shots(&mut self.events);
+        self.terrain.update(dt, &self.player);
+        self.enemies.update(dt, &mut self.player, &mut self.events);
+        self.rings.update(dt, &self.player);
+        self.drone.update(dt, &self.player);
+        self.powerups.update(dt, &self.player);
+        self.score.update(dt);
+        self.lives.check_extra_life(self.score.total(), &mut self.events);
+
+        let damage_source = self
+            .terrain
+            .check_collision(&self.player)
+            .map(DamageSource::Terrain)
+            .or_else(|| self.enemies.check_collision(&self.player).map(DamageSource::Enemy));
+        if let Some(source) = damage_source {
+            self.player.take_hit(10.0, source, &mut self.events);
+            if self.player.is_dead() {
+                self.lose_life();
+            }
+        }
+
+        self.terrain.check_near_misses(&self.player, &mut self.events);
+        self.powerups.check_collection(&mut self.player, &mut self.events);
+        self.rings.check_collection(&self.player, &mut self.events);
+
+        // Drone projectiles hit enemies
+        if self.drone.is_active() {
+            let mut projectiles_to_remove = Vec::new();
+            for (idx, proj) in self.drone.get_projectiles().iter().enumerate() {
+                if self.enemies.check_projectile_hit(proj.position - proj.velocity * dt, proj.position, HitSource::Drone, &mut self.events) {
+                    projectiles_to_remove.push(idx);
+                }
+            }
+            // Reverse order to preserve indices
+            for idx in projectiles_to_remove.iter().rev() {
+                self.drone.clear_projectile(*idx);
+            }
+        }
+    }
+
+    fn fight_boss(&mut self, controls: PlayerControls) {
+        let dt = TICK;
+        let Some(boss) = self.boss.as_mut() else {
+            self.phase = Phase::Flying;
+            return;
+        };
+
+        // The level timer keeps running; time up ends the level even mid-fight
+        self.level.update(dt, self.player.position().z);
+        if self.level.is_complete() {
+            self.events.emit(GameEvent::LevelCompleted { outlasted_boss: !boss.is_defeated() });
+            self.phase = Phase::Complete;
+            return;
+        }
+
+        boss.update(dt, self.player.position(), self.player.velocity());
+        self.score.update(dt);
+        self.lives.check_extra_life(self.score.total(), &mut self.events);
+
+        if boss.check_collision_with_player(self.player.position()) {
+            self.player.take_hit(20.0 * dt, DamageSource::BossContact, &mut self.events); // Continuous damage
+        }
+        if boss.check_projectile_collision(self.player.position()) {
+            self.player.take_hit(15.0, DamageSource::BossProjectile, &mut self.events);
+        }
+        for proj in self.player.projectiles_mut() {
+            if boss.check_hit_by_player_projectile(proj.position - proj.velocity * dt, proj.position, proj.weapon, &mut self.events) {
+                proj.land(&mut self.events);
+            }
+        }
+
+        if boss.is_defeated() {
+            if self.mode == GameMode::BossRush {
+                self.level.mark_rush_boss_defeated();
+                if self.level.is_complete() {
+                    self.events.emit(GameEvent::LevelCompleted { outlasted_boss: false });
+                } else {
+                    self.terrain.pause_spawning(BOSS_RUSH_INTERLUDE);
+                    self.enemies.pause_spawning(BOSS_RUSH_INTERLUDE);
+                }
+            }
+            if self.level.is_complete() {
+                // A boss rush's last win stands even if its final blow was traded
+                self.phase = Phase::Complete;
+                return;
+            }
+            // Boss down with time left; the level carries on
+            log::debug!("Boss defeated! Continuing level...");
+            self.phase = Phase::Flying;
+        }
+
+        if self.player.is_dead() {
+            self.lose_life();
+        }
+
+        self.player.update(dt, controls);
+        self.player.report_shots(&mut self.events);
+    }
+
+    fn lose_life(&mut self) {
+        if self.lives.lose_life() {
+            self.checkpoints.start_respawn();
+            self.phase = Phase::Respawning;
+        } else {
+            self.phase = Phase::GameOver;
+        }
+    }
+
+    /// Whether the run can go on from game over. Endless runs can't: how far
+    /// they got on their lives is what they're ranked on.
+    pub fn can_continue(&self) -> bool {
+        self.mode != GameMode::Endless && self.lives.can_continue() && self.checkpoints.get_last_checkpoint_position().is_some()
+    }
+
+    /// Spend a continue at game over; the score penalty lands on the respawn
+    pub fn use_continue(&mut self) -> bool {
+        if self.phase != Phase::GameOver || !self.can_continue() {
+            return false;
+        }
+        self.lives.use_continue();
+        self.checkpoints.start_respawn();
+        self.phase = Phase::Respawning;
+        if let Some(replay) = &mut self.replay {
+            replay.record(ReplayAction::Continue);
+        }
+        true
+    }
+
+    /// Respawn at the selected checkpoint, putting the world back exactly as it
+    /// was when the checkpoint was taken
+    pub fn respawn(&mut self) {
+        if self.phase != Phase::Respawning {
+            return;
+        }
+        if let Some(replay) = &mut self.replay {
+            replay.record(ReplayAction::Respawn { checkpoint: self.checkpoints.active_index() });
+        }
+
+        self.checkpoints.cancel_respawn();
+        self.checkpoints.discard_newer();
+        self.checkpoints.restore_player_state(&mut self.player, &mut self.score);
+        if let Some(penalty) = self.lives.take_continue_penalty() {
+            self.score.apply_penalty(penalty);
+        }
+
+        if let Some(checkpoint) = self.checkpoints.get_active_checkpoint() {
+            self.boss = checkpoint.world.restore(&mut self.level, &mut self.terrain, &mut self.enemies, &mut self.powerups, &mut self.rings, &mut self.drone);
+
+            // A safe zone around the spawn point, and a moment before anything new arrives
+            self.terrain.clear_around_position(self.player.position(), RESPAWN_CLEAR_RADIUS);
+            self.enemies.clear_around_position(self.player.position(), RESPAWN_CLEAR_RADIUS);
+            self.terrain.pause_spawning(RESPAWN_SPAWN_PAUSE);
+            self.enemies.pause_spawning(RESPAWN_SPAWN_PAUSE);
+        }
+
+        self.phase = if self.boss.as_ref().is_some_and(|b| !b.is_defeated()) {
+            Phase::BossFight
+        } else {
+            Phase::Flying
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::ScoreCategory;
+
+    /// A scripted player: weaves, climbs and fires in a fixed pattern
+    fn pilot(tick: u32) -> PlayerControls {
+        PlayerControls {
+            left: tick % 240 < 60,
+            right: (120..180).contains(&(tick % 240)),
+            up: tick % 90 < 40,
+            down: tick % 300 > 280,
+            boost: tick % 600 < 100,
+            shoot: tick.is_multiple_of(20),
+        }
+    }
+
+    /// Fly until the run stops or `ticks` run out, then play the recorded replay back
+    fn fly_and_replay(setting: DifficultySetting, ticks: u32) -> (Simulation, Simulation) {
+        let mut sim = Simulation::new(Continent::NorthAmerica, GameMode::Standard, setting, true, 31337);
+        let mut events = EventBus::new();
+        for tick in 0..ticks {
+            if !sim.is_running() {
+                break;
+            }
+            sim.step(pilot(tick), &mut events);
+            events.clear();
+        }
+
+        let replay = Replay::from_text(&sim.replay().unwrap().to_text()).unwrap();
+        let replayed = Simulation::play(&replay).unwrap();
+        (sim, replayed)
+    }
+
+    #[test]
+    fn test_replay_reproduces_run() {
+        let (sim, replayed) = fly_and_replay(DifficultySetting::Hard, 60 * 60);
+        assert!(sim.score.total() > 0);
+        assert_eq!(replayed.score.total(), sim.score.total());
+        assert_eq!(replayed.level.elapsed_time(), sim.level.elapsed_time());
+        assert_eq!(replayed.player.position(), sim.player.position());
+        assert_eq!(replayed.phase(), sim.phase());
+    }
+
+    #[test]
+    fn test_replay_reproduces_respawn_and_resume() {
+        let mut sim = Simulation::new(Continent::Europe, GameMode::Standard, DifficultySetting::Normal, false, 7);
+        let mut events = EventBus::new();
+        let mut tick = 0;
+        let mut resumed = false;
+        while tick < 60 * 120 {
+            match sim.phase() {
+                Phase::Respawning => sim.respawn(),
+                Phase::GameOver if sim.can_continue() => {
+                    sim.use_continue();
+                }
+                Phase::GameOver | Phase::Complete => break,
+                Phase::Flying | Phase::BossFight => {
+                    sim.step(pilot(tick), &mut events);
+                    events.clear();
+                    tick += 1;
+                }
+            }
+            if tick == 60 * 45 && !resumed {
+                let json = serde_json::to_string(&sim.suspend()).unwrap();
+                sim = Simulation::resume(serde_json::from_str(&json).unwrap(), false);
+                resumed = true;
+            }
+        }
+
+        let replay = sim.replay().unwrap().clone();
+        assert!(replay.actions().contains(&ReplayAction::Resume));
+        assert!(replay.actions().iter().any(|action| matches!(action, ReplayAction::Respawn { .. })));
+        let replayed = Simulation::play(&replay).unwrap();
+        assert_eq!(replayed.score.total(), sim.score.total());
+        assert_eq!(replayed.level.elapsed_time(), sim.level.elapsed_time());
+        assert_eq!(replayed.lives.lives(), sim.lives.lives());
+        assert_eq!(replayed.ticks(), sim.ticks());
+    }
+
+    #[test]
+    fn test_run_waiting_to_respawn_or_continue_suspends() {
+        let mut sim = Simulation::new(Continent::NorthAmerica, GameMode::Standard, DifficultySetting::Hard, false, 31337);
+        let mut events = EventBus::new();
+        let suspend_and_resume = |sim: &Simulation| {
+            assert!(sim.can_suspend());
+            let json = serde_json::to_string(&sim.suspend()).unwrap();
+            Simulation::resume(serde_json::from_str(&json).unwrap(), false)
+        };
+        let (mut waited_to_respawn, mut waited_to_continue) = (false, false);
+        let mut tick = 0;
+        while tick < 60 * 300 {
+            match sim.phase() {
+                Phase::Respawning => {
+                    if !waited_to_respawn {
+                        sim = suspend_and_resume(&sim);
+                        assert_eq!(sim.phase(), Phase::Respawning);
+                        assert!(sim.checkpoints.is_respawning());
+                        waited_to_respawn = true;
+                    }
+                    sim.respawn();
+                }
+                Phase::GameOver if sim.can_continue() => {
+                    if !waited_to_continue {
+                        sim = suspend_and_resume(&sim);
+                        assert_eq!(sim.phase(), Phase::GameOver);
+                        waited_to_continue = true;
+                    }
+                    sim.use_continue();
+                }
+                Phase::GameOver | Phase::Complete => break,
+                Phase::Flying | Phase::BossFight => {
+                    sim.step(pilot(tick), &mut events);
+                    events.clear();
+                    tick += 1;
+                }
+            }
+        }
+        assert!(waited_to_respawn && waited_to_continue);
+        assert!(!sim.can_suspend(), "out of continues");
+
+        let replayed = Simulation::play(sim.replay().unwrap()).unwrap();
+        assert_eq!(replayed.score.total(), sim.score.total());
+        assert_eq!(replayed.level.elapsed_time(), sim.level.elapsed_time());
+        assert_eq!(replayed.phase(), sim.phase());
+    }
+
+    #[test]
+    fn test_resumed_run_keeps_adapting_where_it_left_off() {
+        let mut sim = Simulation::new(Continent::NorthAmerica, GameMode::Standard, DifficultySetting::Hard, true, 4242);
+        let mut events = EventBus::new();
+        let mut fly = |sim: &mut Simulation, ticks: std::ops::Range<u32>| {
+            for tick in ticks {
+                sim.player.start_invulnerability(1.0);
+                sim.step(pilot(tick), &mut events);
+                events.clear();
+            }
+        };
+
+        // Long enough for the director to have made a few decisions
+        fly(&mut sim, 0..60 * 50);
+        assert!(sim.director.decisions().iter().any(|d| d.intensity_after != 0.0));
+        let json = serde_json::to_string(&sim.suspend()).unwrap();
+        let mut resumed = Simulation::resume(serde_json::from_str(&json).unwrap(), true);
+        assert_eq!(resumed.director.tuning(), sim.director.tuning());
+        assert_eq!(resumed.director.decisions(), sim.director.decisions());
+
+        // The next evaluation builds on the earlier ones rather than starting from neutral
+        let decided = resumed.director.decisions().len();
+        let intensity = resumed.director.decisions()[decided - 1].intensity_after;
+        fly(&mut resumed, 60 * 50..60 * 70);
+        let next = resumed.director.decisions()[decided];
+        assert_eq!(next.intensity_before, intensity);
+        assert!(next.time > 60.0);
+    }
+
+    #[test]
+    fn test_time_attack_finishes_at_distance() {
+        let mut sim = Simulation::new(Continent::Tutorial, GameMode::TimeAttack, DifficultySetting::Easy, false, 12);
+        let distance = sim.level.time_attack().distance;
+        let mut events = EventBus::new();
+        let mut tick = 0;
+        while tick < 60 * 300 {
+            match sim.phase() {
+                Phase::Respawning => sim.respawn(),
+                Phase::GameOver if sim.can_continue() => {
+                    sim.use_continue();
+                }
+                Phase::GameOver | Phase::Complete => break,
+                Phase::Flying | Phase::BossFight => {
+                    // Weave across the course at a safe height, boosting when it can
+                    let position = sim.player.position();
+                    let going_left = (tick / 40) % 2 == 0;
+                    let controls = PlayerControls {
+                        left: going_left && position.x > -15.0,
+                        right: !going_left && position.x < 15.0,
+                        up: position.y < 15.0,
+                        boost: true,
+                        shoot: true,
+                        ..Default::default()
+                    };
+                    sim.step(controls, &mut events);
+                    events.clear();
+                    tick += 1;
+                }
+            }
+        }
+
+        assert_eq!(sim.phase(), Phase::Complete);
+        assert!(sim.boss.is_none());
+        assert!(sim.player.position().z >= distance);
+        // Finishing without a boss standing earns no survival bonus
+        assert_eq!(sim.score.category_total(ScoreCategory::LevelBonus), 0);
+
+        // The mode goes with the replay
+        let replay = Replay::from_text(&sim.replay().unwrap().to_text()).unwrap();
+        assert_eq!(replay.mode, GameMode::TimeAttack);
+        assert_eq!(Simulation::play(&replay).unwrap().run_time(), sim.run_time());
+    }
+
+    #[test]
+    fn test_endless_mini_bosses_and_no_continues() {
+        let mut sim = Simulation::new(Continent::NorthAmerica, GameMode::Endless, DifficultySetting::Normal, false, 99);
+        let mut events = EventBus::new();
+        let mut jump_to = |sim: &mut Simulation, z: f32| {
+            let position = sim.player.position();
+            sim.player.set_position(vec3(position.x, position.y, z));
+            sim.step(PlayerControls::default(), &mut events);
+            events.clear();
+        };
+
+        // Checkpoints are still taken, but game over is final
+        jump_to(&mut sim, 100.0);
+        assert!(sim.checkpoints.get_last_checkpoint_position().is_some());
+        assert!(sim.lives.can_continue());
+        assert!(!sim.can_continue());
+
+        // A mini-boss, with a fraction of a full boss's health
+        jump_to(&mut sim, crate::level::MINI_BOSS_INTERVAL);
+        assert_eq!(sim.phase(), Phase::BossFight);
+        let boss = sim.boss.as_ref().unwrap();
+        assert!(boss.max_health() < boss.boss_type().max_health());
+        assert!(!sim.level.is_complete());
+    }
+
+    #[test]
+    fn test_long_endless_run_keeps_grids_bounded() {
+        let mut sim = Simulation::new(Continent::NorthAmerica, GameMode::Endless, DifficultySetting::Normal, false, 2024);
+        let mut events = EventBus::new();
+        let buckets = |sim: &Simulation| {
+            sim.terrain.grid().bucket_count() + sim.enemies.grid().bucket_count() + sim.powerups.grid().bucket_count() + sim.rings.grid().bucket_count()
+        };
+
+        // Half an hour of boosting, kept alive and beating every mini-boss outright
+        let mut most = 0;
+        for tick in 0..60 * 60 * 30 {
+            sim.player.start_invulnerability(1.0);
+            if let Some(boss) = sim.boss.as_mut() {
+                boss.take_damage(f32::MAX);
+            }
+            let controls = PlayerControls { boost: true, up: sim.player.position().y < 3.0, ..Default::default() };
+            sim.step(controls, &mut events);
+            events.clear();
+            assert!(sim.is_running(), "run stopped at tick {}", tick);
+            if tick > 60 * 60 {
+                most = most.max(buckets(&sim));
+            }
+        }
+
+        assert!(sim.level.distance() > 20_000.0);
+        assert!(most < 100, "{} grid buckets in use", most);
+
+        // Only the checkpoints that can still be chosen are kept, and suspended
+        assert!(sim.checkpoints.checkpoint_count() > 100);
+        assert_eq!(sim.checkpoints.checkpoints().len(), crate::checkpoint::SELECTABLE_CHECKPOINTS);
+        assert_eq!(sim.suspend().checkpoints.len(), crate::checkpoint::SELECTABLE_CHECKPOINTS);
+    }
+
+    #[test]
+    fn test_respawning_at_an_older_checkpoint_drops_the_newer_ones() {
+        let mut sim = Simulation::new(Continent::Europe, GameMode::Standard, DifficultySetting::Normal, false, 11);
+        let mut events = EventBus::new();
+        let fly_to_checkpoint = |sim: &mut Simulation, events: &mut EventBus, count: usize| {
+            while sim.checkpoints.checkpoint_count() < count {
+                sim.player.start_invulnerability(1.0);
+                sim.step(PlayerControls { boost: true, ..Default::default() }, events);
+                events.clear();
+            }
+        };
+        fly_to_checkpoint(&mut sim, &mut events, 3);
+        let second = sim.checkpoints.checkpoint(1).unwrap().position;
+        let abandoned = sim.checkpoints.checkpoint(2).unwrap().time_created;
+
+        // Into a boulder on the last of its health
+        sim.terrain.add_obstacle(crate::terrain::Obstacle {
+            position: sim.player.position() + vec3(0.0, 0.0, 0.2),
+            size: vec3(1.5, 1.5, 1.5),
+            obstacle_type: crate::terrain::ObstacleType::Boulder,
+            blade_angle: 0.0,
+            closest_approach: f32::MAX,
+        });
+        sim.player.start_invulnerability(0.0);
+        sim.player.take_damage(99.0);
+        sim.step(PlayerControls::default(), &mut events);
+        assert_eq!(sim.phase(), Phase::Respawning);
+        assert!(sim.checkpoints.select(1));
+        sim.respawn();
+        assert_eq!(sim.checkpoints.checkpoint_count(), 2);
+        assert_eq!(sim.checkpoints.selectable_range(), 0..2);
+
+        // The next checkpoint is the third again, taken on this attempt
+        fly_to_checkpoint(&mut sim, &mut events, 3);
+        assert_eq!(sim.checkpoints.checkpoint(1).unwrap().position, second);
+        let retaken = sim.checkpoints.checkpoint(2).unwrap();
+        assert!(retaken.position > second);
+        assert!(retaken.time_created > abandoned);
+        assert_eq!(sim.checkpoints.selectable_range(), 0..3);
+        assert_eq!(sim.checkpoints.active_index(), 2);
+    }
+
+    #[test]
+    fn test_boss_rush_chains_every_boss() {
+        let mut sim = Simulation::new(Continent::NorthAmerica, GameMode::BossRush, DifficultySetting::Normal, false, 5);
+        let mut events = EventBus::new();
+        let mut fought = Vec::new();
+        let mut tick = 0;
+        while tick < 60 * 120 && sim.phase() != Phase::Complete {
+            match sim.phase() {
+                Phase::Respawning => sim.respawn(),
+                Phase::GameOver => panic!("out of lives"),
+                Phase::BossFight => {
+                    // Take a hit from each boss, then beat it outright
+                    let boss = sim.boss.as_mut().unwrap();
+                    if fought.last() != Some(&boss.boss_type()) {
+                        fought.push(boss.boss_type());
+                        sim.player.take_hit(5.0, DamageSource::BossProjectile, &mut sim.events);
+                    } else {
+                        boss.take_damage(f32::MAX);
+                    }
+                }
+                Phase::Flying | Phase::Complete => {}
+            }
+            if sim.is_running() {
+                let controls = PlayerControls { up: sim.player.position().y < 10.0, ..Default::default() };
+                sim.step(controls, &mut events);
+                events.clear();
+                tick += 1;
+            }
+        }
+
+        assert_eq!(sim.phase(), Phase::Complete);
+        let expected: Vec<BossType> = crate::level::BOSS_RUSH_ROUTE.iter().map(|c| BossType::from_continent(*c)).collect();
+        assert_eq!(fought, expected);
+        assert!(sim.damage_taken() >= 30.0);
+        assert_eq!(sim.checkpoints.checkpoint_count(), 6, "one per fight");
+
+        // The tally survives suspending the run
+        let json = serde_json::to_string(&sim.suspend()).unwrap();
+        assert_eq!(serde_json::from_str::<SuspendedRun>(&json).unwrap().damage_taken, sim.damage_taken());
+    }
+
+    #[test]
+    fn test_dying_on_the_completion_tick_still_completes() {
+        let mut sim = Simulation::new(Continent::Tutorial, GameMode::Standard, DifficultySetting::Normal, false, 3);
+        let remaining = sim.level.remaining_time();
+        sim.level.update(remaining - TICK / 2.0, 0.0);
+
+        // A boulder dead ahead and one hit left in the player
+        sim.terrain.add_obstacle(crate::terrain::Obstacle {
+            position: vec3(0.0, 0.0, 0.2),
+            size: vec3(1.5, 1.5, 1.5),
+            obstacle_type: crate::terrain::ObstacleType::Boulder,
+            blade_angle: 0.0,
+            closest_approach: f32::MAX,
+        });
+        sim.player.take_damage(99.0);
+        let lives = sim.lives.lives();
+
+        let mut events = EventBus::new();
+        sim.step(PlayerControls::default(), &mut events);
+        assert_eq!(sim.phase(), Phase::Complete);
+        assert_eq!(sim.lives.lives(), lives);
+        let events = events.drain();
+        assert!(events.contains(&GameEvent::LevelCompleted { outlasted_boss: false }));
+        assert!(!events.iter().any(|event| matches!(event, GameEvent::PlayerDied { .. })));
+    }
+
+    #[test]
+    fn test_replay_that_does_not_fit_is_rejected() {
+        let mut replay = Replay::new(Continent::Tutorial, DifficultySetting::Easy, false, 1);
+        replay.record(ReplayAction::Respawn { checkpoint: 0 });
+        assert!(Simulation::play(&replay).is_err());
+
+        let mut replay = Replay::new(Continent::Tutorial, DifficultySetting::Easy, false, 1);
+        replay.record(ReplayAction::Continue);
+        assert!(Simulation::play(&replay).is_err());
+    }
+}
@@ -0,0 +1,32 @@
+//! Serde helpers for the checkpoint snapshots each manager produces.
+//!
+//! macroquad's `Vec3` isn't serializable, so snapshot fields holding one use
+//! `#[serde(with = "crate::snapshot::vec3")]` and are stored as `[x, y, z]`.
+
+pub mod vec3 {
+    use macroquad::prelude::Vec3;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(v: &Vec3, serializer: S) -> Result<S::Ok, S::Error> {
+        v.to_array().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec3, D::Error> {
+        <[f32; 3]>::deserialize(deserializer).map(Vec3::from_array)
+    }
+}
+
+/// Same as [`vec3`] for a list of positions
+pub mod vec3_list {
+    use macroquad::prelude::Vec3;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(list: &[Vec3], serializer: S) -> Result<S::Ok, S::Error> {
+        let arrays: Vec<[f32; 3]> = list.iter().map(|v| v.to_array()).collect();
+        arrays.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Vec3>, D::Error> {
+        Vec::<[f32; 3]>::deserialize(deserializer).map(|arrays| arrays.into_iter().map(Vec3::from_array).collect())
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+/// Broad-phase grid that buckets entities by their Z extent.
+///
+/// The course is a narrow corridor flown along +Z, so X/Y buckets would add
+/// little: nearly everything that shares a Z slice is also within reach on X/Y.
+/// Entries are indices into the owner's entity list and the grid is rebuilt
+/// whenever that list changes.
+pub struct SpatialGrid {
+    bucket_size: f32,
+    buckets: HashMap<i32, Vec<usize>>,
+}
+
+impl SpatialGrid {
+    pub fn new(bucket_size: f32) -> Self {
+        Self {
+            bucket_size,
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Remove all entries. Buckets the last rebuild used keep their allocation for
+    /// the next one; the rest are behind the player for good and are dropped, so
+    /// the grid only ever spans the stretch of course in play.
+    pub fn clear(&mut self) {
+        self.buckets.retain(|_, bucket| !bucket.is_empty());
+        for bucket in self.buckets.values_mut() {
+            bucket.clear();
+        }
+    }
+
+    /// Index an entity covering `min_z..=max_z`
+    pub fn insert(&mut self, index: usize, min_z: f32, max_z: f32) {
+        for key in self.bucket_key(min_z)..=self.bucket_key(max_z) {
+            self.buckets.entry(key).or_default().push(index);
+        }
+    }
+
+    /// Collect the indices of every entity that may overlap `min_z..=max_z`.
+    ///
+    /// Results are sorted and deduplicated; callers still run the narrow-phase test.
+    pub fn query(&self, min_z: f32, max_z: f32, results: &mut Vec<usize>) {
+        results.clear();
+        for key in self.bucket_key(min_z)..=self.bucket_key(max_z) {
+            if let Some(bucket) = self.buckets.get(&key) {
+                results.extend_from_slice(bucket);
+            }
+        }
+        results.sort_unstable();
+        results.dedup();
+    }
+
+    /// Number of Z slices the grid currently holds, filled or not
+    pub fn bucket_count(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Convenience wrapper around `query` that allocates the result list
+    pub fn candidates(&self, min_z: f32, max_z: f32) -> Vec<usize> {
+        let mut results = Vec::new();
+        self.query(min_z, max_z, &mut results);
+        results
+    }
+
+    fn bucket_key(&self, z: f32) -> i32 {
+        (z / self.bucket_size).floor() as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_returns_overlapping_entries() {
+        let mut grid = SpatialGrid::new(10.0);
+        grid.insert(0, 5.0, 6.0);
+        grid.insert(1, 25.0, 26.0);
+        grid.insert(2, 55.0, 56.0);
+
+        assert_eq!(grid.candidates(0.0, 9.0), vec![0]);
+        assert_eq!(grid.candidates(20.0, 30.0), vec![1]);
+        assert!(grid.candidates(30.0, 49.0).is_empty());
+    }
+
+    #[test]
+    fn test_entries_spanning_buckets_are_deduplicated() {
+        let mut grid = SpatialGrid::new(10.0);
+        grid.insert(7, 5.0, 35.0);
+
+        assert_eq!(grid.candidates(0.0, 40.0), vec![7]);
+        assert_eq!(grid.candidates(22.0, 23.0), vec![7]);
+    }
+
+    #[test]
+    fn test_negative_z() {
+        let mut grid = SpatialGrid::new(10.0);
+        grid.insert(0, -15.0, -12.0);
+
+        assert_eq!(grid.candidates(-11.0, -10.5), vec![0]);
+        assert!(grid.candidates(0.0, 5.0).is_empty());
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut grid = SpatialGrid::new(10.0);
+        grid.insert(0, 0.0, 1.0);
+        assert_eq!(grid.candidates(0.0, 1.0), vec![0]);
+
+        grid.clear();
+        assert!(grid.candidates(0.0, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_clear_drops_buckets_left_behind() {
+        let mut grid = SpatialGrid::new(10.0);
+        // Rebuild a window of entities sliding forward, as the course scrolls by
+        for step in 0..1000 {
+            grid.clear();
+            let z = step as f32 * 10.0;
+            grid.insert(0, z, z + 25.0);
+        }
+        assert!(grid.bucket_count() <= 8, "{} buckets kept", grid.bucket_count());
+        assert_eq!(grid.candidates(9990.0, 9995.0), vec![0]);
+    }
+}
@@ -0,0 +1,241 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::assets::Continent;
+use crate::events::{EventListener, GameEvent};
+
+/// Projectiles fired with one weapon, and how many of them hit something
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct WeaponStats {
+    pub fired: u32,
+    pub landed: u32,
+}
+
+impl WeaponStats {
+    /// Fraction of projectiles that hit, or `None` before any were fired
+    pub fn accuracy(&self) -> Option<f32> {
+        (self.fired > 0).then(|| self.landed as f32 / self.fired as f32)
+    }
+}
+
+/// Everything recorded about one continent (or, summed, about all of them).
+/// The maps are keyed by the display name of the cause, weapon or powerup.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContinentStats {
+    pub attempts: u32,
+    pub completions: u32,
+    pub deaths: HashMap<String, u32>, // By cause: obstacle type, enemy type or boss attack
+    pub rings_hit: u32,
+    pub rings_missed: u32,
+    pub weapons: HashMap<String, WeaponStats>,
+    pub powerups: HashMap<String, u32>,
+    pub distance: f32, // World units flown
+}
+
+impl ContinentStats {
+    pub fn total_deaths(&self) -> u32 {
+        self.deaths.values().sum()
+    }
+
+    pub fn total_powerups(&self) -> u32 {
+        self.powerups.values().sum()
+    }
+
+    /// Fraction of rings flown through out of all rings reached
+    pub fn ring_accuracy(&self) -> Option<f32> {
+        let total = self.rings_hit + self.rings_missed;
+        (total > 0).then(|| self.rings_hit as f32 / total as f32)
+    }
+
+    /// Deaths by cause, most frequent first
+    pub fn deaths_by_cause(&self) -> Vec<(&str, u32)> {
+        let mut deaths: Vec<(&str, u32)> = self.deaths.iter().map(|(cause, &n)| (cause.as_str(), n)).collect();
+        deaths.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        deaths
+    }
+
+    /// Add `other`'s counts to these
+    fn add(&mut self, other: &ContinentStats) {
+        self.attempts += other.attempts;
+        self.completions += other.completions;
+        self.rings_hit += other.rings_hit;
+        self.rings_missed += other.rings_missed;
+        self.distance += other.distance;
+        for (cause, &n) in &other.deaths {
+            *self.deaths.entry(cause.clone()).or_insert(0) += n;
+        }
+        for (weapon, stats) in &other.weapons {
+            let ours = self.weapons.entry(weapon.clone()).or_default();
+            ours.fired += stats.fired;
+            ours.landed += stats.landed;
+        }
+        for (powerup, &n) in &other.powerups {
+            *self.powerups.entry(powerup.clone()).or_insert(0) += n;
+        }
+    }
+
+    /// Keep the larger of each count (both sides usually share history)
+    fn merge(&mut self, other: &ContinentStats) {
+        self.attempts = self.attempts.max(other.attempts);
+        self.completions = self.completions.max(other.completions);
+        self.rings_hit = self.rings_hit.max(other.rings_hit);
+        self.rings_missed = self.rings_missed.max(other.rings_missed);
+        self.distance = self.distance.max(other.distance);
+        for (cause, &n) in &other.deaths {
+            let ours = self.deaths.entry(cause.clone()).or_insert(0);
+            *ours = (*ours).max(n);
+        }
+        for (weapon, stats) in &other.weapons {
+            let ours = self.weapons.entry(weapon.clone()).or_default();
+            if stats.fired > ours.fired {
+                *ours = *stats;
+            }
+        }
+        for (powerup, &n) in &other.powerups {
+            let ours = self.powerups.entry(powerup.clone()).or_insert(0);
+            *ours = (*ours).max(n);
+        }
+    }
+}
+
+/// Per-continent statistics for a profile, kept up to date from gameplay events
+/// while a level is being played.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerStats {
+    continents: HashMap<String, ContinentStats>, // By continent name
+    #[serde(skip)]
+    current: Option<Continent>, // The continent being played
+}
+
+impl PlayerStats {
+    /// Count an attempt at a continent and record what happens in it from now on
+    pub fn start_level(&mut self, continent: Continent) {
+        self.current = Some(continent);
+        self.continents.entry(continent.name().to_string()).or_default().attempts += 1;
+    }
+
+    /// Carry on recording a resumed run (already counted as an attempt)
+    pub fn resume_level(&mut self, continent: Continent) {
+        self.current = Some(continent);
+    }
+
+    pub fn continent(&self, continent: Continent) -> ContinentStats {
+        self.continents.get(continent.name()).cloned().unwrap_or_default()
+    }
+
+    /// Every continent added together
+    pub fn lifetime(&self) -> ContinentStats {
+        let mut total = ContinentStats::default();
+        for stats in self.continents.values() {
+            total.add(stats);
+        }
+        total
+    }
+
+    pub fn add_distance(&mut self, distance: f32) {
+        if let Some(stats) = self.current_mut() {
+            stats.distance += distance;
+        }
+    }
+
+    pub fn merge(&mut self, other: &PlayerStats) {
+        for (continent, stats) in &other.continents {
+            self.continents.entry(continent.clone()).or_default().merge(stats);
+        }
+    }
+
+    fn current_mut(&mut self) -> Option<&mut ContinentStats> {
+        let continent = self.current?;
+        Some(self.continents.entry(continent.name().to_string()).or_default())
+    }
+}
+
+impl EventListener for PlayerStats {
+    fn on_event(&mut self, event: &GameEvent) {
+        let Some(stats) = self.current_mut() else {
+            return;
+        };
+
+        match event {
+            GameEvent::LevelCompleted { .. } => stats.completions += 1,
+            GameEvent::PlayerDied { cause, .. } => *stats.deaths.entry(cause.name().to_string()).or_insert(0) += 1,
+            GameEvent::RingPassed { .. } => stats.rings_hit += 1,
+            GameEvent::RingMissed { .. } => stats.rings_missed += 1,
+            GameEvent::ShotFired { weapon } => stats.weapons.entry(weapon.name().to_string()).or_default().fired += 1,
+            GameEvent::ShotLanded { weapon } => stats.weapons.entry(weapon.name().to_string()).or_default().landed += 1,
+            GameEvent::PowerupCollected { powerup_type, .. } => {
+                *stats.powerups.entry(powerup_type.name().to_string()).or_insert(0) += 1;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glidewars::enemy::EnemyType;
+    use crate::events::DamageSource;
+    use crate::player::Weapon;
+    use glidewars::terrain::ObstacleType;
+    use macroquad::prelude::Vec3;
+
+    fn died(cause: DamageSource) -> GameEvent {
+        GameEvent::PlayerDied { position: Vec3::ZERO, cause }
+    }
+
+    #[test]
+    fn test_events_recorded_against_current_continent() {
+        let mut stats = PlayerStats::default();
+        stats.on_event(&GameEvent::RingPassed { position: Vec3::ZERO, chain: 1, precision: 0.0 }); // Not in a level
+
+        stats.start_level(Continent::Europe);
+        stats.on_event(&died(DamageSource::Terrain(ObstacleType::Mountain)));
+        stats.on_event(&died(DamageSource::Terrain(ObstacleType::Mountain)));
+        stats.on_event(&died(DamageSource::Enemy(EnemyType::Seeker)));
+        stats.on_event(&GameEvent::RingPassed { position: Vec3::ZERO, chain: 1, precision: 0.0 });
+        stats.on_event(&GameEvent::RingMissed { position: Vec3::ZERO });
+        stats.on_event(&GameEvent::ShotFired { weapon: Weapon::Laser });
+        stats.on_event(&GameEvent::ShotFired { weapon: Weapon::Laser });
+        stats.on_event(&GameEvent::ShotLanded { weapon: Weapon::Laser });
+        stats.on_event(&GameEvent::LevelCompleted { outlasted_boss: false });
+        stats.add_distance(120.0);
+
+        let europe = stats.continent(Continent::Europe);
+        assert_eq!((europe.attempts, europe.completions), (1, 1));
+        assert_eq!(europe.deaths_by_cause(), vec![("Mountain", 2), ("Seeker", 1)]);
+        assert_eq!(europe.ring_accuracy(), Some(0.5));
+        assert_eq!(europe.weapons["LASER"].accuracy(), Some(0.5));
+        assert_eq!(europe.distance, 120.0);
+        assert_eq!(stats.continent(Continent::Asia).attempts, 0);
+    }
+
+    #[test]
+    fn test_lifetime_sums_continents() {
+        let mut stats = PlayerStats::default();
+        stats.start_level(Continent::Tutorial);
+        stats.on_event(&died(DamageSource::BossProjectile));
+        stats.start_level(Continent::Asia);
+        stats.on_event(&died(DamageSource::BossProjectile));
+        stats.resume_level(Continent::Asia);
+
+        let lifetime = stats.lifetime();
+        assert_eq!(lifetime.attempts, 2);
+        assert_eq!(lifetime.deaths_by_cause(), vec![("Boss Projectile", 2)]);
+    }
+
+    #[test]
+    fn test_saved_stats_reload() {
+        let mut stats = PlayerStats::default();
+        stats.start_level(Continent::Africa);
+        stats.on_event(&GameEvent::ShotFired { weapon: Weapon::Missile });
+
+        let reloaded: PlayerStats = serde_json::from_str(&serde_json::to_string(&stats).unwrap()).unwrap();
+        assert_eq!(reloaded.continent(Continent::Africa).weapons["MISSILE"].fired, 1);
+
+        // Stats from an old save start empty
+        let empty: PlayerStats = serde_json::from_str("{}").unwrap();
+        assert_eq!(empty.lifetime().attempts, 0);
+    }
+}
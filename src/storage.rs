@@ -0,0 +1,260 @@
+//! Where saves, profiles and suspended runs are kept.
+//!
+//! Everything is stored under a key shaped like a relative path
+//! (`profiles/profile1/save.json`). Desktop builds map keys to files under
+//! `~/.glidewars`; the web build keeps them in the browser's localStorage through
+//! the `glidewars_storage` plugin registered in `index.html`.
+
+use std::io;
+use std::rc::Rc;
+
+pub trait Storage {
+    /// Contents stored under `key`, or `None` if there's nothing there
+    fn read(&self, key: &str) -> io::Result<Option<String>>;
+
+    /// Replace the contents of `key` in one step: a reader sees the old contents
+    /// or the new ones, never a mix
+    fn write(&self, key: &str, contents: &str) -> io::Result<()>;
+
+    /// Remove `key`; removing a missing key is not an error
+    fn remove(&self, key: &str) -> io::Result<()>;
+
+    /// Remove every key under the directory `prefix`
+    fn remove_all(&self, prefix: &str) -> io::Result<()>;
+
+    fn exists(&self, key: &str) -> bool {
+        matches!(self.read(key), Ok(Some(_)))
+    }
+
+    /// Move `from` to `to`, replacing whatever was there
+    fn rename(&self, from: &str, to: &str) -> io::Result<()> {
+        if let Some(contents) = self.read(from)? {
+            self.write(to, &contents)?;
+            self.remove(from)?;
+        }
+        Ok(())
+    }
+}
+
+/// Storage shared by the save, profile and suspend managers
+pub type SharedStorage = Rc<dyn Storage>;
+
+/// The platform's storage: files on desktop, localStorage on the web
+pub fn platform_storage() -> SharedStorage {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        Rc::new(FileStorage::new(file::data_dir()))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        Rc::new(LocalStorage)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use file::{data_dir, FileStorage};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod file {
+    use super::Storage;
+    use std::fs::{self, File};
+    use std::io::{self, Write};
+    use std::path::PathBuf;
+
+    /// `~/.glidewars`, holding the save and other per-user files on desktop
+    pub fn data_dir() -> PathBuf {
+        let mut path = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        path.push(".glidewars");
+        path
+    }
+
+    /// Keys are files relative to `root`
+    pub struct FileStorage {
+        root: PathBuf,
+    }
+
+    impl FileStorage {
+        pub fn new(root: PathBuf) -> Self {
+            Self { root }
+        }
+
+        fn path(&self, key: &str) -> PathBuf {
+            self.root.join(key)
+        }
+    }
+
+    impl Storage for FileStorage {
+        fn read(&self, key: &str) -> io::Result<Option<String>> {
+            match fs::read_to_string(self.path(key)) {
+                Ok(contents) => Ok(Some(contents)),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e),
+            }
+        }
+
+        /// Written to a synced temp file next to the target, then renamed into place
+        fn write(&self, key: &str, contents: &str) -> io::Result<()> {
+            let path = self.path(key);
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+
+            let mut temp_path = path.as_os_str().to_owned();
+            temp_path.push(".tmp");
+            let temp_path = PathBuf::from(temp_path);
+            {
+                let mut file = File::create(&temp_path)?;
+                file.write_all(contents.as_bytes())?;
+                file.sync_all()?;
+            }
+            fs::rename(&temp_path, &path)?;
+
+            // Make the rename itself durable
+            #[cfg(unix)]
+            if let Some(dir) = path.parent() {
+                File::open(dir).and_then(|dir| dir.sync_all()).ok();
+            }
+            Ok(())
+        }
+
+        fn remove(&self, key: &str) -> io::Result<()> {
+            match fs::remove_file(self.path(key)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            }
+        }
+
+        fn remove_all(&self, prefix: &str) -> io::Result<()> {
+            match fs::remove_dir_all(self.path(prefix)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            }
+        }
+
+        fn rename(&self, from: &str, to: &str) -> io::Result<()> {
+            let to = self.path(to);
+            if let Some(dir) = to.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            match fs::rename(self.path(from), to) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub use web::LocalStorage;
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use super::Storage;
+    use std::io;
+
+    // Implemented by the glidewars_storage plugin in index.html
+    extern "C" {
+        /// Copies the value into `buf` if it fits; returns its length in bytes, or -1 if missing
+        fn glidewars_storage_get(key: *const u8, key_len: usize, buf: *mut u8, buf_len: usize) -> i32;
+        /// Returns 0 if the browser refused the write (quota exceeded, storage disabled)
+        fn glidewars_storage_set(key: *const u8, key_len: usize, value: *const u8, value_len: usize) -> i32;
+        fn glidewars_storage_remove(key: *const u8, key_len: usize);
+        fn glidewars_storage_remove_prefix(prefix: *const u8, prefix_len: usize);
+    }
+
+    /// Lets the JS side check it matches this build
+    #[no_mangle]
+    pub extern "C" fn glidewars_storage_crate_version() -> u32 {
+        1
+    }
+
+    /// The browser's localStorage, with every key namespaced under `glidewars/`
+    pub struct LocalStorage;
+
+    fn namespaced(key: &str) -> String {
+        format!("glidewars/{}", key)
+    }
+
+    impl Storage for LocalStorage {
+        fn read(&self, key: &str) -> io::Result<Option<String>> {
+            let key = namespaced(key);
+            let len = unsafe { glidewars_storage_get(key.as_ptr(), key.len(), std::ptr::null_mut(), 0) };
+            if len < 0 {
+                return Ok(None);
+            }
+
+            let mut buf = vec![0u8; len as usize];
+            unsafe { glidewars_storage_get(key.as_ptr(), key.len(), buf.as_mut_ptr(), buf.len()) };
+            String::from_utf8(buf)
+                .map(Some)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        }
+
+        /// A single setItem, which the browser applies atomically
+        fn write(&self, key: &str, contents: &str) -> io::Result<()> {
+            let key = namespaced(key);
+            let stored = unsafe { glidewars_storage_set(key.as_ptr(), key.len(), contents.as_ptr(), contents.len()) };
+            if stored == 0 {
+                return Err(io::Error::new(io::ErrorKind::Other, "localStorage refused the write"));
+            }
+            Ok(())
+        }
+
+        fn remove(&self, key: &str) -> io::Result<()> {
+            let key = namespaced(key);
+            unsafe { glidewars_storage_remove(key.as_ptr(), key.len()) };
+            Ok(())
+        }
+
+        fn remove_all(&self, prefix: &str) -> io::Result<()> {
+            // Only whole path segments, so `profile1` doesn't take `profile10` with it
+            let prefix = format!("{}/", namespaced(prefix.trim_end_matches('/')));
+            unsafe { glidewars_storage_remove_prefix(prefix.as_ptr(), prefix.len()) };
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_storage(name: &str) -> (FileStorage, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("glidewars_storage_{}_{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        (FileStorage::new(dir.clone()), dir)
+    }
+
+    #[test]
+    fn test_file_storage_round_trip() {
+        let (storage, root) = temp_storage("round_trip");
+        assert_eq!(storage.read("a/b.json").unwrap(), None);
+
+        storage.write("a/b.json", "hello").unwrap();
+        assert_eq!(storage.read("a/b.json").unwrap().as_deref(), Some("hello"));
+        assert!(!root.join("a/b.json.tmp").exists());
+
+        storage.rename("a/b.json", "c/d.json").unwrap();
+        assert!(!storage.exists("a/b.json"));
+        assert_eq!(storage.read("c/d.json").unwrap().as_deref(), Some("hello"));
+
+        storage.remove("c/d.json").unwrap();
+        storage.remove("c/d.json").unwrap(); // Already gone is fine
+        assert!(!storage.exists("c/d.json"));
+    }
+
+    #[test]
+    fn test_remove_all_under_prefix() {
+        let (storage, _root) = temp_storage("remove_all");
+        storage.write("profiles/one/save.json", "1").unwrap();
+        storage.write("profiles/one/suspend.json", "2").unwrap();
+        storage.write("profiles/two/save.json", "3").unwrap();
+
+        storage.remove_all("profiles/one").unwrap();
+        assert!(!storage.exists("profiles/one/save.json"));
+        assert!(!storage.exists("profiles/one/suspend.json"));
+        assert!(storage.exists("profiles/two/save.json"));
+    }
+}
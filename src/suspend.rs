@@ -0,0 +1,226 @@
+use serde::{Deserialize, Serialize};
+use crate::assets::Continent;
+use crate::checkpoint::{Checkpoint, WorldSnapshot};
+use crate::difficulty::DifficultySetting;
+use crate::game_mode::GameMode;
+use crate::lives::LivesManager;
+use crate::storage::{self, SharedStorage};
+
+/// Bump whenever anything stored in a suspended run changes shape. Files written
+/// by another version are discarded on load rather than half-restored.
+pub const SUSPEND_FORMAT_VERSION: u32 = 1;
+
+/// What a suspended run was waiting on the player for, if anything
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Awaiting {
+    #[default]
+    Nothing,  // Mid-flight
+    Respawn,  // On the checkpoint screen
+    Continue, // At game over with a continue left
+}
+
+/// A level left mid-run, written on quit so it can be resumed from the main menu
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SuspendedRun {
+    pub format_version: u32,
+    pub continent: Continent,
+    #[serde(default)]
+    pub mode: GameMode,
+    pub difficulty: DifficultySetting, // As the run was started, whatever Options says now
+    #[serde(default)]
+    pub seed: u64, // Kept for the run's leaderboard entry
+    pub world: WorldSnapshot,
+    pub checkpoints: Vec<Checkpoint>, // The ones still selectable
+    #[serde(default)]
+    pub checkpoints_reached: usize, // Including those no longer kept
+    pub lives: LivesManager,
+    #[serde(default)]
+    pub rng_state: u64, // Gameplay random generator, so the rest of the run matches its replay
+    #[serde(default)]
+    pub replay: Option<String>, // The run's replay so far, as text
+    #[serde(default)]
+    pub damage_taken: f32, // Over the run so far
+    #[serde(default)]
+    pub awaiting: Awaiting,
+}
+
+impl SuspendedRun {
+    pub fn new(continent: Continent, difficulty: DifficultySetting, seed: u64, world: WorldSnapshot, checkpoints: Vec<Checkpoint>, lives: LivesManager) -> Self {
+        Self {
+            format_version: SUSPEND_FORMAT_VERSION,
+            continent,
+            mode: GameMode::Standard,
+            difficulty,
+            seed,
+            world,
+            checkpoints,
+            checkpoints_reached: 0,
+            lives,
+            rng_state: 0,
+            replay: None,
+            damage_taken: 0.0,
+            awaiting: Awaiting::Nothing,
+        }
+    }
+
+    /// Resume straight into the boss fight if the boss was alive when suspended
+    pub fn in_boss_fight(&self) -> bool {
+        self.world.boss.as_ref().is_some_and(|boss| !boss.is_defeated())
+    }
+}
+
+/// The single suspended-run slot, stored next to the save
+pub struct SuspendStore {
+    storage: SharedStorage,
+    key: String,
+    available: bool,
+}
+
+impl SuspendStore {
+    pub fn new() -> Self {
+        Self::open(storage::platform_storage(), "suspend.json")
+    }
+
+    /// Open the slot under `key`, discarding it straight away if it can't be resumed
+    pub fn open(storage: SharedStorage, key: &str) -> Self {
+        let mut store = Self { storage, key: key.to_string(), available: false };
+        store.available = store.read().is_some();
+        store
+    }
+
+    /// Whether there's a run to offer in the main menu
+    pub fn has_run(&self) -> bool {
+        self.available
+    }
+
+    pub fn save(&mut self, run: &SuspendedRun) -> Result<(), Box<dyn std::error::Error>> {
+        let json = serde_json::to_string(run)?;
+        self.storage.write(&self.key, &json)?;
+        self.available = true;
+        println!("Suspended run saved: {}", self.key);
+        Ok(())
+    }
+
+    /// Take the suspended run out of the slot; a run can only be resumed once
+    pub fn take(&mut self) -> Option<SuspendedRun> {
+        let run = self.read();
+        self.clear();
+        run
+    }
+
+    pub fn clear(&mut self) {
+        if self.available {
+            self.storage.remove(&self.key).ok();
+        }
+        self.available = false;
+    }
+
+    /// Read the slot, deleting it if it's unreadable or from another format version
+    fn read(&self) -> Option<SuspendedRun> {
+        let contents = self.storage.read(&self.key).ok().flatten()?;
+
+        // Check the version before deserializing everything else, whose shape may have changed
+        let version = serde_json::from_str::<serde_json::Value>(&contents)
+            .ok()
+            .and_then(|value| value.get("format_version")?.as_u64());
+        let run = match version {
+            Some(v) if v == SUSPEND_FORMAT_VERSION as u64 => serde_json::from_str::<SuspendedRun>(&contents).ok(),
+            _ => None,
+        };
+
+        if run.is_none() {
+            println!("Discarding suspended run from another version: {}", self.key);
+            self.storage.remove(&self.key).ok();
+        }
+        run
+    }
+}
+
+impl Default for SuspendStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::director::DifficultyDirector;
+    use crate::drone_companion::DroneCompanion;
+    use crate::enemy::EnemyManager;
+    use crate::level::LevelManager;
+    use crate::lives::LivesConfig;
+    use crate::player::Player;
+    use crate::powerup::PowerupManager;
+    use crate::rings::RingManager;
+    use crate::scoring::{ScoreKeeper, ScoreKind};
+    use crate::storage::FileStorage;
+    use crate::terrain::TerrainManager;
+    use std::rc::Rc;
+
+    fn temp_storage(name: &str) -> SharedStorage {
+        let dir = std::env::temp_dir().join(format!("glidewars_suspend_{}_{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        Rc::new(FileStorage::new(dir))
+    }
+
+    fn sample_run() -> SuspendedRun {
+        let mut score = ScoreKeeper::new();
+        score.award(ScoreKind::EnemyKill, None);
+        let world = WorldSnapshot::capture(
+            &Player::new(),
+            &score,
+            &LevelManager::new(Continent::Europe, DifficultySetting::Hard),
+            &TerrainManager::new(),
+            &EnemyManager::new(),
+            &PowerupManager::new(),
+            &RingManager::new(),
+            None,
+            &DroneCompanion::new(),
+            &DifficultyDirector::new(),
+        );
+        let lives = LivesManager::new(LivesConfig::for_setting(DifficultySetting::Hard));
+        SuspendedRun::new(Continent::Europe, DifficultySetting::Hard, 42, world, Vec::new(), lives)
+    }
+
+    #[test]
+    fn test_run_can_be_resumed_once() {
+        let storage = temp_storage("once");
+        let mut store = SuspendStore::open(storage.clone(), "suspend.json");
+        assert!(!store.has_run());
+
+        store.save(&sample_run()).unwrap();
+        assert!(SuspendStore::open(storage, "suspend.json").has_run()); // Survives a restart
+
+        let run = store.take().unwrap();
+        assert_eq!(run.continent, Continent::Europe);
+        assert_eq!(run.difficulty, DifficultySetting::Hard);
+        assert_eq!(run.world.score.total(), sample_run().world.score.total());
+        assert!(!run.in_boss_fight());
+
+        assert!(!store.has_run());
+        assert!(store.take().is_none());
+    }
+
+    #[test]
+    fn test_other_format_version_is_discarded() {
+        let storage = temp_storage("version");
+        let mut run = sample_run();
+        run.format_version = SUSPEND_FORMAT_VERSION + 1;
+        storage.write("suspend.json", &serde_json::to_string(&run).unwrap()).unwrap();
+
+        let store = SuspendStore::open(storage.clone(), "suspend.json");
+        assert!(!store.has_run());
+        assert!(!storage.exists("suspend.json"));
+    }
+
+    #[test]
+    fn test_unreadable_file_is_discarded() {
+        let storage = temp_storage("garbage");
+        storage.write("suspend.json", r#"{"format_version":1,"continent":"Europe"}"#).unwrap();
+
+        let store = SuspendStore::open(storage.clone(), "suspend.json");
+        assert!(!store.has_run());
+        assert!(!storage.exists("suspend.json"));
+    }
+}
@@ -1,15 +1,32 @@
 use macroquad::prelude::*;
-use macroquad::rand::gen_range;
-use crate::player::Player;
-
-#[derive(Clone)]
+use crate::rng::gen_range;
+use serde::{Deserialize, Serialize};
+use crate::assets::TerrainTheme;
+use crate::collision::Shape;
+use crate::difficulty::Difficulty;
+use crate::events::{EventBus, GameEvent};
+use crate::fmath;
+use crate::player::{self, Player};
+use crate::spatial_grid::SpatialGrid;
+
+/// How far beyond the player's hitbox a pass still counts as a near miss
+pub const NEAR_MISS_MARGIN: f32 = 1.5;
+
+/// Thickness of each canyon wall, as a share of the canyon's width
+const CANYON_WALL_SHARE: f32 = 0.1;
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Obstacle {
+    #[serde(with = "crate::snapshot::vec3")]
     pub position: Vec3,
+    #[serde(with = "crate::snapshot::vec3")]
     pub size: Vec3,
     pub obstacle_type: ObstacleType,
+    pub blade_angle: f32, // Wind turbine rotor angle (radians)
+    pub closest_approach: f32, // Closest the player has come while passing (for near misses)
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ObstacleType {
     Mountain,
     Canyon,
@@ -17,12 +34,100 @@ pub enum ObstacleType {
     WindTurbine,
 }
 
+impl ObstacleType {
+    pub fn name(&self) -> &str {
+        match self {
+            ObstacleType::Mountain => "Mountain",
+            ObstacleType::Canyon => "Canyon",
+            ObstacleType::Boulder => "Boulder",
+            ObstacleType::WindTurbine => "Wind Turbine",
+        }
+    }
+}
+
+impl Obstacle {
+    /// Collision description for this obstacle, built from its type and size
+    pub fn collision_shape(&self) -> Shape {
+        let pos = self.position;
+        let size = self.size;
+
+        match self.obstacle_type {
+            ObstacleType::Mountain => {
+                // Three stacked tiers that narrow toward the peak
+                let base_y = pos.y - size.y / 2.0;
+                Shape::Compound(vec![
+                    Shape::aabb(vec3(pos.x, base_y + size.y * 0.2, pos.z), vec3(size.x, size.y * 0.4, size.z)),
+                    Shape::aabb(vec3(pos.x, base_y + size.y * 0.55, pos.z), size * vec3(0.65, 0.3, 0.65)),
+                    Shape::aabb(vec3(pos.x, base_y + size.y * 0.85, pos.z), size * vec3(0.3, 0.3, 0.3)),
+                ])
+            }
+            ObstacleType::Canyon => {
+                // Two thin walls at the edges of its box, with a flyable gap between them
+                let wall_thickness = size.x * CANYON_WALL_SHARE;
+                let wall_offset = size.x / 2.0 - wall_thickness / 2.0;
+                let wall_size = vec3(wall_thickness, size.y, size.z);
+                Shape::Compound(vec![
+                    Shape::aabb(pos - vec3(wall_offset, 0.0, 0.0), wall_size),
+                    Shape::aabb(pos + vec3(wall_offset, 0.0, 0.0), wall_size),
+                ])
+            }
+            ObstacleType::Boulder => Shape::sphere(pos, size.x / 2.0),
+            ObstacleType::WindTurbine => {
+                // Tower post plus three rotating blades facing the player
+                let tower_radius = size.x / 2.0;
+                let hub = pos + vec3(0.0, size.y / 2.0, -tower_radius);
+                let blade_length = size.y * 0.45;
+
+                let mut shapes = vec![Shape::capsule(
+                    pos - vec3(0.0, size.y / 2.0, 0.0),
+                    pos + vec3(0.0, size.y / 2.0, 0.0),
+                    tower_radius,
+                )];
+                for i in 0..3 {
+                    let angle = self.blade_angle + i as f32 * std::f32::consts::TAU / 3.0;
+                    let rotation = fmath::rotation_z(angle);
+                    let center = hub + rotation * vec3(blade_length / 2.0, 0.0, 0.0);
+                    shapes.push(Shape::obb(center, vec3(blade_length, 0.3, 0.1), rotation));
+                }
+                Shape::Compound(shapes)
+            }
+        }
+    }
+
+    /// How far `point` is from this obstacle (whose `collision_shape()` is `shape`)
+    /// for near misses. Threading a canyon's gap is the way through it, not a
+    /// skim, so there's no distance while inside.
+    pub fn near_miss_distance(&self, shape: &Shape, point: Vec3) -> Option<f32> {
+        if self.obstacle_type == ObstacleType::Canyon {
+            let gap_half_width = self.size.x / 2.0 - self.size.x * CANYON_WALL_SHARE;
+            let offset = point - self.position;
+            if offset.x.abs() < gap_half_width && offset.y.abs() < self.size.y / 2.0 {
+                return None;
+            }
+        }
+        Some(shape.distance_to_point(point))
+    }
+}
+
+/// Everything needed to put the terrain back exactly as it was at a checkpoint
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TerrainSnapshot {
+    #[serde(with = "crate::snapshot::vec3_list")]
+    ground_tiles: Vec<Vec3>,
+    obstacles: Vec<Obstacle>,
+    last_spawn_z: f32,
+    spawn_cooldown: f32,
+}
+
 pub struct TerrainManager {
     ground_tiles: Vec<Vec3>,
     obstacles: Vec<Obstacle>,
-    difficulty: f32,
+    difficulty: Difficulty,
     last_spawn_z: f32,
     spawn_cooldown: f32,
+    grid: SpatialGrid,
+    ground_color: Color,
+    grid_color: Color,
 }
 
 impl TerrainManager {
@@ -30,9 +135,12 @@ impl TerrainManager {
         let mut manager = Self {
             ground_tiles: Vec::new(),
             obstacles: Vec::new(),
-            difficulty: 1.0,
+            difficulty: Difficulty::default(),
             last_spawn_z: 0.0,
             spawn_cooldown: 0.0,
+            grid: SpatialGrid::new(10.0),
+            ground_color: Color::from_rgba(20, 40, 60, 255),
+            grid_color: Color::from_rgba(0, 100, 150, 255),
         };
 
         // Initialize ground tiles
@@ -51,9 +159,6 @@ impl TerrainManager {
             self.spawn_cooldown -= dt;
         }
 
-        // Progressive difficulty increase
-        self.difficulty = 1.0 + (player_z / 500.0);
-
         // Update ground tiles (scrolling effect)
         for tile in &mut self.ground_tiles {
             if tile.z < player_z - 20.0 {
@@ -66,16 +171,47 @@ impl TerrainManager {
         // Spawn new obstacles based on distance (only if cooldown is 0)
         if self.spawn_cooldown <= 0.0 && player_z > self.last_spawn_z {
             self.spawn_obstacles(player_z);
-            self.last_spawn_z = player_z + 20.0;
+            self.last_spawn_z = player_z + self.difficulty.obstacle_wave_spacing();
+        }
+
+        // Spin wind turbine blades
+        for obstacle in &mut self.obstacles {
+            if matches!(obstacle.obstacle_type, ObstacleType::WindTurbine) {
+                obstacle.blade_angle += dt * 1.5;
+            }
         }
 
         // Remove obstacles that are behind the player
         self.obstacles.retain(|obs| obs.position.z > player_z - 30.0);
+
+        self.rebuild_grid();
+    }
+
+    /// Set the difficulty used for upcoming obstacle waves
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+    }
+
+    /// Draw the ground in a continent's colours instead of the default retro blue
+    pub fn set_theme(&mut self, theme: &TerrainTheme) {
+        let [r, g, b] = theme.ground_color;
+        self.ground_color = Color::from_rgba(r, g, b, 255);
+        let [r, g, b] = theme.grid_color;
+        self.grid_color = Color::from_rgba(r, g, b, 255);
+    }
+
+    /// Re-index obstacles in the broad-phase grid (call after changing the obstacle list)
+    pub fn rebuild_grid(&mut self) {
+        self.grid.clear();
+        for (index, obstacle) in self.obstacles.iter().enumerate() {
+            let (min, max) = obstacle.collision_shape().bounds();
+            self.grid.insert(index, min.z, max.z);
+        }
     }
 
     fn spawn_obstacles(&mut self, player_z: f32) {
         // Number of obstacles increases with difficulty
-        let num_obstacles = gen_range(1, (2 + self.difficulty as i32).min(5) + 1);
+        let num_obstacles = gen_range(1, self.difficulty.max_obstacles_per_wave() + 1);
 
         for _ in 0..num_obstacles {
             let spawn_z = player_z + gen_range(30.0, 60.0);
@@ -96,14 +232,23 @@ impl TerrainManager {
                 ObstacleType::WindTurbine => vec3(0.5, 4.0, 0.5),
             };
 
-            self.obstacles.push(Obstacle {
+            self.add_obstacle(Obstacle {
                 position: vec3(spawn_x, spawn_y, spawn_z),
                 size,
                 obstacle_type,
+                blade_angle: gen_range(0.0, std::f32::consts::TAU),
+                closest_approach: f32::MAX,
             });
         }
     }
 
+    /// Add an obstacle and index it in the broad-phase grid
+    pub fn add_obstacle(&mut self, obstacle: Obstacle) {
+        let (min, max) = obstacle.collision_shape().bounds();
+        self.grid.insert(self.obstacles.len(), min.z, max.z);
+        self.obstacles.push(obstacle);
+    }
+
     pub fn draw(&self) {
         // Draw ground plane (grid effect for retro look)
         for tile in &self.ground_tiles {
@@ -114,7 +259,7 @@ impl TerrainManager {
                 *tile,
                 vec3(20.0, 0.5, tile_size),
                 None,
-                Color::from_rgba(20, 40, 60, 255)
+                self.ground_color
             );
 
             // Grid lines (retro style)
@@ -123,7 +268,7 @@ impl TerrainManager {
                 draw_line_3d(
                     vec3(tile.x + x_offset, tile.y + 0.3, tile.z),
                     vec3(tile.x + x_offset, tile.y + 0.3, tile.z + tile_size),
-                    Color::from_rgba(0, 100, 150, 255)
+                    self.grid_color
                 );
             }
 
@@ -151,54 +296,81 @@ impl TerrainManager {
                 ObstacleType::WindTurbine => Color::from_rgba(200, 200, 200, 255),
             };
 
-            draw_cube(obstacle.position, obstacle.size, None, color);
+            // Render from the collision shape so visuals match hitboxes
+            let shape = obstacle.collision_shape();
+            shape.draw(color);
 
             // Draw wireframe outline for retro effect
-            draw_cube_wires(obstacle.position, obstacle.size, Color::from_rgba(255, 255, 255, 100));
+            shape.draw_wires(Color::from_rgba(255, 255, 255, 100));
         }
     }
 
-    pub fn check_collision(&self, player: &Player) -> bool {
-        let player_pos = player.position();
-        let player_radius = 0.7; // Collision radius for player
+    /// Type of the obstacle the player flew into this frame, if any
+    pub fn check_collision(&self, player: &Player) -> Option<ObstacleType> {
+        // Sweep from last frame's position so boosting can't tunnel through thin obstacles
+        let start = player.previous_position();
+        let end = player.position();
+        let radius = player::COLLISION_RADIUS;
+
+        self.grid
+            .candidates(start.z.min(end.z) - radius, start.z.max(end.z) + radius)
+            .into_iter()
+            .map(|index| &self.obstacles[index])
+            .find(|obstacle| obstacle.collision_shape().sweep_sphere(start, end, radius).is_some())
+            .map(|obstacle| obstacle.obstacle_type)
+    }
 
-        for obstacle in &self.obstacles {
-            // Simple box collision
-            let dx = (player_pos.x - obstacle.position.x).abs();
-            let dy = (player_pos.y - obstacle.position.y).abs();
-            let dz = (player_pos.z - obstacle.position.z).abs();
-
-            if dx < obstacle.size.x / 2.0 + player_radius
-                && dy < obstacle.size.y / 2.0 + player_radius
-                && dz < obstacle.size.z / 2.0 + player_radius
-            {
-                return true;
+    /// Track how closely the player skims each obstacle and report a near miss
+    /// once a skimmed obstacle is behind them without having been hit
+    pub fn check_near_misses(&mut self, player: &Player, events: &mut EventBus) {
+        let position = player.position();
+        let radius = player::COLLISION_RADIUS;
+
+        for index in self.grid.candidates(position.z - 10.0, position.z + radius + NEAR_MISS_MARGIN) {
+            let obstacle = &mut self.obstacles[index];
+            let shape = obstacle.collision_shape();
+            let (_, max) = shape.bounds();
+
+            if max.z + radius >= position.z {
+                // Still alongside or ahead
+                if let Some(distance) = obstacle.near_miss_distance(&shape, position) {
+                    obstacle.closest_approach = obstacle.closest_approach.min(distance);
+                }
+            } else if obstacle.closest_approach < radius + NEAR_MISS_MARGIN {
+                // Passed it: a touch is a hit, not a near miss
+                if obstacle.closest_approach > radius {
+                    events.emit(GameEvent::NearMiss { position: obstacle.position });
+                }
+                obstacle.closest_approach = f32::MAX;
             }
         }
+    }
 
-        false
+    /// The broad-phase index, rebuilt on every update
+    pub fn grid(&self) -> &SpatialGrid {
+        &self.grid
     }
 
     pub fn get_obstacles(&self) -> &Vec<Obstacle> {
         &self.obstacles
     }
 
-    pub fn reset_to_position(&mut self, z_position: f32) {
-        // Clear obstacles behind the checkpoint position
-        self.obstacles.retain(|obs| obs.position.z > z_position - 20.0);
-
-        // Reset ground tiles to align with checkpoint position
-        self.ground_tiles.clear();
-        for i in 0..20 {
-            let z = z_position - 50.0 + i as f32 * 10.0;
-            self.ground_tiles.push(vec3(0.0, -3.0, z));
+    pub fn snapshot(&self) -> TerrainSnapshot {
+        TerrainSnapshot {
+            ground_tiles: self.ground_tiles.clone(),
+            obstacles: self.obstacles.clone(),
+            last_spawn_z: self.last_spawn_z,
+            spawn_cooldown: self.spawn_cooldown,
         }
+    }
 
-        // Update last spawn position to be just before checkpoint
-        self.last_spawn_z = z_position;
-
-        #[cfg(debug_assertions)]
-        println!("Terrain reset to Z={:.1}", z_position);
+    /// Put the ground and obstacles back exactly as they were when the snapshot was taken
+    pub fn restore(&mut self, snapshot: &TerrainSnapshot) {
+        self.ground_tiles = snapshot.ground_tiles.clone();
+        self.obstacles = snapshot.obstacles.clone();
+        self.last_spawn_z = snapshot.last_spawn_z;
+        self.spawn_cooldown = snapshot.spawn_cooldown;
+        self.rebuild_grid();
     }
 
     /// Clear obstacles within a radius around a position (used on checkpoint respawn)
@@ -209,24 +381,24 @@ impl TerrainManager {
             let distance = (obs.position - position).length();
             distance > radius
         });
+        self.rebuild_grid();
         let cleared = initial_count - self.obstacles.len();
-        #[cfg(debug_assertions)]
         if cleared > 0 {
-            println!("Cleared {} obstacles around checkpoint", cleared);
+            log::debug!("Cleared {} obstacles around checkpoint", cleared);
         }
     }
 
     /// Pause spawning for a duration (used after respawn)
     pub fn pause_spawning(&mut self, duration: f32) {
         self.spawn_cooldown = duration;
-        #[cfg(debug_assertions)]
-        println!("Terrain spawning paused for {:.1}s", duration);
+        log::debug!("Terrain spawning paused for {:.1}s", duration);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::difficulty::DifficultySetting;
 
     fn create_test_player(z: f32) -> Player {
         let mut player = Player::new();
@@ -239,7 +411,7 @@ mod tests {
         let terrain = TerrainManager::new();
         assert_eq!(terrain.ground_tiles.len(), 20);
         assert_eq!(terrain.obstacles.len(), 0);
-        assert_eq!(terrain.difficulty, 1.0);
+        assert_eq!(terrain.difficulty.level, 1.0);
     }
 
     #[test]
@@ -253,21 +425,23 @@ mod tests {
         }
 
         // Should have spawned some obstacles
-        assert!(terrain.obstacles.len() > 0);
+        assert!(!terrain.obstacles.is_empty());
     }
 
     #[test]
-    fn test_difficulty_increases_with_distance() {
+    fn test_difficulty_drives_wave_density() {
         let mut terrain = TerrainManager::new();
-        let player1 = create_test_player(0.0);
-        terrain.update(0.1, &player1);
-        let difficulty1 = terrain.difficulty;
+        let player = create_test_player(1.0);
+        terrain.update(0.1, &player);
+        let easy_spacing = terrain.last_spawn_z;
 
-        let player2 = create_test_player(500.0);
-        terrain.update(0.1, &player2);
-        let difficulty2 = terrain.difficulty;
+        // Harder waves are packed closer together
+        let mut hard_terrain = TerrainManager::new();
+        hard_terrain.set_difficulty(Difficulty::new(2.0, 1.0, DifficultySetting::Hard));
+        hard_terrain.update(0.1, &player);
 
-        assert!(difficulty2 > difficulty1);
+        assert!(hard_terrain.last_spawn_z < easy_spacing);
+        assert!(hard_terrain.difficulty.max_obstacles_per_wave() > terrain.difficulty.max_obstacles_per_wave());
     }
 
     #[test]
@@ -294,12 +468,14 @@ mod tests {
             position: vec3(0.0, 0.0, -50.0),
             size: vec3(2.0, 2.0, 2.0),
             obstacle_type: ObstacleType::Boulder,
+            blade_angle: 0.0,
+            closest_approach: f32::MAX,
         });
 
         terrain.update(0.1, &player);
 
-        // Old obstacle should be removed
-        assert_eq!(terrain.obstacles.len(), 0);
+        // Old obstacle should be removed (new ones spawn ahead of the player)
+        assert!(terrain.obstacles.iter().all(|obs| obs.position.z > 70.0));
     }
 
     #[test]
@@ -312,9 +488,12 @@ mod tests {
             position: vec3(0.0, 0.0, 10.0),
             size: vec3(2.0, 2.0, 2.0),
             obstacle_type: ObstacleType::Boulder,
+            blade_angle: 0.0,
+            closest_approach: f32::MAX,
         });
+        terrain.rebuild_grid();
 
-        assert!(terrain.check_collision(&player));
+        assert!(terrain.check_collision(&player).is_some());
     }
 
     #[test]
@@ -327,35 +506,36 @@ mod tests {
             position: vec3(20.0, 20.0, 50.0),
             size: vec3(2.0, 2.0, 2.0),
             obstacle_type: ObstacleType::Boulder,
+            blade_angle: 0.0,
+            closest_approach: f32::MAX,
         });
+        terrain.rebuild_grid();
 
-        assert!(!terrain.check_collision(&player));
+        assert!(terrain.check_collision(&player).is_none());
     }
 
     #[test]
-    fn test_reset_to_position() {
+    fn test_snapshot_restores_exact_world() {
         let mut terrain = TerrainManager::new();
+        for z in [10.0, 40.0, 70.0, 100.0, 130.0] {
+            terrain.update(0.1, &create_test_player(z));
+        }
+        let snapshot = terrain.snapshot();
+        let positions = |terrain: &TerrainManager| -> Vec<Vec3> { terrain.obstacles.iter().map(|o| o.position).collect() };
+        let saved_obstacles = positions(&terrain);
+        let saved_tiles = terrain.ground_tiles.clone();
+
+        // Fly on so the world moves past the checkpoint, then come back
+        for z in [200.0, 300.0, 400.0] {
+            terrain.update(0.1, &create_test_player(z));
+        }
+        assert_ne!(positions(&terrain), saved_obstacles);
 
-        // Add obstacles at various positions
-        terrain.obstacles.push(Obstacle {
-            position: vec3(0.0, 0.0, 50.0),
-            size: vec3(2.0, 2.0, 2.0),
-            obstacle_type: ObstacleType::Boulder,
-        });
-        terrain.obstacles.push(Obstacle {
-            position: vec3(0.0, 0.0, 100.0),
-            size: vec3(2.0, 2.0, 2.0),
-            obstacle_type: ObstacleType::Mountain,
-        });
-
-        terrain.reset_to_position(90.0);
-
-        // Should remove obstacles behind reset position
-        assert_eq!(terrain.obstacles.len(), 1);
-        assert!(terrain.obstacles[0].position.z > 70.0);
-
-        // Ground tiles should be realigned
-        assert_eq!(terrain.ground_tiles.len(), 20);
+        // Restored through JSON, as a saved checkpoint would be
+        let json = serde_json::to_string(&snapshot).unwrap();
+        terrain.restore(&serde_json::from_str(&json).unwrap());
+        assert_eq!(positions(&terrain), saved_obstacles);
+        assert_eq!(terrain.ground_tiles, saved_tiles);
     }
 
     #[test]
@@ -368,11 +548,15 @@ mod tests {
             position: vec3(5.0, 0.0, 100.0), // Close
             size: vec3(2.0, 2.0, 2.0),
             obstacle_type: ObstacleType::Boulder,
+            blade_angle: 0.0,
+            closest_approach: f32::MAX,
         });
         terrain.obstacles.push(Obstacle {
             position: vec3(30.0, 0.0, 100.0), // Far
             size: vec3(2.0, 2.0, 2.0),
             obstacle_type: ObstacleType::Mountain,
+            blade_angle: 0.0,
+            closest_approach: f32::MAX,
         });
 
         terrain.clear_around_position(center, 15.0);
@@ -381,4 +565,108 @@ mod tests {
         assert_eq!(terrain.obstacles.len(), 1);
         assert!(terrain.obstacles[0].position.x > 20.0);
     }
+
+    #[test]
+    fn test_canyon_gap_is_flyable() {
+        let mut terrain = TerrainManager::new();
+        terrain.obstacles.push(Obstacle {
+            position: vec3(0.0, 0.0, 10.0),
+            size: vec3(2.0, 1.0, 4.0),
+            obstacle_type: ObstacleType::Canyon,
+            blade_angle: 0.0,
+            closest_approach: f32::MAX,
+        });
+        terrain.rebuild_grid();
+
+        // Centered between the walls is safe, hugging a wall is not
+        assert!(terrain.check_collision(&create_test_player(10.0)).is_none());
+
+        let mut player = create_test_player(10.0);
+        player.set_position(vec3(0.6, 0.0, 10.0));
+        assert!(terrain.check_collision(&player).is_some());
+    }
+
+    #[test]
+    fn test_wind_turbine_blades_rotate() {
+        let mut turbine = Obstacle {
+            position: vec3(0.0, 0.0, 10.0),
+            size: vec3(0.5, 4.0, 0.5),
+            obstacle_type: ObstacleType::WindTurbine,
+            blade_angle: 0.0,
+            closest_approach: f32::MAX,
+        };
+
+        // A blade points along +X at angle 0
+        let beside_hub = vec3(1.5, 2.0, 9.75);
+        assert!(turbine.collision_shape().intersects_sphere(beside_hub, 0.3));
+
+        // Rotated a sixth of a turn, the gap between blades lines up with the same point
+        turbine.blade_angle = std::f32::consts::PI / 3.0;
+        assert!(!turbine.collision_shape().intersects_sphere(beside_hub, 0.3));
+    }
+
+    #[test]
+    fn test_fast_player_cannot_tunnel() {
+        let tower = Obstacle {
+            position: vec3(0.0, 0.0, 10.0),
+            size: vec3(0.5, 4.0, 0.5),
+            obstacle_type: ObstacleType::WindTurbine,
+            blade_angle: 0.0,
+            closest_approach: f32::MAX,
+        };
+        let shape = tower.collision_shape();
+
+        // Both frame endpoints are clear of the tower, but the path between them isn't
+        let start = vec3(0.0, 0.0, 8.0);
+        let end = vec3(0.0, 0.0, 12.0);
+        assert!(!shape.intersects_sphere(start, player::COLLISION_RADIUS));
+        assert!(!shape.intersects_sphere(end, player::COLLISION_RADIUS));
+        assert!(shape.sweep_sphere(start, end, player::COLLISION_RADIUS).is_some());
+    }
+
+    fn fly_past(obstacle_type: ObstacleType, size: Vec3, x: f32) -> Vec<GameEvent> {
+        let mut terrain = TerrainManager::new();
+        let mut events = EventBus::new();
+        terrain.add_obstacle(Obstacle {
+            position: vec3(0.0, 0.0, 10.0),
+            size,
+            obstacle_type,
+            blade_angle: 0.0,
+            closest_approach: f32::MAX,
+        });
+
+        let mut player = Player::new();
+        for z in 0..20 {
+            player.set_position(vec3(x, 0.0, z as f32));
+            terrain.check_near_misses(&player, &mut events);
+        }
+        events.drain()
+    }
+
+    fn fly_past_boulder(x: f32) -> Vec<GameEvent> {
+        fly_past(ObstacleType::Boulder, vec3(1.5, 1.5, 1.5), x)
+    }
+
+    #[test]
+    fn test_near_miss_when_skimming() {
+        let events = fly_past_boulder(2.0);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], GameEvent::NearMiss { .. }));
+    }
+
+    #[test]
+    fn test_no_near_miss_when_hit_or_far() {
+        assert!(fly_past_boulder(0.5).is_empty());
+        assert!(fly_past_boulder(6.0).is_empty());
+    }
+
+    #[test]
+    fn test_threading_canyon_is_not_a_near_miss() {
+        let canyon = vec3(2.0, 1.0, 4.0);
+        assert!(fly_past(ObstacleType::Canyon, canyon, 0.0).is_empty());
+
+        // Skimming the outside of a wall still counts
+        let events = fly_past(ObstacleType::Canyon, canyon, 2.5);
+        assert!(matches!(events[..], [GameEvent::NearMiss { .. }]));
+    }
 }
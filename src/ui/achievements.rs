@@ -0,0 +1,94 @@
+use macroquad::prelude::*;
+use crate::achievements::{AchievementProgress, ACHIEVEMENTS};
+
+/// Rows that fit on screen at once
+const VISIBLE_ROWS: usize = 7;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AchievementsAction {
+    None,
+    Back,
+}
+
+pub struct AchievementsScreen {
+    time: f32,
+    scroll: usize, // First row shown
+    progress: AchievementProgress,
+}
+
+impl AchievementsScreen {
+    pub fn new() -> Self {
+        Self {
+            time: 0.0,
+            scroll: 0,
+            progress: AchievementProgress::default(),
+        }
+    }
+
+    /// Show the active profile's achievements
+    pub fn set_progress(&mut self, progress: &AchievementProgress) {
+        self.progress = progress.clone();
+        self.scroll = 0;
+    }
+
+    pub fn update(&mut self, dt: f32) -> AchievementsAction {
+        self.time += dt;
+
+        if (is_key_pressed(KeyCode::Up) || is_key_pressed(KeyCode::W)) && self.scroll > 0 {
+            self.scroll -= 1;
+        }
+        if (is_key_pressed(KeyCode::Down) || is_key_pressed(KeyCode::S)) && self.scroll + VISIBLE_ROWS < ACHIEVEMENTS.len() {
+            self.scroll += 1;
+        }
+
+        if is_key_pressed(KeyCode::Escape) || is_key_pressed(KeyCode::Enter) {
+            return AchievementsAction::Back;
+        }
+        AchievementsAction::None
+    }
+
+    pub fn draw(&self) {
+        let screen_w = screen_width();
+        let screen_h = screen_height();
+
+        clear_background(Color::from_rgba(0, 0, 20, 255));
+
+        let title = "ACHIEVEMENTS";
+        let title_width = measure_text(title, None, 48, 1.0).width;
+        let pulse = (self.time * 2.0).sin() * 0.2 + 0.8;
+        draw_text(title, screen_w / 2.0 - title_width / 2.0, 70.0, 48.0, Color::from_rgba(0, 255, 255, (pulse * 255.0) as u8));
+
+        let count = format!("{} / {} UNLOCKED", self.progress.unlocked_count(), ACHIEVEMENTS.len());
+        let count_width = measure_text(&count, None, 20, 1.0).width;
+        draw_text(&count, screen_w / 2.0 - count_width / 2.0, 100.0, 20.0, Color::from_rgba(255, 200, 0, 220));
+
+        let panel_x = screen_w / 2.0 - 300.0;
+        let row_height = 58.0;
+        for (row, achievement) in ACHIEVEMENTS.iter().skip(self.scroll).take(VISIBLE_ROWS).enumerate() {
+            let y = 125.0 + row as f32 * row_height;
+            let unlocked = self.progress.is_unlocked(achievement);
+            let (border, name_color, text_color) = if unlocked {
+                (Color::from_rgba(255, 215, 0, 200), Color::from_rgba(255, 215, 0, 255), WHITE)
+            } else {
+                (Color::from_rgba(80, 80, 100, 200), Color::from_rgba(150, 150, 150, 255), Color::from_rgba(120, 120, 120, 255))
+            };
+
+            draw_rectangle(panel_x, y, 600.0, row_height - 8.0, Color::from_rgba(10, 10, 30, 220));
+            draw_rectangle_lines(panel_x, y, 600.0, row_height - 8.0, 2.0, border);
+            draw_text(achievement.name, panel_x + 15.0, y + 22.0, 24.0, name_color);
+            draw_text(achievement.description, panel_x + 15.0, y + 42.0, 18.0, text_color);
+
+            let status = match self.progress.counter_progress(achievement) {
+                _ if unlocked => "UNLOCKED".to_string(),
+                Some((current, target)) => format!("{} / {}", current, target),
+                None => "LOCKED".to_string(),
+            };
+            let status_width = measure_text(&status, None, 18, 1.0).width;
+            draw_text(&status, panel_x + 585.0 - status_width, y + 22.0, 18.0, text_color);
+        }
+
+        let hint = "↑↓ SCROLL | ESC BACK";
+        let hint_width = measure_text(hint, None, 16, 1.0).width;
+        draw_text(hint, screen_w / 2.0 - hint_width / 2.0, screen_h - 30.0, 16.0, Color::from_rgba(200, 200, 200, 180));
+    }
+}
@@ -0,0 +1,360 @@
+use macroquad::prelude::*;
+use crate::assets::Continent;
+use crate::difficulty::DifficultySetting;
+use crate::leaderboard::{self, LeaderboardEntry, LeaderboardStore, LEADERBOARD_SIZE};
+use crate::leaderboard_api::RankedEntry;
+use crate::online_leaderboard::Board;
+use crate::profiles::MAX_PROFILE_NAME;
+
+const DIFFICULTIES: [DifficultySetting; 3] = [DifficultySetting::Easy, DifficultySetting::Normal, DifficultySetting::Hard];
+
+/// Column offsets from the left of the table
+const COLUMNS: [(&str, f32); 6] = [("#", 0.0), ("NAME", 50.0), ("SCORE", 250.0), ("TIME", 370.0), ("DATE", 460.0), ("PROFILE", 580.0)];
+
+const ROW_HEIGHT: f32 = 26.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LeaderboardAction {
+    None,
+    Submit(LeaderboardEntry), // New run with its name entered
+    FetchOnline(Board),       // The online view needs this board
+    RaceGhost(LeaderboardEntry), // Race the selected run's replay next time
+    Back,
+}
+
+enum Mode {
+    Viewing,
+    Naming { rank: usize, entry: LeaderboardEntry },
+}
+
+/// What's known of the online board being shown
+enum OnlineBoard {
+    Loading,
+    Loaded { top: Vec<RankedEntry>, around: Vec<RankedEntry> },
+    Failed(String),
+}
+
+pub struct LeaderboardScreen {
+    time: f32,
+    continent: Continent,
+    endless: bool,     // Showing the endless boards rather than the continent's
+    difficulty: usize, // Index into DIFFICULTIES
+    boards: [Vec<LeaderboardEntry>; 3],
+    highlight: Option<usize>, // Row of the run just added, or picked to race
+    mode: Mode,
+    online_available: bool,
+    show_online: bool,
+    online: OnlineBoard,
+    player_name: String, // Highlighted on the online board
+    message: Option<(String, bool)>, // Text, whether it's an error
+}
+
+impl LeaderboardScreen {
+    pub fn new() -> Self {
+        Self {
+            time: 0.0,
+            continent: Continent::Tutorial,
+            endless: false,
+            difficulty: 1,
+            boards: Default::default(),
+            highlight: None,
+            mode: Mode::Viewing,
+            online_available: false,
+            show_online: false,
+            online: OnlineBoard::Loading,
+            player_name: String::new(),
+            message: None,
+        }
+    }
+
+    /// Whether Tab can switch to the online boards
+    pub fn set_online_available(&mut self, available: bool, player_name: &str) {
+        self.online_available = available;
+        self.player_name = player_name.to_string();
+        self.show_online &= available;
+    }
+
+    /// Show a continent's local boards, starting on `difficulty`
+    pub fn set_boards(&mut self, store: &LeaderboardStore, continent: Continent, difficulty: DifficultySetting) {
+        self.continent = continent;
+        self.endless = false;
+        self.show(DIFFICULTIES.map(|d| store.top(continent, d).to_vec()), difficulty);
+    }
+
+    /// Show the endless boards, starting on `difficulty`. They're only kept on
+    /// this machine, since the server can't check an endless run.
+    pub fn set_endless_boards(&mut self, store: &LeaderboardStore, difficulty: DifficultySetting) {
+        self.endless = true;
+        self.online_available = false;
+        self.show(DIFFICULTIES.map(|d| store.endless_top(d).to_vec()), difficulty);
+    }
+
+    fn show(&mut self, boards: [Vec<LeaderboardEntry>; 3], difficulty: DifficultySetting) {
+        self.difficulty = DIFFICULTIES.iter().position(|d| *d == difficulty).unwrap_or(1);
+        self.boards = boards;
+        self.highlight = None;
+        self.mode = Mode::Viewing;
+        self.show_online = false;
+        self.message = None;
+    }
+
+    /// Continent and difficulty of the board on screen
+    pub fn board(&self) -> Board {
+        Board { continent: self.continent, difficulty: DIFFICULTIES[self.difficulty] }
+    }
+
+    /// Whether the boards on screen are the endless ones
+    pub fn is_endless(&self) -> bool {
+        self.endless
+    }
+
+    /// Mark the run that just went on the shown board
+    pub fn highlight(&mut self, rank: usize) {
+        self.highlight = Some(rank);
+    }
+
+    /// Ask for a name for a run that will place at `rank` on the shown board.
+    /// The entry's current name is offered as the default.
+    pub fn enter_name(&mut self, rank: usize, entry: LeaderboardEntry) {
+        // Drop the key that finished the level so it isn't typed into the name
+        while get_char_pressed().is_some() {}
+        self.highlight = Some(rank);
+        self.mode = Mode::Naming { rank, entry };
+    }
+
+    /// An online board arrived; ignored if another board is on screen by now
+    pub fn set_online_board(&mut self, board: Board, top: Result<Vec<RankedEntry>, String>, around: Vec<RankedEntry>) {
+        if board != self.board() {
+            return;
+        }
+        self.online = match top {
+            Ok(top) => OnlineBoard::Loaded { top, around },
+            Err(e) => OnlineBoard::Failed(e),
+        };
+    }
+
+    /// A line under the board, e.g. where a run placed online
+    pub fn show_message(&mut self, message: &str) {
+        self.message = Some((message.to_string(), false));
+    }
+
+    pub fn show_error(&mut self, message: &str) {
+        self.message = Some((message.to_string(), true));
+    }
+
+    pub fn update(&mut self, dt: f32) -> LeaderboardAction {
+        self.time += dt;
+
+        match &mut self.mode {
+            Mode::Viewing => {
+                let mut changed = false;
+                if (is_key_pressed(KeyCode::Left) || is_key_pressed(KeyCode::A)) && self.difficulty > 0 {
+                    self.difficulty -= 1;
+                    changed = true;
+                }
+                if (is_key_pressed(KeyCode::Right) || is_key_pressed(KeyCode::D)) && self.difficulty + 1 < DIFFICULTIES.len() {
+                    self.difficulty += 1;
+                    changed = true;
+                }
+                if is_key_pressed(KeyCode::Tab) && self.online_available {
+                    self.show_online = !self.show_online;
+                    changed = true;
+                }
+                if changed {
+                    self.highlight = None;
+                    if self.show_online {
+                        self.online = OnlineBoard::Loading;
+                        return LeaderboardAction::FetchOnline(self.board());
+                    }
+                }
+
+                // Pick a local run with a replay to race as a ghost
+                if !self.show_online && !self.endless {
+                    let rows = self.boards[self.difficulty].len();
+                    if (is_key_pressed(KeyCode::Down) || is_key_pressed(KeyCode::S)) && rows > 0 {
+                        self.highlight = Some(self.highlight.map_or(0, |row| (row + 1).min(rows - 1)));
+                    }
+                    if is_key_pressed(KeyCode::Up) || is_key_pressed(KeyCode::W) {
+                        self.highlight = self.highlight.map(|row| row.saturating_sub(1));
+                    }
+                    if is_key_pressed(KeyCode::G) {
+                        match self.highlight.and_then(|row| self.boards[self.difficulty].get(row)) {
+                            Some(entry) if entry.replay.is_some() => return LeaderboardAction::RaceGhost(entry.clone()),
+                            Some(_) => self.show_error("THAT RUN HAS NO REPLAY"),
+                            None => self.show_error("PICK A RUN WITH ↑↓ FIRST"),
+                        }
+                    }
+                }
+
+                if is_key_pressed(KeyCode::Escape) || is_key_pressed(KeyCode::Enter) {
+                    return LeaderboardAction::Back;
+                }
+                LeaderboardAction::None
+            }
+            Mode::Naming { entry, .. } => {
+                while let Some(c) = get_char_pressed() {
+                    if (c.is_ascii_alphanumeric() || c == ' ') && entry.name.chars().count() < MAX_PROFILE_NAME {
+                        entry.name.push(c.to_ascii_uppercase());
+                    }
+                }
+                if is_key_pressed(KeyCode::Backspace) {
+                    entry.name.pop();
+                }
+
+                if is_key_pressed(KeyCode::Enter) && !entry.name.trim().is_empty() {
+                    entry.name = entry.name.trim().to_string();
+                    let action = LeaderboardAction::Submit(entry.clone());
+                    self.mode = Mode::Viewing;
+                    return action;
+                }
+                LeaderboardAction::None
+            }
+        }
+    }
+
+    pub fn draw(&self) {
+        let screen_w = screen_width();
+        let screen_h = screen_height();
+
+        clear_background(Color::from_rgba(0, 0, 20, 255));
+
+        let title = match self.mode {
+            Mode::Naming { rank, .. } => format!("NEW #{} RUN!", rank + 1),
+            Mode::Viewing if self.show_online => "ONLINE LEADERBOARD".to_string(),
+            Mode::Viewing if self.endless => "ENDLESS LEADERBOARD".to_string(),
+            Mode::Viewing => "LEADERBOARD".to_string(),
+        };
+        let title_width = measure_text(&title, None, 48, 1.0).width;
+        let pulse = (self.time * 2.0).sin() * 0.2 + 0.8;
+        draw_text(&title, screen_w / 2.0 - title_width / 2.0, 70.0, 48.0, Color::from_rgba(0, 255, 255, (pulse * 255.0) as u8));
+
+        // Board selector; the difficulty is fixed while naming a new run
+        let board = if self.endless { "ENDLESS".to_string() } else { self.continent.name().to_uppercase() };
+        let board_name = format!("{} - {}", board, DIFFICULTIES[self.difficulty].name().to_uppercase());
+        let selector = match self.mode {
+            Mode::Viewing => format!("<  {}  >", board_name),
+            Mode::Naming { .. } => board_name,
+        };
+        let selector_width = measure_text(&selector, None, 26, 1.0).width;
+        draw_text(&selector, screen_w / 2.0 - selector_width / 2.0, 110.0, 26.0, Color::from_rgba(255, 200, 0, 255));
+
+        let table_x = screen_w / 2.0 - 330.0;
+        for (heading, offset) in COLUMNS {
+            // Online runs belong to players, not to profiles on this machine
+            // Endless runs are ranked on how far they got rather than their time
+            let heading = if self.endless && heading == "TIME" { "DISTANCE" } else { heading };
+            if !(self.show_online && heading == "PROFILE") {
+                draw_text(heading, table_x + offset, 155.0, 20.0, Color::from_rgba(0, 255, 255, 255));
+            }
+        }
+
+        if self.show_online {
+            self.draw_online(table_x);
+        } else {
+            self.draw_local(table_x);
+        }
+
+        if let Some((message, is_error)) = &self.message {
+            let color = if *is_error {
+                Color::from_rgba(255, 80, 80, 255)
+            } else {
+                Color::from_rgba(0, 255, 150, 255)
+            };
+            let width = measure_text(message, None, 20, 1.0).width;
+            draw_text(message, screen_w / 2.0 - width / 2.0, screen_h - 60.0, 20.0, color);
+        }
+
+        let hint = match self.mode {
+            Mode::Naming { .. } => "TYPE YOUR NAME | ENTER CONFIRM",
+            Mode::Viewing if self.show_online => "←→ DIFFICULTY | TAB LOCAL/ONLINE | ESC BACK",
+            Mode::Viewing if self.endless => "←→ DIFFICULTY | ESC BACK",
+            Mode::Viewing if self.online_available => "←→ DIFFICULTY | ↑↓ G RACE GHOST | TAB LOCAL/ONLINE | ESC BACK",
+            Mode::Viewing => "←→ DIFFICULTY | ↑↓ G RACE GHOST | ESC BACK",
+        };
+        let hint_width = measure_text(hint, None, 16, 1.0).width;
+        draw_text(hint, screen_w / 2.0 - hint_width / 2.0, screen_h - 30.0, 16.0, Color::from_rgba(200, 200, 200, 180));
+    }
+
+    fn draw_local(&self, table_x: f32) {
+        // The shown board, with the run being named slotted in at its place
+        let mut rows: Vec<&LeaderboardEntry> = self.boards[self.difficulty].iter().collect();
+        if let Mode::Naming { rank, entry } = &self.mode {
+            rows.insert((*rank).min(rows.len()), entry);
+            rows.truncate(LEADERBOARD_SIZE);
+        }
+
+        if rows.is_empty() {
+            draw_notice("NO RUNS YET");
+        }
+
+        for (i, entry) in rows.iter().enumerate() {
+            let name = match &self.mode {
+                Mode::Naming { rank, .. } if *rank == i => {
+                    let cursor = if (self.time * 3.0).sin() > 0.0 { "_" } else { " " };
+                    format!("{}{}", entry.name, cursor)
+                }
+                _ => entry.name.clone(),
+            };
+            let cells = [
+                (i + 1).to_string(),
+                name,
+                format!("{:08}", entry.score),
+                if self.endless { format!("{:.0}M", entry.distance) } else { format_time(entry.time) },
+                leaderboard::format_date(entry.date),
+                entry.profile.clone(),
+            ];
+            draw_row(table_x, 185.0 + i as f32 * ROW_HEIGHT, &cells, self.highlight == Some(i));
+        }
+    }
+
+    fn draw_online(&self, table_x: f32) {
+        let (top, around) = match &self.online {
+            OnlineBoard::Loading => return draw_notice("LOADING..."),
+            OnlineBoard::Failed(e) => return draw_notice(&format!("COULDN'T REACH THE SERVER: {}", e.to_uppercase())),
+            OnlineBoard::Loaded { top, around } => (top, around),
+        };
+        if top.is_empty() {
+            return draw_notice("NO RUNS YET");
+        }
+
+        // The top of the board, then the player's neighbourhood if they're further down
+        let below_top: Vec<&RankedEntry> = around.iter().filter(|e| e.rank > top.len()).collect();
+        let mut y = 185.0;
+        for (i, entry) in top.iter().chain(below_top.iter().copied()).enumerate() {
+            if i == top.len() {
+                draw_text("...", table_x, y, 20.0, Color::from_rgba(150, 150, 150, 255));
+                y += ROW_HEIGHT;
+            }
+            let cells = [
+                entry.rank.to_string(),
+                entry.name.clone(),
+                format!("{:08}", entry.score),
+                format_time(entry.time),
+                leaderboard::format_date(entry.date),
+            ];
+            draw_row(table_x, y, &cells, entry.name == self.player_name);
+            y += ROW_HEIGHT;
+        }
+    }
+}
+
+/// "4:05" for 245 seconds
+fn format_time(seconds: f32) -> String {
+    format!("{}:{:02}", seconds as u32 / 60, seconds as u32 % 60)
+}
+
+fn draw_notice(text: &str) {
+    let width = measure_text(text, None, 22, 1.0).width;
+    draw_text(text, screen_width() / 2.0 - width / 2.0, 200.0, 22.0, Color::from_rgba(150, 150, 150, 255));
+}
+
+/// One table row, cells in `COLUMNS` order
+fn draw_row(table_x: f32, y: f32, cells: &[String], highlighted: bool) {
+    if highlighted {
+        draw_rectangle(table_x - 10.0, y - 19.0, 680.0, ROW_HEIGHT - 2.0, Color::from_rgba(255, 215, 0, 50));
+    }
+    let color = if highlighted { Color::from_rgba(255, 215, 0, 255) } else { WHITE };
+    for (text, (_, offset)) in cells.iter().zip(COLUMNS) {
+        draw_text(text, table_x + offset, y, 20.0, color);
+    }
+}
@@ -1,10 +1,14 @@
 use macroquad::prelude::*;
 use crate::assets::Continent;
+use crate::game_mode::GameMode;
+use crate::level::{self, TimeAttackConfig, BOSS_RUSH_ROUTE, ENDLESS_LEG_DISTANCE, ENDLESS_ROUTE, MINI_BOSS_INTERVAL};
+use crate::save_system::{BossRushRecord, SaveData};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum LevelSelectAction {
     None,
-    StartLevel(Continent),
+    StartLevel(Continent, GameMode),
+    ShowLeaderboard(Continent, GameMode),
     Back,
 }
 
@@ -14,6 +18,10 @@ pub struct LevelSelectScreen {
     selected_continent: usize,
     continents: Vec<ContinentDisplay>,
     unlocked_continents: Vec<bool>,
+    mode: GameMode,
+    time_attack_bests: Vec<Option<f32>>, // Per continent, in `continents` order
+    boss_rush_unlocked: bool,             // Once Oceania has been cleared
+    boss_rush_best: Option<BossRushRecord>,
 }
 
 struct ContinentDisplay {
@@ -79,6 +87,10 @@ impl LevelSelectScreen {
             selected_continent: 0,
             continents,
             unlocked_continents,
+            mode: GameMode::Standard,
+            time_attack_bests: vec![None; 7],
+            boss_rush_unlocked: false,
+            boss_rush_best: None,
         }
     }
 
@@ -101,16 +113,36 @@ impl LevelSelectScreen {
             self.selected_continent = (self.selected_continent + 1) % self.continents.len();
         }
 
-        // Select continent
+        // Switch mode
+        if is_key_pressed(KeyCode::M) {
+            self.mode = self.mode.next();
+        }
+
+        // Select continent; endless runs and boss rushes always set off from the
+        // start of their route
         if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Space) {
-            if self.unlocked_continents[self.selected_continent] {
-                return LevelSelectAction::StartLevel(self.continents[self.selected_continent].continent);
+            let continent = match self.mode {
+                GameMode::Endless => ENDLESS_ROUTE[0],
+                GameMode::BossRush => BOSS_RUSH_ROUTE[0],
+                GameMode::Standard | GameMode::TimeAttack => self.continents[self.selected_continent].continent,
+            };
+            let unlocked = match self.mode {
+                GameMode::BossRush => self.boss_rush_unlocked,
+                _ => self.is_unlocked(continent),
+            };
+            if unlocked {
+                return LevelSelectAction::StartLevel(continent, self.mode);
             }
         }
 
+        // Leaderboard for the selected continent (or the endless one)
+        if is_key_pressed(KeyCode::L) {
+            return LevelSelectAction::ShowLeaderboard(self.continents[self.selected_continent].continent, self.mode);
+        }
+
         // Tutorial shortcut
         if is_key_pressed(KeyCode::T) {
-            return LevelSelectAction::StartLevel(Continent::Tutorial);
+            return LevelSelectAction::StartLevel(Continent::Tutorial, GameMode::Standard);
         }
 
         // Back
@@ -163,7 +195,7 @@ impl LevelSelectScreen {
         );
 
         // Controls
-        let hint = "← → SELECT | ENTER START | ESC BACK";
+        let hint = "← → SELECT | ENTER START | M MODE | L LEADERBOARD | ESC BACK";
         let hint_size = 16.0;
         let hint_width = measure_text(hint, None, hint_size as u16, 1.0).width;
 
@@ -288,8 +320,14 @@ impl LevelSelectScreen {
         let screen_w = screen_width();
         let screen_h = screen_height();
 
+        // Endless runs and boss rushes take in every continent, so they're shown
+        // in place of the selected one
         let continent = &self.continents[self.selected_continent];
-        let is_unlocked = self.unlocked_continents[self.selected_continent];
+        let (name, is_unlocked) = match self.mode {
+            GameMode::Endless => ("Endless", self.is_unlocked(ENDLESS_ROUTE[0])),
+            GameMode::BossRush => ("Boss Rush", self.boss_rush_unlocked),
+            GameMode::Standard | GameMode::TimeAttack => (continent.name, self.unlocked_continents[self.selected_continent]),
+        };
 
         // Info panel
         let panel_y = screen_h - 180.0;
@@ -315,11 +353,20 @@ impl LevelSelectScreen {
             Color::from_rgba(0, 255, 255, 255),
         );
 
+        // Mode
+        draw_text(
+            &format!("MODE: {}", self.mode.name().to_uppercase()),
+            panel_x + 15.0,
+            panel_y + 22.0,
+            16.0,
+            Color::from_rgba(255, 200, 0, 255),
+        );
+
         // Continent name
         let name_size = 32.0;
-        let name_width = measure_text(continent.name, None, name_size as u16, 1.0).width;
+        let name_width = measure_text(name, None, name_size as u16, 1.0).width;
         draw_text(
-            continent.name,
+            name,
             screen_w / 2.0 - name_width / 2.0,
             panel_y + 40.0,
             name_size,
@@ -331,11 +378,24 @@ impl LevelSelectScreen {
         );
 
         // Status
-        let status_text = if is_unlocked {
-            "PRESS ENTER TO START"
-        } else {
-            "LOCKED - COMPLETE PREVIOUS LEVELS"
+        let best = self.time_attack_bests[self.selected_continent];
+        let status_text = match (is_unlocked, self.mode, best) {
+            (false, GameMode::Endless, _) => "LOCKED - COMPLETE THE TUTORIAL".to_string(),
+            (false, GameMode::BossRush, _) => "LOCKED - CLEAR OCEANIA".to_string(),
+            (true, GameMode::BossRush, _) => match self.boss_rush_best {
+                Some(best) => format!("BEST {} - {:.0} DAMAGE TAKEN", level::format_race_time(best.time), best.damage_taken),
+                None => "PRESS ENTER TO START".to_string(),
+            },
+            (false, _, _) => "LOCKED - COMPLETE PREVIOUS LEVELS".to_string(),
+            (true, GameMode::TimeAttack, Some(best)) => {
+                let medal = TimeAttackConfig::for_continent(continent.continent)
+                    .medal(best)
+                    .map_or("NO MEDAL", |medal| medal.name());
+                format!("BEST {} - {}", level::format_race_time(best), medal.to_uppercase())
+            }
+            (true, _, _) => "PRESS ENTER TO START".to_string(),
         };
+        let status_text = status_text.as_str();
 
         let status_size = 18.0;
         let status_width = measure_text(status_text, None, status_size as u16, 1.0).width;
@@ -353,7 +413,27 @@ impl LevelSelectScreen {
 
         // Level details
         if is_unlocked {
-            let details = "5 MINUTES | 3 CHECKPOINTS | BOSS BATTLE";
+            let details = match self.mode {
+                GameMode::Standard => "5 MINUTES | 3 CHECKPOINTS | BOSS BATTLE".to_string(),
+                GameMode::TimeAttack => {
+                    let config = TimeAttackConfig::for_continent(continent.continent);
+                    let targets: Vec<String> = config
+                        .targets()
+                        .iter()
+                        .map(|(medal, time)| format!("{} {}", medal.name().to_uppercase(), level::format_race_time(*time)))
+                        .collect();
+                    format!("{:.0}M | {}", config.distance, targets.join(" | "))
+                }
+                GameMode::Endless => format!(
+                    "NO TIMER | A NEW CONTINENT EVERY {:.0}M | MINI-BOSS EVERY {:.0}M",
+                    ENDLESS_LEG_DISTANCE, MINI_BOSS_INTERVAL
+                ),
+                GameMode::BossRush => format!(
+                    "{} BOSSES BACK TO BACK | HEALTH AND WEAPONS CARRY OVER",
+                    BOSS_RUSH_ROUTE.len()
+                ),
+            };
+            let details = details.as_str();
             let details_size = 14.0;
             let details_width = measure_text(details, None, details_size as u16, 1.0).width;
             draw_text(
@@ -375,6 +455,28 @@ impl LevelSelectScreen {
         }
     }
 
+    fn is_unlocked(&self, continent: Continent) -> bool {
+        self.continents
+            .iter()
+            .zip(&self.unlocked_continents)
+            .any(|(display, unlocked)| display.continent == continent && *unlocked)
+    }
+
+    /// Best time attack times, for the selected continent's info panel
+    pub fn set_time_attack_bests(&mut self, save: &SaveData) {
+        self.time_attack_bests = self
+            .continents
+            .iter()
+            .map(|display| save.get_best_time(&display.continent, GameMode::TimeAttack))
+            .collect();
+    }
+
+    /// Whether the boss rush is open yet, and its best clear
+    pub fn set_boss_rush(&mut self, save: &SaveData) {
+        self.boss_rush_unlocked = save.has_cleared(&Continent::Oceania);
+        self.boss_rush_best = save.boss_rush_best;
+    }
+
     /// Sync unlocked status with save data
     pub fn sync_with_save(&mut self, unlocked_continents: &[String]) {
         for (i, display) in self.continents.iter().enumerate() {
@@ -3,14 +3,19 @@ use macroquad::prelude::*;
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MenuAction {
     None,
+    Resume,
     Start,
+    Profiles,
+    Achievements,
+    Statistics,
     Options,
 }
 
 pub struct MainMenu {
     time: f32,
     selected_index: usize,
-    menu_items: Vec<String>,
+    menu_items: Vec<(String, MenuAction)>,
+    profile_name: String,
 }
 
 impl MainMenu {
@@ -19,9 +24,29 @@ impl MainMenu {
             time: 0.0,
             selected_index: 0,
             menu_items: vec![
-                "START GAME".to_string(),
-                "OPTIONS".to_string(),
+                ("START GAME".to_string(), MenuAction::Start),
+                ("CHANGE PROFILE".to_string(), MenuAction::Profiles),
+                ("ACHIEVEMENTS".to_string(), MenuAction::Achievements),
+                ("STATISTICS".to_string(), MenuAction::Statistics),
+                ("OPTIONS".to_string(), MenuAction::Options),
             ],
+            profile_name: String::new(),
+        }
+    }
+
+    /// Name of the active profile, shown under the title
+    pub fn set_profile_name(&mut self, name: &str) {
+        self.profile_name = name.to_string();
+    }
+
+    /// Show "RESUME RUN" at the top of the menu while a suspended run exists
+    pub fn set_resume_available(&mut self, available: bool) {
+        let shown = self.menu_items.first().is_some_and(|(_, action)| *action == MenuAction::Resume);
+        if available && !shown {
+            self.menu_items.insert(0, ("RESUME RUN".to_string(), MenuAction::Resume));
+        } else if !available && shown {
+            self.menu_items.remove(0);
+            self.selected_index = self.selected_index.saturating_sub(1);
         }
     }
 
@@ -42,11 +67,9 @@ impl MainMenu {
         }
 
         if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Space) {
-            return match self.selected_index {
-                0 => MenuAction::Start,
-                1 => MenuAction::Options,
-                _ => MenuAction::None,
-            };
+            return self.menu_items
+                .get(self.selected_index)
+                .map_or(MenuAction::None, |(_, action)| *action);
         }
 
         MenuAction::None
@@ -74,11 +97,21 @@ impl MainMenu {
             Color::from_rgba(0, 255, 255, (pulse * 255.0) as u8),
         );
 
+        let profile = format!("PROFILE: {}", self.profile_name);
+        let profile_width = measure_text(&profile, None, 20, 1.0).width;
+        draw_text(
+            &profile,
+            screen_w / 2.0 - profile_width / 2.0,
+            screen_h / 3.0 + 40.0,
+            20.0,
+            Color::from_rgba(255, 200, 0, 220),
+        );
+
         // Menu items
-        let menu_start_y = screen_h / 2.0;
-        let item_spacing = 60.0;
+        let menu_start_y = screen_h / 2.0 - 30.0;
+        let item_spacing = 45.0;
 
-        for (i, item) in self.menu_items.iter().enumerate() {
+        for (i, (item, _)) in self.menu_items.iter().enumerate() {
             let y = menu_start_y + i as f32 * item_spacing;
             let is_selected = i == self.selected_index;
 
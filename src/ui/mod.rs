@@ -3,9 +3,17 @@ pub mod main_menu;
 pub mod options;
 pub mod level_select;
 pub mod tutorial_instructions;
+pub mod profile_select;
+pub mod achievements;
+pub mod statistics;
+pub mod leaderboard;
 
 pub use splash::SplashScreen;
 pub use main_menu::MainMenu;
 pub use options::OptionsMenu;
 pub use level_select::LevelSelectScreen;
 pub use tutorial_instructions::TutorialInstructions;
+pub use profile_select::ProfileSelectScreen;
+pub use achievements::AchievementsScreen;
+pub use statistics::StatisticsScreen;
+pub use leaderboard::LeaderboardScreen;
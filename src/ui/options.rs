@@ -1,5 +1,8 @@
 use macroquad::prelude::*;
 
+/// Respawn countdown choices (seconds), cycled with left/right
+const RESPAWN_COUNTDOWNS: [f32; 5] = [3.0, 5.0, 10.0, 30.0, 60.0];
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum OptionsAction {
     None,
@@ -12,6 +15,9 @@ pub struct OptionsMenu {
     sound_volume: f32,
     music_volume: f32,
     difficulty: usize, // 0=Easy, 1=Normal, 2=Hard
+    adaptive_difficulty: bool,
+    respawn_countdown: f32,
+    ghost: bool,
 }
 
 impl OptionsMenu {
@@ -22,6 +28,9 @@ impl OptionsMenu {
             sound_volume: 100.0,
             music_volume: 100.0,
             difficulty: 1, // Default to Normal
+            adaptive_difficulty: false,
+            respawn_countdown: RESPAWN_COUNTDOWNS[1],
+            ghost: false,
         }
     }
 
@@ -36,7 +45,7 @@ impl OptionsMenu {
         }
 
         if is_key_pressed(KeyCode::Down) || is_key_pressed(KeyCode::S) {
-            if self.selected_index < 3 { // 4 options (0-3)
+            if self.selected_index < 6 { // 7 options (0-6)
                 self.selected_index += 1;
             }
         }
@@ -71,11 +80,34 @@ impl OptionsMenu {
                     }
                 }
             }
+            3 => { // Adaptive Difficulty
+                if is_key_pressed(KeyCode::Left) || is_key_pressed(KeyCode::A)
+                    || is_key_pressed(KeyCode::Right) || is_key_pressed(KeyCode::D)
+                {
+                    self.adaptive_difficulty = !self.adaptive_difficulty;
+                }
+            }
+            4 => { // Respawn Countdown
+                let current = RESPAWN_COUNTDOWNS.iter().position(|&s| s >= self.respawn_countdown).unwrap_or(1);
+                if is_key_pressed(KeyCode::Left) || is_key_pressed(KeyCode::A) {
+                    self.respawn_countdown = RESPAWN_COUNTDOWNS[current.saturating_sub(1)];
+                }
+                if is_key_pressed(KeyCode::Right) || is_key_pressed(KeyCode::D) {
+                    self.respawn_countdown = RESPAWN_COUNTDOWNS[(current + 1).min(RESPAWN_COUNTDOWNS.len() - 1)];
+                }
+            }
+            5 => { // Ghost
+                if is_key_pressed(KeyCode::Left) || is_key_pressed(KeyCode::A)
+                    || is_key_pressed(KeyCode::Right) || is_key_pressed(KeyCode::D)
+                {
+                    self.ghost = !self.ghost;
+                }
+            }
             _ => {}
         }
 
         // Back button
-        if self.selected_index == 3 && (is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Space)) {
+        if self.selected_index == 6 && (is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Space)) {
             return OptionsAction::Back;
         }
 
@@ -107,8 +139,8 @@ impl OptionsMenu {
         );
 
         // Options
-        let start_y = 200.0;
-        let spacing = 80.0;
+        let start_y = 180.0;
+        let spacing = 55.0;
 
         // Sound Volume
         self.draw_option(
@@ -143,9 +175,36 @@ impl OptionsMenu {
             0.0,
         );
 
+        // Adaptive Difficulty
+        self.draw_option(
+            3,
+            "ADAPTIVE",
+            if self.adaptive_difficulty { "ON" } else { "OFF" },
+            start_y + spacing * 3.0,
+            0.0,
+        );
+
+        // Respawn Countdown
+        self.draw_option(
+            4,
+            "RESPAWN TIMER",
+            &format!("{:.0}s", self.respawn_countdown),
+            start_y + spacing * 4.0,
+            0.0,
+        );
+
+        // Ghost
+        self.draw_option(
+            5,
+            "GHOST",
+            if self.ghost { "ON" } else { "OFF" },
+            start_y + spacing * 5.0,
+            0.0,
+        );
+
         // Back button
-        let back_y = start_y + spacing * 3.0;
-        let is_selected = self.selected_index == 3;
+        let back_y = start_y + spacing * 6.0;
+        let is_selected = self.selected_index == 6;
 
         if is_selected {
             let pulse = (self.time * 8.0).sin() * 0.5 + 0.5;
@@ -287,9 +346,24 @@ impl OptionsMenu {
         self.difficulty
     }
 
-    pub fn set_from_settings(&mut self, sound_volume: f32, music_volume: f32, difficulty: usize) {
+    pub fn get_adaptive_difficulty(&self) -> bool {
+        self.adaptive_difficulty
+    }
+
+    pub fn get_respawn_countdown(&self) -> f32 {
+        self.respawn_countdown
+    }
+
+    pub fn get_ghost(&self) -> bool {
+        self.ghost
+    }
+
+    pub fn set_from_settings(&mut self, sound_volume: f32, music_volume: f32, difficulty: usize, adaptive_difficulty: bool, respawn_countdown: f32, ghost: bool) {
         self.sound_volume = sound_volume;
         self.music_volume = music_volume;
         self.difficulty = difficulty;
+        self.adaptive_difficulty = adaptive_difficulty;
+        self.respawn_countdown = respawn_countdown;
+        self.ghost = ghost;
     }
 }
@@ -0,0 +1,288 @@
+use macroquad::prelude::*;
+use crate::profile_export::ImportMode;
+use crate::profiles::{Profile, MAX_PROFILE_NAME};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProfileAction {
+    None,
+    Select(String),         // Profile id
+    Create(String),         // Name as typed
+    Rename(String, String), // Profile id, new name
+    Delete(String),         // Profile id
+    Export(String),         // Profile id
+    Import(String),         // Look for an export to import into this profile id
+    ConfirmImport(String, ImportMode),
+    Back,
+}
+
+enum Mode {
+    Browse,
+    Naming { renaming: Option<String>, name: String },
+    ConfirmDelete(String),
+    ImportPreview { target: String, name: String, summary: String },
+}
+
+pub struct ProfileSelectScreen {
+    time: f32,
+    selected_index: usize, // profiles.len() is the "NEW PROFILE" row
+    profiles: Vec<Profile>,
+    active_id: String,
+    mode: Mode,
+    message: Option<(String, bool, f32)>, // Text, whether it's an error, seconds left on screen
+}
+
+impl ProfileSelectScreen {
+    pub fn new() -> Self {
+        Self {
+            time: 0.0,
+            selected_index: 0,
+            profiles: Vec::new(),
+            active_id: String::new(),
+            mode: Mode::Browse,
+            message: None,
+        }
+    }
+
+    /// Refresh the list after profiles change, keeping the cursor on the active one
+    pub fn set_profiles(&mut self, profiles: &[Profile], active_id: &str) {
+        self.profiles = profiles.to_vec();
+        self.active_id = active_id.to_string();
+        self.selected_index = self.profiles.iter().position(|p| p.id == active_id).unwrap_or(0);
+        self.mode = Mode::Browse;
+    }
+
+    /// Show feedback under the list for a few seconds
+    pub fn show_message(&mut self, message: &str) {
+        self.message = Some((message.to_string(), false, 3.0));
+    }
+
+    /// Show a problem (such as a rejected name) under the list for a few seconds
+    pub fn show_error(&mut self, message: &str) {
+        self.message = Some((message.to_string(), true, 3.0));
+    }
+
+    /// Ask whether to merge a found export into the target profile or overwrite it
+    pub fn show_import_preview(&mut self, target_id: &str, export_name: &str, summary: &str) {
+        self.mode = Mode::ImportPreview {
+            target: target_id.to_string(),
+            name: export_name.to_string(),
+            summary: summary.to_string(),
+        };
+    }
+
+    pub fn update(&mut self, dt: f32) -> ProfileAction {
+        self.time += dt;
+        if let Some((_, _, remaining)) = &mut self.message {
+            *remaining -= dt;
+            if *remaining <= 0.0 {
+                self.message = None;
+            }
+        }
+
+        match &mut self.mode {
+            Mode::Browse => self.update_browse(),
+            Mode::Naming { renaming, name } => {
+                while let Some(c) = get_char_pressed() {
+                    if (c.is_ascii_alphanumeric() || c == ' ') && name.chars().count() < MAX_PROFILE_NAME {
+                        name.push(c.to_ascii_uppercase());
+                    }
+                }
+                if is_key_pressed(KeyCode::Backspace) {
+                    name.pop();
+                }
+
+                if is_key_pressed(KeyCode::Enter) {
+                    let action = match renaming.take() {
+                        Some(id) => ProfileAction::Rename(id, name.clone()),
+                        None => ProfileAction::Create(name.clone()),
+                    };
+                    self.mode = Mode::Browse;
+                    return action;
+                }
+                if is_key_pressed(KeyCode::Escape) {
+                    self.mode = Mode::Browse;
+                }
+                ProfileAction::None
+            }
+            Mode::ConfirmDelete(id) => {
+                if is_key_pressed(KeyCode::Y) || is_key_pressed(KeyCode::Enter) {
+                    let action = ProfileAction::Delete(id.clone());
+                    self.mode = Mode::Browse;
+                    return action;
+                }
+                if is_key_pressed(KeyCode::N) || is_key_pressed(KeyCode::Escape) {
+                    self.mode = Mode::Browse;
+                }
+                ProfileAction::None
+            }
+            Mode::ImportPreview { target, .. } => {
+                let mode = if is_key_pressed(KeyCode::M) {
+                    Some(ImportMode::Merge)
+                } else if is_key_pressed(KeyCode::O) {
+                    Some(ImportMode::Overwrite)
+                } else {
+                    None
+                };
+                if let Some(mode) = mode {
+                    let action = ProfileAction::ConfirmImport(target.clone(), mode);
+                    self.mode = Mode::Browse;
+                    return action;
+                }
+                if is_key_pressed(KeyCode::Escape) {
+                    self.mode = Mode::Browse;
+                }
+                ProfileAction::None
+            }
+        }
+    }
+
+    fn update_browse(&mut self) -> ProfileAction {
+        if (is_key_pressed(KeyCode::Up) || is_key_pressed(KeyCode::W)) && self.selected_index > 0 {
+            self.selected_index -= 1;
+        }
+        if (is_key_pressed(KeyCode::Down) || is_key_pressed(KeyCode::S)) && self.selected_index < self.profiles.len() {
+            self.selected_index += 1;
+        }
+
+        let selected = self.profiles.get(self.selected_index).cloned();
+
+        if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Space) {
+            return match selected {
+                Some(profile) => ProfileAction::Select(profile.id),
+                None => {
+                    self.start_naming(None, String::new());
+                    ProfileAction::None
+                }
+            };
+        }
+
+        if let Some(profile) = selected {
+            if is_key_pressed(KeyCode::R) {
+                self.start_naming(Some(profile.id), profile.name);
+            } else if is_key_pressed(KeyCode::Delete) || is_key_pressed(KeyCode::X) {
+                self.mode = Mode::ConfirmDelete(profile.id);
+            } else if is_key_pressed(KeyCode::E) {
+                return ProfileAction::Export(profile.id);
+            } else if is_key_pressed(KeyCode::I) {
+                return ProfileAction::Import(profile.id);
+            }
+        }
+
+        if is_key_pressed(KeyCode::Escape) {
+            return ProfileAction::Back;
+        }
+        ProfileAction::None
+    }
+
+    fn start_naming(&mut self, renaming: Option<String>, name: String) {
+        // Drop the key that opened the prompt so it isn't typed into the name
+        while get_char_pressed().is_some() {}
+        self.mode = Mode::Naming { renaming, name };
+    }
+
+    pub fn draw(&self) {
+        let screen_w = screen_width();
+        let screen_h = screen_height();
+
+        clear_background(Color::from_rgba(0, 0, 20, 255));
+
+        let title = "SELECT PROFILE";
+        let title_width = measure_text(title, None, 48, 1.0).width;
+        let pulse = (self.time * 2.0).sin() * 0.2 + 0.8;
+        draw_text(
+            title,
+            screen_w / 2.0 - title_width / 2.0,
+            screen_h * 0.18,
+            48.0,
+            Color::from_rgba(0, 255, 255, (pulse * 255.0) as u8),
+        );
+
+        // Profiles, then the row for adding one
+        let start_y = screen_h * 0.32;
+        let spacing = 40.0;
+        for i in 0..=self.profiles.len() {
+            let y = start_y + i as f32 * spacing;
+            let is_selected = i == self.selected_index;
+            let label = match self.profiles.get(i) {
+                Some(profile) if profile.id == self.active_id => format!("{}  (CURRENT)", profile.name),
+                Some(profile) => profile.name.clone(),
+                None => "+ NEW PROFILE".to_string(),
+            };
+
+            let size = if is_selected { 30.0 } else { 26.0 };
+            let color = if is_selected {
+                Color::from_rgba(0, 255, 255, 255)
+            } else {
+                Color::from_rgba(150, 150, 150, 200)
+            };
+            let width = measure_text(&label, None, size as u16, 1.0).width;
+            draw_text(&label, screen_w / 2.0 - width / 2.0, y, size, color);
+
+            if is_selected {
+                let indicator_pulse = (self.time * 8.0).sin() * 0.5 + 0.5;
+                draw_text(
+                    ">",
+                    screen_w / 2.0 - 200.0,
+                    y,
+                    30.0,
+                    Color::from_rgba(0, 255, 255, (indicator_pulse * 255.0) as u8),
+                );
+            }
+        }
+
+        // Name prompt / delete confirmation over the list
+        match &self.mode {
+            Mode::Browse => {}
+            Mode::Naming { renaming, name } => {
+                let prompt = if renaming.is_some() { "RENAME PROFILE" } else { "NEW PROFILE NAME" };
+                let cursor = if (self.time * 3.0).sin() > 0.0 { "_" } else { " " };
+                self.draw_dialog(prompt, &format!("{}{}", name, cursor), "ENTER CONFIRM | ESC CANCEL");
+            }
+            Mode::ConfirmDelete(id) => {
+                let name = self.profiles.iter().find(|p| &p.id == id).map_or("", |p| p.name.as_str());
+                self.draw_dialog(&format!("DELETE {}?", name), "ALL PROGRESS WILL BE LOST", "Y DELETE | N CANCEL");
+            }
+            Mode::ImportPreview { target, name, summary } => {
+                let into = self.profiles.iter().find(|p| &p.id == target).map_or("", |p| p.name.as_str());
+                self.draw_dialog(
+                    &format!("IMPORT {} INTO {}?", name, into),
+                    summary,
+                    "M MERGE (KEEP BEST) | O OVERWRITE | ESC CANCEL",
+                );
+            }
+        }
+
+        if let Some((message, is_error, _)) = &self.message {
+            let color = if *is_error {
+                Color::from_rgba(255, 80, 80, 255)
+            } else {
+                Color::from_rgba(0, 255, 150, 255)
+            };
+            let width = measure_text(message, None, 20, 1.0).width;
+            draw_text(message, screen_w / 2.0 - width / 2.0, screen_h - 95.0, 20.0, color);
+        }
+
+        let hint = "ENTER SELECT | R RENAME | X DELETE | E EXPORT | I IMPORT | ESC BACK";
+        let hint_width = measure_text(hint, None, 16, 1.0).width;
+        draw_text(hint, screen_w / 2.0 - hint_width / 2.0, screen_h - 60.0, 16.0, Color::from_rgba(200, 200, 200, 180));
+    }
+
+    fn draw_dialog(&self, title: &str, body: &str, hint: &str) {
+        let screen_w = screen_width();
+        let screen_h = screen_height();
+        let (w, h) = (420.0, 150.0);
+        let (x, y) = (screen_w / 2.0 - w / 2.0, screen_h / 2.0 - h / 2.0);
+
+        draw_rectangle(x, y, w, h, Color::from_rgba(0, 0, 30, 240));
+        draw_rectangle_lines(x, y, w, h, 2.0, Color::from_rgba(0, 255, 255, 200));
+
+        for (text, size, offset, color) in [
+            (title, 26.0, 40.0, Color::from_rgba(0, 255, 255, 255)),
+            (body, 30.0, 85.0, WHITE),
+            (hint, 16.0, 125.0, Color::from_rgba(200, 200, 200, 180)),
+        ] {
+            let width = measure_text(text, None, size as u16, 1.0).width;
+            draw_text(text, screen_w / 2.0 - width / 2.0, y + offset, size, color);
+        }
+    }
+}
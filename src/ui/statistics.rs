@@ -0,0 +1,167 @@
+use macroquad::prelude::*;
+use crate::assets::Continent;
+use crate::save_system::SaveData;
+use crate::stats::ContinentStats;
+
+/// Most causes of death listed on a page
+const MAX_DEATH_CAUSES: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatisticsAction {
+    None,
+    Back,
+}
+
+/// One page of the screen: lifetime totals or a single continent
+struct StatsPage {
+    title: String,
+    stats: ContinentStats,
+}
+
+pub struct StatisticsScreen {
+    time: f32,
+    page: usize,
+    pages: Vec<StatsPage>,
+    play_time: f32,
+    boss_kills: u32,
+}
+
+impl StatisticsScreen {
+    pub fn new() -> Self {
+        Self {
+            time: 0.0,
+            page: 0,
+            pages: Vec::new(),
+            play_time: 0.0,
+            boss_kills: 0,
+        }
+    }
+
+    /// Show the active profile's statistics, starting on the lifetime page
+    pub fn set_save(&mut self, save: &SaveData) {
+        self.pages = vec![StatsPage {
+            title: "LIFETIME".to_string(),
+            stats: save.stats.lifetime(),
+        }];
+        self.pages.extend(Continent::all().into_iter().map(|continent| StatsPage {
+            title: continent.name().to_uppercase(),
+            stats: save.stats.continent(continent),
+        }));
+        self.play_time = save.total_play_time;
+        self.boss_kills = save.total_boss_kills;
+        self.page = 0;
+    }
+
+    pub fn update(&mut self, dt: f32) -> StatisticsAction {
+        self.time += dt;
+
+        if (is_key_pressed(KeyCode::Left) || is_key_pressed(KeyCode::A)) && self.page > 0 {
+            self.page -= 1;
+        }
+        if (is_key_pressed(KeyCode::Right) || is_key_pressed(KeyCode::D)) && self.page + 1 < self.pages.len() {
+            self.page += 1;
+        }
+
+        if is_key_pressed(KeyCode::Escape) || is_key_pressed(KeyCode::Enter) {
+            return StatisticsAction::Back;
+        }
+        StatisticsAction::None
+    }
+
+    pub fn draw(&self) {
+        let screen_w = screen_width();
+        let screen_h = screen_height();
+
+        clear_background(Color::from_rgba(0, 0, 20, 255));
+
+        let title = "STATISTICS";
+        let title_width = measure_text(title, None, 48, 1.0).width;
+        let pulse = (self.time * 2.0).sin() * 0.2 + 0.8;
+        draw_text(title, screen_w / 2.0 - title_width / 2.0, 70.0, 48.0, Color::from_rgba(0, 255, 255, (pulse * 255.0) as u8));
+
+        let Some(page) = self.pages.get(self.page) else {
+            return;
+        };
+
+        // Page selector
+        let selector = format!("<  {}  >", page.title);
+        let selector_width = measure_text(&selector, None, 26, 1.0).width;
+        draw_text(&selector, screen_w / 2.0 - selector_width / 2.0, 110.0, 26.0, Color::from_rgba(255, 200, 0, 255));
+
+        let stats = &page.stats;
+        let left_x = screen_w / 2.0 - 340.0;
+        let right_x = screen_w / 2.0 + 20.0;
+
+        // Left column: runs, rings and (on the lifetime page) overall totals
+        let mut rows = vec![
+            ("ATTEMPTS".to_string(), stats.attempts.to_string()),
+            ("COMPLETIONS".to_string(), stats.completions.to_string()),
+            ("DEATHS".to_string(), stats.total_deaths().to_string()),
+            ("DISTANCE".to_string(), format!("{:.1} KM", stats.distance / 1000.0)),
+            ("RINGS".to_string(), format!("{} HIT / {} MISSED", stats.rings_hit, stats.rings_missed)),
+            ("RING ACCURACY".to_string(), percentage(stats.ring_accuracy())),
+            ("POWERUPS".to_string(), stats.total_powerups().to_string()),
+        ];
+        if self.page == 0 {
+            let minutes = (self.play_time / 60.0) as u32;
+            rows.push(("PLAY TIME".to_string(), format!("{}H {:02}M", minutes / 60, minutes % 60)));
+            rows.push(("BOSSES DEFEATED".to_string(), self.boss_kills.to_string()));
+        }
+        let overview_bottom = draw_section(left_x, 160.0, "OVERVIEW", &rows);
+
+        // Right column: what killed you, how well you shoot, what you picked up
+        let mut deaths: Vec<(String, String)> = stats
+            .deaths_by_cause()
+            .into_iter()
+            .take(MAX_DEATH_CAUSES)
+            .map(|(cause, n)| (cause.to_uppercase(), n.to_string()))
+            .collect();
+        if deaths.is_empty() {
+            deaths.push(("NONE YET".to_string(), String::new()));
+        }
+        let deaths_bottom = draw_section(right_x, 160.0, "DEATHS BY CAUSE", &deaths);
+
+        let mut weapons: Vec<(String, String)> = stats
+            .weapons
+            .iter()
+            .map(|(weapon, w)| (weapon.to_uppercase(), format!("{} ({}/{})", percentage(w.accuracy()), w.landed, w.fired)))
+            .collect();
+        weapons.sort();
+        if weapons.is_empty() {
+            weapons.push(("NO SHOTS FIRED".to_string(), String::new()));
+        }
+        draw_section(right_x, deaths_bottom + 15.0, "ACCURACY", &weapons);
+
+        let mut powerups: Vec<(String, String)> = stats
+            .powerups
+            .iter()
+            .map(|(powerup, n)| (powerup.to_uppercase(), n.to_string()))
+            .collect();
+        powerups.sort();
+        if !powerups.is_empty() {
+            draw_section(left_x, overview_bottom + 15.0, "POWERUPS BY TYPE", &powerups);
+        }
+
+        let hint = "←→ CONTINENT | ESC BACK";
+        let hint_width = measure_text(hint, None, 16, 1.0).width;
+        draw_text(hint, screen_w / 2.0 - hint_width / 2.0, screen_h - 30.0, 16.0, Color::from_rgba(200, 200, 200, 180));
+    }
+}
+
+/// "75%" or "-" when there's nothing to measure yet
+fn percentage(fraction: Option<f32>) -> String {
+    fraction.map_or("-".to_string(), |f| format!("{:.0}%", f * 100.0))
+}
+
+/// Heading plus label/value rows; returns the y below the last row
+fn draw_section(x: f32, y: f32, heading: &str, rows: &[(String, String)]) -> f32 {
+    draw_text(heading, x, y, 22.0, Color::from_rgba(0, 255, 255, 255));
+    let mut y = y + 24.0;
+    for (label, value) in rows {
+        draw_text(label, x, y, 18.0, Color::from_rgba(180, 180, 180, 255));
+        let value_width = measure_text(value, None, 18, 1.0).width;
+        draw_text(value, x + 320.0 - value_width, y, 18.0, WHITE);
+        y += 20.0;
+    }
+    y
+}
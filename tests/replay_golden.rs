@@ -0,0 +1,21 @@
+// Golden values for a recorded replay. The leaderboard server must replay runs
+// recorded in the browser to the same result natively, so any change in how the
+// simulation steps (or in the math it uses) shows up here first. Re-record the
+// replay and its values only when gameplay is meant to change.
+use glidewars::assets::Continent;
+use glidewars::replay::Replay;
+use glidewars::simulation::{Phase, Simulation};
+use macroquad::prelude::vec3;
+
+#[test]
+fn test_recorded_run_replays_to_golden_result() {
+    let replay = Replay::from_text(include_str!("data/north_america_2024.replay")).unwrap();
+    assert_eq!(replay.continent, Continent::NorthAmerica);
+    assert_eq!(replay.seed, 2024);
+
+    let sim = Simulation::play(&replay).unwrap();
+    assert_eq!(sim.phase(), Phase::GameOver);
+    assert_eq!(sim.score.total(), 4812);
+    assert_eq!(sim.level.elapsed_time(), 128.07951);
+    assert_eq!(sim.player.position(), vec3(4.4881516, -0.89334404, 1480.2805));
+}